@@ -0,0 +1,120 @@
+//! Allocations and time to parse class files, borrowing from the bytes and
+//! with `into_owned`, that copies what the parser used to copy: the
+//! CONSTANT_Utf8 bytes and strings, the bytecode and the non-standard
+//! attributes. `skip_code` leaves out the Code of methods, `lazy` only
+//! parses the header, constant pool and interfaces and `reader` parses from
+//! a `Read` one structure at a time.
+//!
+//! cargo bench --bench parse -- [file.class | directory]...
+//!
+//! Without arguments parses tmp/A.class. Directories are searched for class
+//! files recursively, ex.: an extracted JDK jmods or dependency jars.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use std::{env, fs, io};
+use bytecoder::parser::attribute_decoder::AttributeDecoders;
+use bytecoder::parser::ParseOptions;
+use bytecoder::{parse_class_file, parse_class_file_from_reader, parse_class_file_lazy, parse_class_file_with_options};
+
+const ITERATIONS: usize = 20;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+struct Measure {
+    allocations: usize,
+    allocated_bytes: usize,
+    elapsed: Duration
+}
+
+fn main() {
+    let paths: Vec<PathBuf> = env::args().skip(1).filter(|arg| !arg.starts_with("--")).map(PathBuf::from).collect();
+    let paths = if paths.is_empty() { vec![PathBuf::from("tmp/A.class")] } else { paths };
+    let mut class_files: Vec<Vec<u8>> = Vec::new();
+    for path in paths.iter() {
+        read_class_files(path, &mut class_files).expect("Failed to read class files");
+    }
+    let bytes: usize = class_files.iter().map(|class_file| class_file.len()).sum();
+    println!("{} class files, {} bytes, {} iterations", class_files.len(), bytes, ITERATIONS);
+
+    let borrowed = measure(&class_files, |bytecode| {
+        parse_class_file(bytecode).map(|class_file| class_file.cp_info.len()).unwrap_or(0)
+    });
+    let owned = measure(&class_files, |bytecode| {
+        parse_class_file(bytecode).map(|class_file| class_file.into_owned().cp_info.len()).unwrap_or(0)
+    });
+    let decoders = AttributeDecoders::new();
+    let skip_code = ParseOptions { skip_code: true, ..ParseOptions::default() };
+    let without_code = measure(&class_files, |bytecode| {
+        parse_class_file_with_options(bytecode, &decoders, &skip_code).map(|class_file| class_file.cp_info.len()).unwrap_or(0)
+    });
+    let lazy = measure(&class_files, |bytecode| {
+        parse_class_file_lazy(bytecode, &decoders, &ParseOptions::default()).map(|class_file| class_file.cp_info.len()).unwrap_or(0)
+    });
+    let reader = measure(&class_files, |bytecode| {
+        parse_class_file_from_reader(bytecode).map(|class_file| class_file.cp_info.len()).unwrap_or(0)
+    });
+    println!("{:<12}{:>16}{:>16}{:>12}", "", "allocations", "bytes", "ms");
+    for (name, m) in [("borrowed", borrowed), ("into_owned", owned), ("skip_code", without_code), ("lazy", lazy), ("reader", reader)] {
+        let per_iteration = |n: usize| n / ITERATIONS;
+        println!("{:<12}{:>16}{:>16}{:>12.1}", name, per_iteration(m.allocations), per_iteration(m.allocated_bytes),
+                 m.elapsed.as_secs_f64() * 1000.0 / ITERATIONS as f64);
+    }
+}
+
+/// Parse every class file `ITERATIONS` times.
+fn measure<F: Fn(&[u8]) -> usize>(class_files: &[Vec<u8>], parse: F) -> Measure {
+    let mut checksum = 0;
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        for class_file in class_files.iter() {
+            checksum += parse(class_file);
+        }
+    }
+    let elapsed = start.elapsed();
+    std::hint::black_box(checksum);
+    Measure {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_bytes,
+        elapsed
+    }
+}
+
+fn read_class_files(path: &Path, class_files: &mut Vec<Vec<u8>>) -> io::Result<()> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            read_class_files(&entry?.path(), class_files)?;
+        }
+    } else if path.extension().is_some_and(|extension| extension == "class") {
+        class_files.push(fs::read(path)?);
+    }
+    Ok(())
+}
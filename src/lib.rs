@@ -1,8 +1,47 @@
+use std::io::Read;
 use crate::parser::ast::ClassFile;
+use crate::parser::attribute_decoder::AttributeDecoders;
+use crate::parser::error::ClassFormatError;
+use crate::parser::lazy::LazyClassFile;
+use crate::parser::stream::ClassEventReader;
+use crate::parser::validate::Diagnostic;
+use crate::parser::ParseOptions;
 
-mod parser;
+pub mod parser;
 
-pub fn parse_class_file(bytecode: &Vec<u8>) -> ClassFile {
+pub fn parse_class_file(bytecode: &[u8]) -> Result<ClassFile<'_>, ClassFormatError> {
     parser::parse_class_file(bytecode)
 }
 
+/// Parse a class file decoding its non-standard attributes with `decoders`.
+pub fn parse_class_file_with_decoders<'a>(bytecode: &'a [u8], decoders: &AttributeDecoders) -> Result<ClassFile<'a>, ClassFormatError> {
+    parser::parse_class_file_with_decoders(bytecode, decoders)
+}
+
+/// Parse a class file leaving out the attributes skipped by `options`.
+pub fn parse_class_file_with_options<'a>(bytecode: &'a [u8], decoders: &AttributeDecoders, options: &ParseOptions) -> Result<ClassFile<'a>, ClassFormatError> {
+    parser::parse_class_file_with_options(bytecode, decoders, options)
+}
+
+/// Parse the header, constant pool and interfaces of a class file, its
+/// fields, methods and attributes are parsed when accessed.
+pub fn parse_class_file_lazy<'a>(bytecode: &'a [u8], decoders: &'a AttributeDecoders, options: &ParseOptions) -> Result<LazyClassFile<'a>, ClassFormatError> {
+    parser::lazy::parse_class_file_lazy(bytecode, decoders, options)
+}
+
+/// Parse a class file from `reader` without reading it whole first.
+pub fn parse_class_file_from_reader<R: Read>(reader: R) -> Result<ClassFile<'static>, ClassFormatError> {
+    parser::stream::parse_class_file_from_reader(reader)
+}
+
+/// Read the structures of a class file from `reader` as a sequence of
+/// events, without building the class file.
+pub fn read_class_events<'d, R: Read>(reader: R, decoders: &'d AttributeDecoders, options: &ParseOptions) -> ClassEventReader<'d, R> {
+    ClassEventReader::new(reader, decoders, options)
+}
+
+/// Check the format of a parsed class file per JVMS 4.8, ex.: constant pool
+/// references, access flags and the Code of methods.
+pub fn validate(class_file: &ClassFile) -> Vec<Diagnostic> {
+    parser::validate::validate(class_file)
+}
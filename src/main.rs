@@ -1,16 +1,79 @@
-#![allow(dead_code)]
+#![allow(dead_code, clippy::enum_variant_names)]
 
-use std::{env, fs};
-use crate::parser::parse_class_file;
-use crate::pretty_print::pretty_print_text;
+use std::{env, process};
+use std::fs::File;
+use crate::parser::stream::parse_class_file_from_reader;
+use crate::parser::validate::validate;
+use crate::pretty_print::{pretty_print_text, PrettyPrintOptions};
 
 mod parser;
 mod pretty_print;
 mod pretty_print_helper;
 
 fn main() {
-    let class_filename = env::args().nth(1).expect("Missing class filename argument");
-    let class_file_contents = fs::read(class_filename).expect("Failed to read class filename");
-    let class_file = parse_class_file(&class_file_contents);
-    pretty_print_text(&class_file);
+    let (flags, filenames): (Vec<String>, Vec<String>) = env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    if filenames.first().is_some_and(|filename| filename == "verify") {
+        verify(&filenames[1..]);
+        return;
+    }
+    let class_filename = filenames.first().unwrap_or_else(|| {
+        eprintln!("Error: missing class filename argument");
+        process::exit(1);
+    });
+    let options = PrettyPrintOptions {
+        annotate_invokedynamic: flags.iter().any(|flag| flag == "--annotate"),
+        annotate_local_variables: flags.iter().any(|flag| flag == "--locals"),
+    };
+    let class_file_reader = File::open(class_filename).unwrap_or_else(|err| {
+        eprintln!("Error: failed to read {}: {}", class_filename, err);
+        process::exit(1);
+    });
+    let class_file = parse_class_file_from_reader(class_file_reader).unwrap_or_else(|err| {
+        eprintln!("Error: failed to parse {}: {}", class_filename, err);
+        process::exit(1);
+    });
+    // The printer resolves constant pool references without checking them
+    if let Some(diagnostic) = validate(&class_file).into_iter().find(|diagnostic| diagnostic.kind.is_unresolved_reference()) {
+        eprintln!("Error: failed to parse {}: {}", class_filename, diagnostic);
+        process::exit(1);
+    }
+    if let Err(err) = pretty_print_text(&class_file, &options) {
+        eprintln!("Error: failed to parse {}: {}", class_filename, err);
+        process::exit(1);
+    }
+}
+
+/// Print the format problems found in each class file, exit with status 1
+/// when a class file cannot be parsed or has problems.
+fn verify(class_filenames: &[String]) {
+    if class_filenames.is_empty() {
+        eprintln!("Error: missing class filename argument");
+        process::exit(1);
+    }
+    let mut valid = true;
+    for class_filename in class_filenames {
+        let class_file_reader = match File::open(class_filename) {
+            Ok(class_file_reader) => class_file_reader,
+            Err(err) => {
+                eprintln!("Error: failed to read {}: {}", class_filename, err);
+                valid = false;
+                continue;
+            }
+        };
+        match parse_class_file_from_reader(class_file_reader) {
+            Ok(class_file) => {
+                for diagnostic in validate(&class_file) {
+                    println!("{}: {}", class_filename, diagnostic);
+                    valid = false;
+                }
+            },
+            Err(err) => {
+                eprintln!("Error: failed to parse {}: {}", class_filename, err);
+                valid = false;
+            }
+        }
+    }
+    if !valid {
+        process::exit(1);
+    }
 }
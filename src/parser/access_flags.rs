@@ -112,7 +112,7 @@ impl MethodAccessFlag {
             MethodAccessFlag::AccVarargs => "varargs",
             MethodAccessFlag::AccNative => "native",
             MethodAccessFlag::AccAbstract => "abstract",
-            MethodAccessFlag::AccStrict => "strict",
+            MethodAccessFlag::AccStrict => "strictfp",
             MethodAccessFlag::AccSynthetic => "synthetic"
         }
     }
@@ -143,3 +143,256 @@ impl MethodAccessFlag {
         descriptions
     }
 }
+
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum FieldAccessFlag {
+    AccPublic,
+    AccPrivate,
+    AccProtected,
+    AccStatic,
+    AccFinal,
+    AccVolatile,
+    AccTransient,
+    AccSynthetic,
+    AccEnum
+}
+
+impl FieldAccessFlag {
+    pub fn to_str(&self) -> &str {
+        match self {
+            FieldAccessFlag::AccPublic => "ACC_PUBLIC",
+            FieldAccessFlag::AccPrivate => "ACC_PRIVATE",
+            FieldAccessFlag::AccProtected => "ACC_PROTECTED",
+            FieldAccessFlag::AccStatic => "ACC_STATIC",
+            FieldAccessFlag::AccFinal => "ACC_FINAL",
+            FieldAccessFlag::AccVolatile => "ACC_VOLATILE",
+            FieldAccessFlag::AccTransient => "ACC_TRANSIENT",
+            FieldAccessFlag::AccSynthetic => "ACC_SYNTHETIC",
+            FieldAccessFlag::AccEnum => "ACC_ENUM"
+        }
+    }
+
+    pub fn to_java_code(&self) -> &str {
+        match self {
+            FieldAccessFlag::AccPublic => "public",
+            FieldAccessFlag::AccPrivate => "private",
+            FieldAccessFlag::AccProtected => "protected",
+            FieldAccessFlag::AccStatic => "static",
+            FieldAccessFlag::AccFinal => "final",
+            FieldAccessFlag::AccVolatile => "volatile",
+            FieldAccessFlag::AccTransient => "transient",
+            FieldAccessFlag::AccSynthetic => "synthetic",
+            FieldAccessFlag::AccEnum => "enum"
+        }
+    }
+
+    pub fn parse_flags(flags: u16) -> Vec<FieldAccessFlag> {
+        let flag_to_field_access_flag: HashMap<u16, FieldAccessFlag> = HashMap::from([
+            (0x0001, FieldAccessFlag::AccPublic),
+            (0x0002, FieldAccessFlag::AccPrivate),
+            (0x0004, FieldAccessFlag::AccProtected),
+            (0x0008, FieldAccessFlag::AccStatic),
+            (0x0010, FieldAccessFlag::AccFinal),
+            (0x0040, FieldAccessFlag::AccVolatile),
+            (0x0080, FieldAccessFlag::AccTransient),
+            (0x1000, FieldAccessFlag::AccSynthetic),
+            (0x4000, FieldAccessFlag::AccEnum)
+        ]);
+
+        let mut descriptions: Vec<FieldAccessFlag> = Vec::new();
+        for (f, v) in flag_to_field_access_flag {
+            if f & flags != 0 {
+                descriptions.push(v)
+            }
+        }
+        descriptions.sort();
+        descriptions
+    }
+}
+
+/// access_flags of a parameter in the MethodParameters attribute.
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum MethodParameterAccessFlag {
+    AccFinal,
+    AccSynthetic,
+    AccMandated
+}
+
+impl MethodParameterAccessFlag {
+    pub fn to_str(&self) -> &str {
+        match self {
+            MethodParameterAccessFlag::AccFinal => "ACC_FINAL",
+            MethodParameterAccessFlag::AccSynthetic => "ACC_SYNTHETIC",
+            MethodParameterAccessFlag::AccMandated => "ACC_MANDATED"
+        }
+    }
+
+    pub fn to_java_code(&self) -> &str {
+        match self {
+            MethodParameterAccessFlag::AccFinal => "final",
+            MethodParameterAccessFlag::AccSynthetic => "synthetic",
+            MethodParameterAccessFlag::AccMandated => "mandated"
+        }
+    }
+
+    pub fn parse_flags(flags: u16) -> Vec<MethodParameterAccessFlag> {
+        let flag_to_method_parameter_access_flag: HashMap<u16, MethodParameterAccessFlag> = HashMap::from([
+            (0x0010, MethodParameterAccessFlag::AccFinal),
+            (0x1000, MethodParameterAccessFlag::AccSynthetic),
+            (0x8000, MethodParameterAccessFlag::AccMandated)
+        ]);
+
+        let mut descriptions: Vec<MethodParameterAccessFlag> = Vec::new();
+        for (f, v) in flag_to_method_parameter_access_flag {
+            if f & flags != 0 {
+                descriptions.push(v)
+            }
+        }
+        descriptions.sort();
+        descriptions
+    }
+}
+
+/// inner_class_access_flags of an InnerClasses entry, the modifiers of the
+/// nested class as declared in the source.
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum InnerClassAccessFlag {
+    AccPublic,
+    AccPrivate,
+    AccProtected,
+    AccStatic,
+    AccFinal,
+    AccInterface,
+    AccAbstract,
+    AccSynthetic,
+    AccAnnotation,
+    AccEnum
+}
+
+impl InnerClassAccessFlag {
+    pub fn to_str(&self) -> &str {
+        match self {
+            InnerClassAccessFlag::AccPublic => "ACC_PUBLIC",
+            InnerClassAccessFlag::AccPrivate => "ACC_PRIVATE",
+            InnerClassAccessFlag::AccProtected => "ACC_PROTECTED",
+            InnerClassAccessFlag::AccStatic => "ACC_STATIC",
+            InnerClassAccessFlag::AccFinal => "ACC_FINAL",
+            InnerClassAccessFlag::AccInterface => "ACC_INTERFACE",
+            InnerClassAccessFlag::AccAbstract => "ACC_ABSTRACT",
+            InnerClassAccessFlag::AccSynthetic => "ACC_SYNTHETIC",
+            InnerClassAccessFlag::AccAnnotation => "ACC_ANNOTATION",
+            InnerClassAccessFlag::AccEnum => "ACC_ENUM"
+        }
+    }
+
+    pub fn to_java_code(&self) -> &str {
+        match self {
+            InnerClassAccessFlag::AccPublic => "public",
+            InnerClassAccessFlag::AccPrivate => "private",
+            InnerClassAccessFlag::AccProtected => "protected",
+            InnerClassAccessFlag::AccStatic => "static",
+            InnerClassAccessFlag::AccFinal => "final",
+            InnerClassAccessFlag::AccInterface => "interface",
+            InnerClassAccessFlag::AccAbstract => "abstract",
+            InnerClassAccessFlag::AccSynthetic => "synthetic",
+            InnerClassAccessFlag::AccAnnotation => "annotation",
+            InnerClassAccessFlag::AccEnum => "enum"
+        }
+    }
+
+    pub fn parse_flags(flags: u16) -> Vec<InnerClassAccessFlag> {
+        let flag_to_inner_class_access_flag: HashMap<u16, InnerClassAccessFlag> = HashMap::from([
+            (0x0001, InnerClassAccessFlag::AccPublic),
+            (0x0002, InnerClassAccessFlag::AccPrivate),
+            (0x0004, InnerClassAccessFlag::AccProtected),
+            (0x0008, InnerClassAccessFlag::AccStatic),
+            (0x0010, InnerClassAccessFlag::AccFinal),
+            (0x0200, InnerClassAccessFlag::AccInterface),
+            (0x0400, InnerClassAccessFlag::AccAbstract),
+            (0x1000, InnerClassAccessFlag::AccSynthetic),
+            (0x2000, InnerClassAccessFlag::AccAnnotation),
+            (0x4000, InnerClassAccessFlag::AccEnum)
+        ]);
+
+        let mut descriptions: Vec<InnerClassAccessFlag> = Vec::new();
+        for (f, v) in flag_to_inner_class_access_flag {
+            if f & flags != 0 {
+                descriptions.push(v)
+            }
+        }
+        descriptions.sort();
+        descriptions
+    }
+}
+
+/// Flags of a module and of its requires, exports and opens directives. The
+/// same bit means ACC_OPEN on a module and ACC_TRANSITIVE on a requires, so
+/// each structure has its own parse function.
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum ModuleAccessFlag {
+    AccOpen,
+    AccTransitive,
+    AccStaticPhase,
+    AccSynthetic,
+    AccMandated
+}
+
+impl ModuleAccessFlag {
+    pub fn to_str(&self) -> &str {
+        match self {
+            ModuleAccessFlag::AccOpen => "ACC_OPEN",
+            ModuleAccessFlag::AccTransitive => "ACC_TRANSITIVE",
+            ModuleAccessFlag::AccStaticPhase => "ACC_STATIC_PHASE",
+            ModuleAccessFlag::AccSynthetic => "ACC_SYNTHETIC",
+            ModuleAccessFlag::AccMandated => "ACC_MANDATED"
+        }
+    }
+
+    pub fn to_java_code(&self) -> &str {
+        match self {
+            ModuleAccessFlag::AccOpen => "open",
+            ModuleAccessFlag::AccTransitive => "transitive",
+            ModuleAccessFlag::AccStaticPhase => "static",
+            ModuleAccessFlag::AccSynthetic => "synthetic",
+            ModuleAccessFlag::AccMandated => "mandated"
+        }
+    }
+
+    /// module_flags of the Module attribute
+    pub fn parse_module_flags(flags: u16) -> Vec<ModuleAccessFlag> {
+        ModuleAccessFlag::parse_flags(flags, HashMap::from([
+            (0x0020, ModuleAccessFlag::AccOpen),
+            (0x1000, ModuleAccessFlag::AccSynthetic),
+            (0x8000, ModuleAccessFlag::AccMandated)
+        ]))
+    }
+
+    /// requires_flags of a requires directive
+    pub fn parse_requires_flags(flags: u16) -> Vec<ModuleAccessFlag> {
+        ModuleAccessFlag::parse_flags(flags, HashMap::from([
+            (0x0020, ModuleAccessFlag::AccTransitive),
+            (0x0040, ModuleAccessFlag::AccStaticPhase),
+            (0x1000, ModuleAccessFlag::AccSynthetic),
+            (0x8000, ModuleAccessFlag::AccMandated)
+        ]))
+    }
+
+    /// exports_flags or opens_flags of an exports or opens directive
+    pub fn parse_exports_flags(flags: u16) -> Vec<ModuleAccessFlag> {
+        ModuleAccessFlag::parse_flags(flags, HashMap::from([
+            (0x1000, ModuleAccessFlag::AccSynthetic),
+            (0x8000, ModuleAccessFlag::AccMandated)
+        ]))
+    }
+
+    fn parse_flags(flags: u16, flag_to_module_access_flag: HashMap<u16, ModuleAccessFlag>) -> Vec<ModuleAccessFlag> {
+        let mut descriptions: Vec<ModuleAccessFlag> = Vec::new();
+        for (f, v) in flag_to_module_access_flag {
+            if f & flags != 0 {
+                descriptions.push(v)
+            }
+        }
+        descriptions.sort();
+        descriptions
+    }
+}
@@ -1,104 +1,817 @@
 #![allow(dead_code)]
 
+use std::borrow::Cow;
+use crate::parser::attribute_decoder::CustomAttribute;
+
+/// A parsed class file. The constant pool strings, the bytecode of the
+/// methods and the bytes of the non-standard attributes borrow from the
+/// parsed bytes when possible, `into_owned` detaches the class file from
+/// them.
 #[derive(Debug)]
-pub struct ClassFile {
+pub struct ClassFile<'a> {
     pub magic: u32,
     pub minor_version: u16,
     pub major_version: u16,
     pub constant_pool_count: u16,
-    pub cp_info: Vec<CpInfo>,
+    pub cp_info: Vec<CpInfo<'a>>,
     pub access_flags: u16,
     pub this_class: u16,
     pub super_class: u16,
     pub interfaces_count: u16,
     pub interfaces: Vec<u16>,
     pub fields_count: u16,
-    pub fields: Vec<FieldInfo>,
+    pub fields: Vec<FieldInfo<'a>>,
     pub methods_count: u16,
-    pub methods: Vec<MethodInfo>,
+    pub methods: Vec<MethodInfo<'a>>,
     pub attributes_count: u16,
-    pub attributes: Vec<AttributeInfo>
+    pub attributes: Vec<AttributeInfo<'a>>
 }
 
-#[derive(Debug)]
-pub enum CpInfo {
+impl ClassFile<'_> {
+    /// Copy the data borrowed from the class file bytes.
+    pub fn into_owned(self) -> ClassFile<'static> {
+        ClassFile {
+            magic: self.magic,
+            minor_version: self.minor_version,
+            major_version: self.major_version,
+            constant_pool_count: self.constant_pool_count,
+            cp_info: self.cp_info.into_iter().map(CpInfo::into_owned).collect(),
+            access_flags: self.access_flags,
+            this_class: self.this_class,
+            super_class: self.super_class,
+            interfaces_count: self.interfaces_count,
+            interfaces: self.interfaces,
+            fields_count: self.fields_count,
+            fields: self.fields.into_iter().map(FieldInfo::into_owned).collect(),
+            methods_count: self.methods_count,
+            methods: self.methods.into_iter().map(MethodInfo::into_owned).collect(),
+            attributes_count: self.attributes_count,
+            attributes: into_owned_attributes(self.attributes)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CpInfo<'a> {
     ConstantClass { tag: u8, name_index: u16 },
     ConstantFieldref { tag: u8, class_index: u16, name_and_type_index: u16 },
     ConstantMethodref { tag: u8, class_index: u16, name_and_type_index: u16 },
-    // ConstantInterfaceMethodref
+    ConstantInterfaceMethodref { tag: u8, class_index: u16, name_and_type_index: u16 },
     ConstantString { tag: u8, string_index: u16 },
-    // ConstantInteger
-    // ConstantFloat
-    // ConstantLong
-    // ConstantDouble
+    ConstantInteger { tag: u8, bytes: u32 },
+    ConstantFloat { tag: u8, bytes: u32 },
+    ConstantLong { tag: u8, high_bytes: u32, low_bytes: u32 },
+    ConstantDouble { tag: u8, high_bytes: u32, low_bytes: u32 },
     ConstantNameAndType { tag: u8, name_index: u16, descriptor_index: u16 },
-    ConstantUtf8 { tag: u8, length: u16, bytes: Vec<u8>, bytes_str: String },
-    // ConstantMethodHandle
-    // ConstantMethodType
-    // ConstantDynamic
-    // ConstantInvokeDynamic
-    // ConstantModule
-    // ConstantPackage
+    /// `bytes` is the modified UTF-8 read from the class file, `bytes_str`
+    /// the decoded string with unpaired surrogates replaced by U+FFFD. Both
+    /// borrow from the class file bytes unless the string uses the encodings
+    /// specific to modified UTF-8: NUL and supplementary characters.
+    ConstantUtf8 { tag: u8, length: u16, bytes: Cow<'a, [u8]>, bytes_str: Cow<'a, str> },
+    ConstantMethodHandle { tag: u8, reference_kind: u8, reference_index: u16 },
+    ConstantMethodType { tag: u8, descriptor_index: u16 },
+    ConstantDynamic { tag: u8, bootstrap_method_attr_index: u16, name_and_type_index: u16 },
+    ConstantInvokeDynamic { tag: u8, bootstrap_method_attr_index: u16, name_and_type_index: u16 },
+    ConstantModule { tag: u8, name_index: u16 },
+    ConstantPackage { tag: u8, name_index: u16 },
+    /// Entry that cannot be referenced: index 0 of the constant pool and the
+    /// slot following a CONSTANT_Long or CONSTANT_Double.
+    Unusable,
+}
+
+impl CpInfo<'_> {
+    pub fn into_owned(self) -> CpInfo<'static> {
+        match self {
+            CpInfo::ConstantClass { tag, name_index } => CpInfo::ConstantClass { tag, name_index },
+            CpInfo::ConstantFieldref { tag, class_index, name_and_type_index } => CpInfo::ConstantFieldref { tag, class_index, name_and_type_index },
+            CpInfo::ConstantMethodref { tag, class_index, name_and_type_index } => CpInfo::ConstantMethodref { tag, class_index, name_and_type_index },
+            CpInfo::ConstantInterfaceMethodref { tag, class_index, name_and_type_index } => CpInfo::ConstantInterfaceMethodref { tag, class_index, name_and_type_index },
+            CpInfo::ConstantString { tag, string_index } => CpInfo::ConstantString { tag, string_index },
+            CpInfo::ConstantInteger { tag, bytes } => CpInfo::ConstantInteger { tag, bytes },
+            CpInfo::ConstantFloat { tag, bytes } => CpInfo::ConstantFloat { tag, bytes },
+            CpInfo::ConstantLong { tag, high_bytes, low_bytes } => CpInfo::ConstantLong { tag, high_bytes, low_bytes },
+            CpInfo::ConstantDouble { tag, high_bytes, low_bytes } => CpInfo::ConstantDouble { tag, high_bytes, low_bytes },
+            CpInfo::ConstantNameAndType { tag, name_index, descriptor_index } => CpInfo::ConstantNameAndType { tag, name_index, descriptor_index },
+            CpInfo::ConstantMethodHandle { tag, reference_kind, reference_index } => CpInfo::ConstantMethodHandle { tag, reference_kind, reference_index },
+            CpInfo::ConstantMethodType { tag, descriptor_index } => CpInfo::ConstantMethodType { tag, descriptor_index },
+            CpInfo::ConstantDynamic { tag, bootstrap_method_attr_index, name_and_type_index } => CpInfo::ConstantDynamic { tag, bootstrap_method_attr_index, name_and_type_index },
+            CpInfo::ConstantInvokeDynamic { tag, bootstrap_method_attr_index, name_and_type_index } => CpInfo::ConstantInvokeDynamic { tag, bootstrap_method_attr_index, name_and_type_index },
+            CpInfo::ConstantModule { tag, name_index } => CpInfo::ConstantModule { tag, name_index },
+            CpInfo::ConstantPackage { tag, name_index } => CpInfo::ConstantPackage { tag, name_index },
+            CpInfo::ConstantUtf8 { tag, length, bytes, bytes_str } =>
+                CpInfo::ConstantUtf8 { tag, length, bytes: Cow::Owned(bytes.into_owned()), bytes_str: Cow::Owned(bytes_str.into_owned()) },
+            CpInfo::Unusable => CpInfo::Unusable,
+        }
+    }
+
+    pub fn integer_value(bytes: u32) -> i32 {
+        bytes as i32
+    }
+
+    pub fn float_value(bytes: u32) -> f32 {
+        f32::from_bits(bytes)
+    }
+
+    pub fn long_value(high_bytes: u32, low_bytes: u32) -> i64 {
+        (((high_bytes as u64) << 32) | low_bytes as u64) as i64
+    }
+
+    pub fn double_value(high_bytes: u32, low_bytes: u32) -> f64 {
+        f64::from_bits(((high_bytes as u64) << 32) | low_bytes as u64)
+    }
+}
+
+/// Kind of a CONSTANT_MethodHandle, see JVMS 5.4.3.5.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ReferenceKind {
+    GetField,
+    GetStatic,
+    PutField,
+    PutStatic,
+    InvokeVirtual,
+    InvokeStatic,
+    InvokeSpecial,
+    NewInvokeSpecial,
+    InvokeInterface,
+}
+
+impl ReferenceKind {
+    pub fn from_value(reference_kind: u8) -> Option<ReferenceKind> {
+        match reference_kind {
+            1 => Some(ReferenceKind::GetField),
+            2 => Some(ReferenceKind::GetStatic),
+            3 => Some(ReferenceKind::PutField),
+            4 => Some(ReferenceKind::PutStatic),
+            5 => Some(ReferenceKind::InvokeVirtual),
+            6 => Some(ReferenceKind::InvokeStatic),
+            7 => Some(ReferenceKind::InvokeSpecial),
+            8 => Some(ReferenceKind::NewInvokeSpecial),
+            9 => Some(ReferenceKind::InvokeInterface),
+            _ => None
+        }
+    }
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            ReferenceKind::GetField => "REF_getField",
+            ReferenceKind::GetStatic => "REF_getStatic",
+            ReferenceKind::PutField => "REF_putField",
+            ReferenceKind::PutStatic => "REF_putStatic",
+            ReferenceKind::InvokeVirtual => "REF_invokeVirtual",
+            ReferenceKind::InvokeStatic => "REF_invokeStatic",
+            ReferenceKind::InvokeSpecial => "REF_invokeSpecial",
+            ReferenceKind::NewInvokeSpecial => "REF_newInvokeSpecial",
+            ReferenceKind::InvokeInterface => "REF_invokeInterface"
+        }
+    }
 }
 
 #[derive(Debug)]
-pub struct FieldInfo {
+pub struct FieldInfo<'a> {
     pub access_flags: u16,
     pub name_index: u16,
     pub descriptor_index: u16,
     pub attributes_count: u16,
-    pub attributes: Vec<AttributeInfo>
+    pub attributes: Vec<AttributeInfo<'a>>
+}
+
+impl FieldInfo<'_> {
+    pub fn into_owned(self) -> FieldInfo<'static> {
+        FieldInfo {
+            access_flags: self.access_flags,
+            name_index: self.name_index,
+            descriptor_index: self.descriptor_index,
+            attributes_count: self.attributes_count,
+            attributes: into_owned_attributes(self.attributes)
+        }
+    }
 }
 
 #[derive(Debug)]
-pub struct MethodInfo {
+pub struct MethodInfo<'a> {
     pub access_flags: u16,
     pub name_index: u16,
     pub descriptor_index: u16,
     pub attributes_count: u16,
-    pub attributes: Vec<AttributeInfo>
+    pub attributes: Vec<AttributeInfo<'a>>
+}
+
+impl MethodInfo<'_> {
+    pub fn into_owned(self) -> MethodInfo<'static> {
+        MethodInfo {
+            access_flags: self.access_flags,
+            name_index: self.name_index,
+            descriptor_index: self.descriptor_index,
+            attributes_count: self.attributes_count,
+            attributes: into_owned_attributes(self.attributes)
+        }
+    }
 }
 
 #[derive(Debug)]
-pub enum AttributeInfo {
+pub enum AttributeInfo<'a> {
     ConstantValue { attribute_name_index: u16, attribute_length: u32, constantvalue_index: u16 },
-    Code { attribute_name_index: u16, attribute_length: u32, max_stack: u16, max_locals: u16, code_length: u32, code: Vec<u8>, exception_table_length: u16, exception_table: Vec<ExceptionTable>, attributes_count: u16, attributes: Vec<AttributeInfo> },
-    StackMapTable {},
-    Exceptions {},
-    InnerClasses {},
-    EnclosingMethod {},
-    Synthetic {},
-    Signature {},
+    Code { attribute_name_index: u16, attribute_length: u32, max_stack: u16, max_locals: u16, code_length: u32, code: Cow<'a, [u8]>, exception_table_length: u16, exception_table: Vec<ExceptionTable>, attributes_count: u16, attributes: Vec<AttributeInfo<'a>> },
+    StackMapTable { attribute_name_index: u16, attribute_length: u32, number_of_entries: u16, entries: Vec<StackMapFrame> },
+    /// Indexes of the CONSTANT_Class of the checked exceptions a method may
+    /// throw, its `throws` clause.
+    Exceptions { attribute_name_index: u16, attribute_length: u32, number_of_exceptions: u16, exception_index_table: Vec<u16> },
+    InnerClasses { attribute_name_index: u16, attribute_length: u32, number_of_classes: u16, classes: Vec<InnerClass> },
+    /// Method that encloses a local or anonymous class. Both indexes point to
+    /// the constant pool, method_index is 0 when the class is not enclosed by
+    /// a method, ex.: an anonymous class in a field initializer.
+    EnclosingMethod { attribute_name_index: u16, attribute_length: u32, class_index: u16, method_index: u16 },
+    Synthetic { attribute_name_index: u16, attribute_length: u32 },
+    Signature { attribute_name_index: u16, attribute_length: u32, signature_index: u16 },
     SourceFile { attribute_name_index: u16, attribute_length: u32, sourcefile_index: u16 },
-    SourceDebugExtension {},
+    /// Extended debugging information, a modified UTF-8 string the JVM
+    /// does not interpret, ex.: the SMAP of a JSP compiled class.
+    SourceDebugExtension { attribute_name_index: u16, attribute_length: u32, debug_extension: Cow<'a, [u8]> },
     LineNumberTable { attribute_name_index: u16, attribute_length: u32, line_number_table_length: u16, line_number_tables: Vec<LineNumberTableItem> },
-    LocalVariableTable {},
-    LocalVariableTypeTable {},
-    Deprecated {},
-    RuntimeVisibleAnnotations {},
-    RuntimeInvisibleAnnotations {},
-    RuntimeVisibleParameterAnnotations {},
-    RuntimeInvisibleParameterAnnotations {},
-    RuntimeVisibleTypeAnnotations {},
-    RuntimeInvisibleTypeAnnotations {},
-    AnnotationDefault {},
-    BootstrapMethods {},
-    MethodParameters {},
-    Module {},
-    ModulePackages {},
-    ModuleMainClass {},
-    NestHost {},
-    NestMembers {},
-    Record {},
-    PermittedSubclasses {},
+    LocalVariableTable { attribute_name_index: u16, attribute_length: u32, local_variable_table_length: u16, local_variable_table: Vec<LocalVariableTableItem> },
+    /// Generic types of the local variables, only for the variables whose
+    /// type uses type variables or parameterized types.
+    LocalVariableTypeTable { attribute_name_index: u16, attribute_length: u32, local_variable_type_table_length: u16, local_variable_type_table: Vec<LocalVariableTypeTableItem> },
+    Deprecated { attribute_name_index: u16, attribute_length: u32 },
+    RuntimeVisibleAnnotations { attribute_name_index: u16, attribute_length: u32, num_annotations: u16, annotations: Vec<Annotation> },
+    RuntimeInvisibleAnnotations { attribute_name_index: u16, attribute_length: u32, num_annotations: u16, annotations: Vec<Annotation> },
+    /// Annotations of each parameter of a method, in declaration order.
+    RuntimeVisibleParameterAnnotations { attribute_name_index: u16, attribute_length: u32, num_parameters: u8, parameter_annotations: Vec<Vec<Annotation>> },
+    RuntimeInvisibleParameterAnnotations { attribute_name_index: u16, attribute_length: u32, num_parameters: u8, parameter_annotations: Vec<Vec<Annotation>> },
+    RuntimeVisibleTypeAnnotations { attribute_name_index: u16, attribute_length: u32, num_annotations: u16, annotations: Vec<TypeAnnotation> },
+    RuntimeInvisibleTypeAnnotations { attribute_name_index: u16, attribute_length: u32, num_annotations: u16, annotations: Vec<TypeAnnotation> },
+    AnnotationDefault { attribute_name_index: u16, attribute_length: u32, default_value: ElementValue },
+    BootstrapMethods { attribute_name_index: u16, attribute_length: u32, num_bootstrap_methods: u16, bootstrap_methods: Vec<BootstrapMethod> },
+    MethodParameters { attribute_name_index: u16, attribute_length: u32, parameters_count: u8, parameters: Vec<MethodParameter> },
+    Module {
+        attribute_name_index: u16,
+        attribute_length: u32,
+        /// Index of the CONSTANT_Module of this module
+        module_name_index: u16,
+        module_flags: u16,
+        /// Index of a CONSTANT_Utf8, 0 when the module has no version
+        module_version_index: u16,
+        requires_count: u16,
+        requires: Vec<ModuleRequires>,
+        exports_count: u16,
+        exports: Vec<ModuleExports>,
+        opens_count: u16,
+        opens: Vec<ModuleOpens>,
+        uses_count: u16,
+        /// Indexes of the CONSTANT_Class of the services used by the module
+        uses_index: Vec<u16>,
+        provides_count: u16,
+        provides: Vec<ModuleProvides>
+    },
+    /// Indexes of the CONSTANT_Package of every package of the module.
+    ModulePackages { attribute_name_index: u16, attribute_length: u32, package_count: u16, package_index: Vec<u16> },
+    ModuleMainClass { attribute_name_index: u16, attribute_length: u32, main_class_index: u16 },
+    /// Index of the CONSTANT_Class of the class that hosts the nest.
+    NestHost { attribute_name_index: u16, attribute_length: u32, host_class_index: u16 },
+    /// Indexes of the CONSTANT_Class of the other members of the nest.
+    NestMembers { attribute_name_index: u16, attribute_length: u32, number_of_classes: u16, classes: Vec<u16> },
+    Record { attribute_name_index: u16, attribute_length: u32, components_count: u16, components: Vec<RecordComponentInfo<'a>> },
+    /// Indexes of the CONSTANT_Class of the subclasses allowed to extend a
+    /// sealed class or interface.
+    PermittedSubclasses { attribute_name_index: u16, attribute_length: u32, number_of_classes: u16, classes: Vec<u16> },
+    /// Attribute not defined by the JVMS and without a registered decoder,
+    /// `info` holds its bytes unchanged.
+    Unknown { attribute_name_index: u16, attribute_length: u32, info: Cow<'a, [u8]> },
+    /// Attribute not defined by the JVMS decoded by a registered decoder, see
+    /// `AttributeDecoders`.
+    Custom { attribute_name_index: u16, attribute_length: u32, info: Cow<'a, [u8]>, value: Box<dyn CustomAttribute> },
+}
+
+impl AttributeInfo<'_> {
+    /// attribute_name_index and attribute_length, shared by all the
+    /// attributes.
+    pub fn header(&self) -> (u16, u32) {
+        match self {
+            AttributeInfo::ConstantValue { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::Code { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::StackMapTable { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::Exceptions { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::InnerClasses { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::EnclosingMethod { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::Synthetic { attribute_name_index, attribute_length } |
+            AttributeInfo::Signature { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::SourceFile { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::SourceDebugExtension { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::LineNumberTable { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::LocalVariableTable { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::LocalVariableTypeTable { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::Deprecated { attribute_name_index, attribute_length } |
+            AttributeInfo::RuntimeVisibleAnnotations { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::RuntimeInvisibleAnnotations { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::RuntimeVisibleParameterAnnotations { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::RuntimeInvisibleParameterAnnotations { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::RuntimeVisibleTypeAnnotations { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::RuntimeInvisibleTypeAnnotations { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::AnnotationDefault { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::BootstrapMethods { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::MethodParameters { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::Module { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::ModulePackages { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::ModuleMainClass { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::NestHost { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::NestMembers { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::Record { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::PermittedSubclasses { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::Unknown { attribute_name_index, attribute_length, .. } |
+            AttributeInfo::Custom { attribute_name_index, attribute_length, .. } => (*attribute_name_index, *attribute_length)
+        }
+    }
+
+    pub fn into_owned(self) -> AttributeInfo<'static> {
+        match self {
+            AttributeInfo::Code { attribute_name_index, attribute_length, max_stack, max_locals, code_length, code, exception_table_length, exception_table, attributes_count, attributes } => {
+                let code = Cow::Owned(code.into_owned());
+                let attributes = into_owned_attributes(attributes);
+                AttributeInfo::Code { attribute_name_index, attribute_length, max_stack, max_locals, code_length, code, exception_table_length, exception_table, attributes_count, attributes }
+            },
+            AttributeInfo::Record { attribute_name_index, attribute_length, components_count, components } => {
+                let components = components.into_iter().map(RecordComponentInfo::into_owned).collect();
+                AttributeInfo::Record { attribute_name_index, attribute_length, components_count, components }
+            },
+            AttributeInfo::Unknown { attribute_name_index, attribute_length, info } =>
+                AttributeInfo::Unknown { attribute_name_index, attribute_length, info: Cow::Owned(info.into_owned()) },
+            AttributeInfo::Custom { attribute_name_index, attribute_length, info, value } =>
+                AttributeInfo::Custom { attribute_name_index, attribute_length, info: Cow::Owned(info.into_owned()), value },
+            AttributeInfo::ConstantValue { attribute_name_index, attribute_length, constantvalue_index } =>
+                AttributeInfo::ConstantValue { attribute_name_index, attribute_length, constantvalue_index },
+            AttributeInfo::StackMapTable { attribute_name_index, attribute_length, number_of_entries, entries } =>
+                AttributeInfo::StackMapTable { attribute_name_index, attribute_length, number_of_entries, entries },
+            AttributeInfo::Exceptions { attribute_name_index, attribute_length, number_of_exceptions, exception_index_table } =>
+                AttributeInfo::Exceptions { attribute_name_index, attribute_length, number_of_exceptions, exception_index_table },
+            AttributeInfo::InnerClasses { attribute_name_index, attribute_length, number_of_classes, classes } =>
+                AttributeInfo::InnerClasses { attribute_name_index, attribute_length, number_of_classes, classes },
+            AttributeInfo::EnclosingMethod { attribute_name_index, attribute_length, class_index, method_index } =>
+                AttributeInfo::EnclosingMethod { attribute_name_index, attribute_length, class_index, method_index },
+            AttributeInfo::Synthetic { attribute_name_index, attribute_length } =>
+                AttributeInfo::Synthetic { attribute_name_index, attribute_length },
+            AttributeInfo::Signature { attribute_name_index, attribute_length, signature_index } =>
+                AttributeInfo::Signature { attribute_name_index, attribute_length, signature_index },
+            AttributeInfo::SourceFile { attribute_name_index, attribute_length, sourcefile_index } =>
+                AttributeInfo::SourceFile { attribute_name_index, attribute_length, sourcefile_index },
+            AttributeInfo::SourceDebugExtension { attribute_name_index, attribute_length, debug_extension } =>
+                AttributeInfo::SourceDebugExtension { attribute_name_index, attribute_length, debug_extension: Cow::Owned(debug_extension.into_owned()) },
+            AttributeInfo::LineNumberTable { attribute_name_index, attribute_length, line_number_table_length, line_number_tables } =>
+                AttributeInfo::LineNumberTable { attribute_name_index, attribute_length, line_number_table_length, line_number_tables },
+            AttributeInfo::LocalVariableTable { attribute_name_index, attribute_length, local_variable_table_length, local_variable_table } =>
+                AttributeInfo::LocalVariableTable { attribute_name_index, attribute_length, local_variable_table_length, local_variable_table },
+            AttributeInfo::LocalVariableTypeTable { attribute_name_index, attribute_length, local_variable_type_table_length, local_variable_type_table } =>
+                AttributeInfo::LocalVariableTypeTable { attribute_name_index, attribute_length, local_variable_type_table_length, local_variable_type_table },
+            AttributeInfo::Deprecated { attribute_name_index, attribute_length } =>
+                AttributeInfo::Deprecated { attribute_name_index, attribute_length },
+            AttributeInfo::RuntimeVisibleAnnotations { attribute_name_index, attribute_length, num_annotations, annotations } =>
+                AttributeInfo::RuntimeVisibleAnnotations { attribute_name_index, attribute_length, num_annotations, annotations },
+            AttributeInfo::RuntimeInvisibleAnnotations { attribute_name_index, attribute_length, num_annotations, annotations } =>
+                AttributeInfo::RuntimeInvisibleAnnotations { attribute_name_index, attribute_length, num_annotations, annotations },
+            AttributeInfo::RuntimeVisibleParameterAnnotations { attribute_name_index, attribute_length, num_parameters, parameter_annotations } =>
+                AttributeInfo::RuntimeVisibleParameterAnnotations { attribute_name_index, attribute_length, num_parameters, parameter_annotations },
+            AttributeInfo::RuntimeInvisibleParameterAnnotations { attribute_name_index, attribute_length, num_parameters, parameter_annotations } =>
+                AttributeInfo::RuntimeInvisibleParameterAnnotations { attribute_name_index, attribute_length, num_parameters, parameter_annotations },
+            AttributeInfo::RuntimeVisibleTypeAnnotations { attribute_name_index, attribute_length, num_annotations, annotations } =>
+                AttributeInfo::RuntimeVisibleTypeAnnotations { attribute_name_index, attribute_length, num_annotations, annotations },
+            AttributeInfo::RuntimeInvisibleTypeAnnotations { attribute_name_index, attribute_length, num_annotations, annotations } =>
+                AttributeInfo::RuntimeInvisibleTypeAnnotations { attribute_name_index, attribute_length, num_annotations, annotations },
+            AttributeInfo::AnnotationDefault { attribute_name_index, attribute_length, default_value } =>
+                AttributeInfo::AnnotationDefault { attribute_name_index, attribute_length, default_value },
+            AttributeInfo::BootstrapMethods { attribute_name_index, attribute_length, num_bootstrap_methods, bootstrap_methods } =>
+                AttributeInfo::BootstrapMethods { attribute_name_index, attribute_length, num_bootstrap_methods, bootstrap_methods },
+            AttributeInfo::MethodParameters { attribute_name_index, attribute_length, parameters_count, parameters } =>
+                AttributeInfo::MethodParameters { attribute_name_index, attribute_length, parameters_count, parameters },
+            AttributeInfo::Module { attribute_name_index, attribute_length, module_name_index, module_flags, module_version_index, requires_count, requires, exports_count, exports, opens_count, opens, uses_count, uses_index, provides_count, provides } =>
+                AttributeInfo::Module { attribute_name_index, attribute_length, module_name_index, module_flags, module_version_index, requires_count, requires, exports_count, exports, opens_count, opens, uses_count, uses_index, provides_count, provides },
+            AttributeInfo::ModulePackages { attribute_name_index, attribute_length, package_count, package_index } =>
+                AttributeInfo::ModulePackages { attribute_name_index, attribute_length, package_count, package_index },
+            AttributeInfo::ModuleMainClass { attribute_name_index, attribute_length, main_class_index } =>
+                AttributeInfo::ModuleMainClass { attribute_name_index, attribute_length, main_class_index },
+            AttributeInfo::NestHost { attribute_name_index, attribute_length, host_class_index } =>
+                AttributeInfo::NestHost { attribute_name_index, attribute_length, host_class_index },
+            AttributeInfo::NestMembers { attribute_name_index, attribute_length, number_of_classes, classes } =>
+                AttributeInfo::NestMembers { attribute_name_index, attribute_length, number_of_classes, classes },
+            AttributeInfo::PermittedSubclasses { attribute_name_index, attribute_length, number_of_classes, classes } =>
+                AttributeInfo::PermittedSubclasses { attribute_name_index, attribute_length, number_of_classes, classes },
+        }
+    }
+}
+
+fn into_owned_attributes(attributes: Vec<AttributeInfo>) -> Vec<AttributeInfo<'static>> {
+    attributes.into_iter().map(AttributeInfo::into_owned).collect()
+}
+
+/// Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.7.24
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodParameter {
+    /// Index of the CONSTANT_Utf8 with the name of the parameter, 0 for a
+    /// parameter without a name
+    pub name_index: u16,
+    pub access_flags: u16,
+}
+
+/// Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.7.6
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InnerClass {
+    /// Index of the CONSTANT_Class of the nested class
+    pub inner_class_info_index: u16,
+    /// Index of the CONSTANT_Class of the enclosing class, 0 for local and
+    /// anonymous classes
+    pub outer_class_info_index: u16,
+    /// Index of the CONSTANT_Utf8 with the simple name of the class, 0 for
+    /// anonymous classes
+    pub inner_name_index: u16,
+    pub inner_class_access_flags: u16,
 }
 
 #[derive(Debug)]
 pub struct ExceptionTable {
-    start_pc: u16,
-    end_pc: u16,
-    handler_pc: u16,
-    catch_type: u16
+    pub start_pc: u16,
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    /// Index of the CONSTANT_Class caught by the handler, 0 means any
+    /// exception (used to implement `finally`).
+    pub catch_type: u16
+}
+
+/// Bootstrap method of invokedynamic call sites and dynamically-computed
+/// constants, referenced by the bootstrap_method_attr_index of
+/// CONSTANT_InvokeDynamic and CONSTANT_Dynamic. Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.7.23
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootstrapMethod {
+    /// Index of a CONSTANT_MethodHandle
+    pub bootstrap_method_ref: u16,
+    pub num_bootstrap_arguments: u16,
+    /// Indexes of the loadable constants passed as static arguments
+    pub bootstrap_arguments: Vec<u16>,
+}
+
+/// An invokedynamic call site or a dynamically-computed constant linked to
+/// the bootstrap method that resolves it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootstrapCallSite<'a> {
+    pub bootstrap_method_attr_index: u16,
+    pub name: String,
+    pub descriptor: String,
+    pub bootstrap_method: &'a BootstrapMethod,
+}
+
+/// Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.7.16
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    /// Index of the CONSTANT_Utf8 with the field descriptor of the
+    /// annotation interface, ex.: Lorg/junit/Test;
+    pub type_index: u16,
+    pub element_value_pairs: Vec<ElementValuePair>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementValuePair {
+    pub element_name_index: u16,
+    pub value: ElementValue,
+}
+
+/// Value of an annotation element, the tag identifies its type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElementValue {
+    /// Tags B, C, D, F, I, J, S, Z and s (String)
+    ConstValue { tag: u8, const_value_index: u16 },
+    /// Tag e
+    EnumConstValue { type_name_index: u16, const_name_index: u16 },
+    /// Tag c, the index of the CONSTANT_Utf8 with a return descriptor
+    ClassInfo { class_info_index: u16 },
+    /// Tag @
+    AnnotationValue { annotation_value: Annotation },
+    /// Tag [
+    ArrayValue { num_values: u16, values: Vec<ElementValue> },
+}
+
+/// Annotation on a use of a type. Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.7.20
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeAnnotation {
+    pub target_type: TargetType,
+    pub target_info: TargetInfo,
+    /// Part of the type that is annotated, empty when the annotation applies
+    /// to the whole type.
+    pub type_path: Vec<TypePathEntry>,
+    pub annotation: Annotation,
+}
+
+/// Kind of target of a type annotation, with the names javap prints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetType {
+    ClassTypeParameter,
+    MethodTypeParameter,
+    ClassExtends,
+    ClassTypeParameterBound,
+    MethodTypeParameterBound,
+    Field,
+    MethodReturn,
+    MethodReceiver,
+    MethodFormalParameter,
+    Throws,
+    LocalVariable,
+    ResourceVariable,
+    ExceptionParameter,
+    Instanceof,
+    New,
+    ConstructorReference,
+    MethodReference,
+    Cast,
+    ConstructorInvocationTypeArgument,
+    MethodInvocationTypeArgument,
+    ConstructorReferenceTypeArgument,
+    MethodReferenceTypeArgument,
+}
+
+impl TargetType {
+    pub fn from_value(target_type: u8) -> Option<TargetType> {
+        match target_type {
+            0x00 => Some(TargetType::ClassTypeParameter),
+            0x01 => Some(TargetType::MethodTypeParameter),
+            0x10 => Some(TargetType::ClassExtends),
+            0x11 => Some(TargetType::ClassTypeParameterBound),
+            0x12 => Some(TargetType::MethodTypeParameterBound),
+            0x13 => Some(TargetType::Field),
+            0x14 => Some(TargetType::MethodReturn),
+            0x15 => Some(TargetType::MethodReceiver),
+            0x16 => Some(TargetType::MethodFormalParameter),
+            0x17 => Some(TargetType::Throws),
+            0x40 => Some(TargetType::LocalVariable),
+            0x41 => Some(TargetType::ResourceVariable),
+            0x42 => Some(TargetType::ExceptionParameter),
+            0x43 => Some(TargetType::Instanceof),
+            0x44 => Some(TargetType::New),
+            0x45 => Some(TargetType::ConstructorReference),
+            0x46 => Some(TargetType::MethodReference),
+            0x47 => Some(TargetType::Cast),
+            0x48 => Some(TargetType::ConstructorInvocationTypeArgument),
+            0x49 => Some(TargetType::MethodInvocationTypeArgument),
+            0x4A => Some(TargetType::ConstructorReferenceTypeArgument),
+            0x4B => Some(TargetType::MethodReferenceTypeArgument),
+            _ => None
+        }
+    }
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            TargetType::ClassTypeParameter => "CLASS_TYPE_PARAMETER",
+            TargetType::MethodTypeParameter => "METHOD_TYPE_PARAMETER",
+            TargetType::ClassExtends => "CLASS_EXTENDS",
+            TargetType::ClassTypeParameterBound => "CLASS_TYPE_PARAMETER_BOUND",
+            TargetType::MethodTypeParameterBound => "METHOD_TYPE_PARAMETER_BOUND",
+            TargetType::Field => "FIELD",
+            TargetType::MethodReturn => "METHOD_RETURN",
+            TargetType::MethodReceiver => "METHOD_RECEIVER",
+            TargetType::MethodFormalParameter => "METHOD_FORMAL_PARAMETER",
+            TargetType::Throws => "THROWS",
+            TargetType::LocalVariable => "LOCAL_VARIABLE",
+            TargetType::ResourceVariable => "RESOURCE_VARIABLE",
+            TargetType::ExceptionParameter => "EXCEPTION_PARAMETER",
+            TargetType::Instanceof => "INSTANCEOF",
+            TargetType::New => "NEW",
+            TargetType::ConstructorReference => "CONSTRUCTOR_REFERENCE",
+            TargetType::MethodReference => "METHOD_REFERENCE",
+            TargetType::Cast => "CAST",
+            TargetType::ConstructorInvocationTypeArgument => "CONSTRUCTOR_INVOCATION_TYPE_ARGUMENT",
+            TargetType::MethodInvocationTypeArgument => "METHOD_INVOCATION_TYPE_ARGUMENT",
+            TargetType::ConstructorReferenceTypeArgument => "CONSTRUCTOR_REFERENCE_TYPE_ARGUMENT",
+            TargetType::MethodReferenceTypeArgument => "METHOD_REFERENCE_TYPE_ARGUMENT"
+        }
+    }
+}
+
+/// Which type in a declaration or expression is annotated, its layout
+/// depends on the target_type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetInfo {
+    /// CLASS_TYPE_PARAMETER, METHOD_TYPE_PARAMETER
+    TypeParameter { type_parameter_index: u8 },
+    /// CLASS_EXTENDS, 65535 is the superclass and other values index the
+    /// interfaces.
+    Supertype { supertype_index: u16 },
+    /// CLASS_TYPE_PARAMETER_BOUND, METHOD_TYPE_PARAMETER_BOUND
+    TypeParameterBound { type_parameter_index: u8, bound_index: u8 },
+    /// FIELD, METHOD_RETURN, METHOD_RECEIVER
+    Empty,
+    /// METHOD_FORMAL_PARAMETER
+    FormalParameter { formal_parameter_index: u8 },
+    /// THROWS
+    Throws { throws_type_index: u16 },
+    /// LOCAL_VARIABLE, RESOURCE_VARIABLE
+    Localvar { table_length: u16, table: Vec<LocalvarTargetItem> },
+    /// EXCEPTION_PARAMETER
+    Catch { exception_table_index: u16 },
+    /// INSTANCEOF, NEW, CONSTRUCTOR_REFERENCE, METHOD_REFERENCE
+    Offset { offset: u16 },
+    /// CAST and the type arguments of invocations and method references
+    TypeArgument { offset: u16, type_argument_index: u8 },
+}
+
+/// Code range where a local variable lives in a local variable slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalvarTargetItem {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypePathEntry {
+    pub type_path_kind: TypePathKind,
+    /// Type argument annotated when the kind is TypeArgument, 0 otherwise.
+    pub type_argument_index: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypePathKind {
+    /// Deeper in an array type
+    Array,
+    /// Deeper in a nested type
+    InnerType,
+    /// On the bound of a wildcard type argument
+    Wildcard,
+    /// On a type argument of a parameterized type
+    TypeArgument,
+}
+
+impl TypePathKind {
+    pub fn from_value(type_path_kind: u8) -> Option<TypePathKind> {
+        match type_path_kind {
+            0 => Some(TypePathKind::Array),
+            1 => Some(TypePathKind::InnerType),
+            2 => Some(TypePathKind::Wildcard),
+            3 => Some(TypePathKind::TypeArgument),
+            _ => None
+        }
+    }
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            TypePathKind::Array => "ARRAY",
+            TypePathKind::InnerType => "INNER_TYPE",
+            TypePathKind::Wildcard => "WILDCARD",
+            TypePathKind::TypeArgument => "TYPE_ARGUMENT"
+        }
+    }
+}
+
+/// Types of the local variables and operand stack at a bytecode offset, used
+/// by the type checking verifier. The offset of each frame is encoded as a
+/// delta from the previous frame. Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.7.4
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StackMapFrame {
+    /// Frame types 0-63, the offset_delta is the frame type.
+    SameFrame { frame_type: u8 },
+    /// Frame types 64-127, the offset_delta is the frame type - 64.
+    SameLocals1StackItemFrame { frame_type: u8, stack: VerificationTypeInfo },
+    /// Frame type 247
+    SameLocals1StackItemFrameExtended { frame_type: u8, offset_delta: u16, stack: VerificationTypeInfo },
+    /// Frame types 248-250, the last 251 - frame_type locals are absent.
+    ChopFrame { frame_type: u8, offset_delta: u16 },
+    /// Frame type 251
+    SameFrameExtended { frame_type: u8, offset_delta: u16 },
+    /// Frame types 252-254, frame_type - 251 locals are added.
+    AppendFrame { frame_type: u8, offset_delta: u16, locals: Vec<VerificationTypeInfo> },
+    /// Frame type 255
+    FullFrame {
+        frame_type: u8,
+        offset_delta: u16,
+        number_of_locals: u16,
+        locals: Vec<VerificationTypeInfo>,
+        number_of_stack_items: u16,
+        stack: Vec<VerificationTypeInfo>
+    },
+}
+
+impl StackMapFrame {
+    pub fn frame_type(&self) -> u8 {
+        match self {
+            StackMapFrame::SameFrame { frame_type } |
+            StackMapFrame::SameLocals1StackItemFrame { frame_type, .. } |
+            StackMapFrame::SameLocals1StackItemFrameExtended { frame_type, .. } |
+            StackMapFrame::ChopFrame { frame_type, .. } |
+            StackMapFrame::SameFrameExtended { frame_type, .. } |
+            StackMapFrame::AppendFrame { frame_type, .. } |
+            StackMapFrame::FullFrame { frame_type, .. } => *frame_type
+        }
+    }
+
+    pub fn offset_delta(&self) -> u16 {
+        match self {
+            StackMapFrame::SameFrame { frame_type } => *frame_type as u16,
+            StackMapFrame::SameLocals1StackItemFrame { frame_type, .. } => *frame_type as u16 - 64,
+            StackMapFrame::SameLocals1StackItemFrameExtended { offset_delta, .. } |
+            StackMapFrame::ChopFrame { offset_delta, .. } |
+            StackMapFrame::SameFrameExtended { offset_delta, .. } |
+            StackMapFrame::AppendFrame { offset_delta, .. } |
+            StackMapFrame::FullFrame { offset_delta, .. } => *offset_delta
+        }
+    }
+}
+
+/// Bytecode offset of each frame of a StackMapTable. The first frame is at
+/// offset_delta and the following ones at offset_delta + 1 past the previous
+/// frame.
+pub fn stack_map_frame_offsets(entries: &[StackMapFrame]) -> Vec<u32> {
+    let mut offsets: Vec<u32> = Vec::with_capacity(entries.len());
+    for frame in entries.iter() {
+        let offset = match offsets.last() {
+            Some(previous) => previous + frame.offset_delta() as u32 + 1,
+            None => frame.offset_delta() as u32
+        };
+        offsets.push(offset);
+    }
+    offsets
+}
+
+/// Type of a local variable or stack item in a stack map frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationTypeInfo {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    UninitializedThis,
+    /// Index of the CONSTANT_Class of the type
+    Object { cpool_index: u16 },
+    /// Offset of the `new` instruction that created the object
+    Uninitialized { offset: u16 },
+}
+
+/// Module dependency. Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.7.25
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleRequires {
+    /// Index of a CONSTANT_Module
+    pub requires_index: u16,
+    pub requires_flags: u16,
+    /// Index of a CONSTANT_Utf8, 0 when the version is unknown
+    pub requires_version_index: u16,
+}
+
+/// Package exported by a module, to every module when exports_to_index is
+/// empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleExports {
+    /// Index of a CONSTANT_Package
+    pub exports_index: u16,
+    pub exports_flags: u16,
+    pub exports_to_count: u16,
+    /// Indexes of CONSTANT_Module
+    pub exports_to_index: Vec<u16>,
+}
+
+/// Package opened by a module for reflection, to every module when
+/// opens_to_index is empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleOpens {
+    /// Index of a CONSTANT_Package
+    pub opens_index: u16,
+    pub opens_flags: u16,
+    pub opens_to_count: u16,
+    /// Indexes of CONSTANT_Module
+    pub opens_to_index: Vec<u16>,
+}
+
+/// Service implementations provided by a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleProvides {
+    /// Index of the CONSTANT_Class of the service interface
+    pub provides_index: u16,
+    pub provides_with_count: u16,
+    /// Indexes of the CONSTANT_Class of the implementations
+    pub provides_with_index: Vec<u16>,
+}
+
+/// Component of a record. Like fields, components carry their own
+/// attributes: Signature, annotations and type annotations. Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.7.30
+#[derive(Debug)]
+pub struct RecordComponentInfo<'a> {
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub attributes_count: u16,
+    pub attributes: Vec<AttributeInfo<'a>>
+}
+
+impl RecordComponentInfo<'_> {
+    pub fn into_owned(self) -> RecordComponentInfo<'static> {
+        RecordComponentInfo {
+            name_index: self.name_index,
+            descriptor_index: self.descriptor_index,
+            attributes_count: self.attributes_count,
+            attributes: into_owned_attributes(self.attributes)
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -107,14 +820,53 @@ pub struct LineNumberTableItem {
     pub line_number: u16
 }
 
+/// A local variable has the value at `index` in the local variables of the
+/// frame for the pcs in [start_pc, start_pc + length).
 #[derive(Debug)]
+pub struct LocalVariableTableItem {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    /// Index of the CONSTANT_Utf8 with the field descriptor of the variable
+    pub descriptor_index: u16,
+    pub index: u16
+}
+
+#[derive(Debug)]
+pub struct LocalVariableTypeTableItem {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    /// Index of the CONSTANT_Utf8 with the field signature of the variable
+    pub signature_index: u16,
+    pub index: u16
+}
+
+/// Type of a field, parameter or local variable. Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.3.2
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FieldType {
     BaseType { term: FieldTypeTerm },
+    /// Binary name of the class, ex.: java/lang/Object
     ObjectType { class_name: String },
     ArrayType { field_type: Box<FieldType> },
 }
 
-#[derive(Debug)]
+/// Parameter and return types of a method. Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.3.3
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodDescriptor {
+    pub params: Vec<FieldType>,
+    pub ret: ReturnType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReturnType {
+    Void,
+    FieldType(FieldType),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FieldTypeTerm {
     B, // byte
     C, // char
@@ -133,17 +885,40 @@ impl FieldType {
         match self {
             FieldType::BaseType { term } =>
                 String::from(term.str_java()),
-            FieldType::ObjectType { class_name } => {
-                let class_name: &str = &class_name[..class_name.len() - 1];
-                let class_name: String = String::from(class_name);
-                class_name.replace("/", ".")
-            },
+            FieldType::ObjectType { class_name } =>
+                class_name.replace('/', "."),
             FieldType::ArrayType { field_type } => {
                 let prefix = field_type.str_java();
                 format!("{}[]", prefix)
             }
         }
     }
+
+    /// Number of local variable slots taken by a value of this type.
+    pub fn slot_size(&self) -> usize {
+        match self {
+            FieldType::BaseType { term: FieldTypeTerm::D } |
+            FieldType::BaseType { term: FieldTypeTerm::J } => 2,
+            _ => 1
+        }
+    }
+}
+
+impl MethodDescriptor {
+    /// Number of local variable slots taken by the parameters, not counting
+    /// `this`.
+    pub fn params_slot_size(&self) -> usize {
+        self.params.iter().map(|p| p.slot_size()).sum()
+    }
+}
+
+impl ReturnType {
+    pub fn str_java(&self) -> String {
+        match self {
+            ReturnType::Void => String::from("void"),
+            ReturnType::FieldType(field_type) => field_type.str_java()
+        }
+    }
 }
 
 impl FieldTypeTerm {
@@ -162,3 +937,191 @@ impl FieldTypeTerm {
         }
     }
 }
+
+/// Generic signature of a class, stored in its Signature attribute. Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.7.9.1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub superclass: ClassTypeSignature,
+    pub interfaces: Vec<ClassTypeSignature>,
+}
+
+/// Generic signature of a method, stored in its Signature attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub params: Vec<JavaTypeSignature>,
+    pub result: ResultSignature,
+    pub throws: Vec<ThrowsSignature>,
+}
+
+/// Ex.: `T:Ljava/lang/Number;:Ljava/lang/Comparable<TT;>;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeParameter {
+    pub identifier: String,
+    pub class_bound: Option<ReferenceTypeSignature>,
+    pub interface_bounds: Vec<ReferenceTypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JavaTypeSignature {
+    BaseType { term: FieldTypeTerm },
+    ReferenceType { reference_type: ReferenceTypeSignature },
+}
+
+/// The signature of a field is a ReferenceTypeSignature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceTypeSignature {
+    ClassType { class_type: ClassTypeSignature },
+    TypeVariable { identifier: String },
+    ArrayType { java_type: Box<JavaTypeSignature> },
+}
+
+/// Ex.: `Ljava/util/Map$Entry<Ljava/lang/String;*>;`, `LG<TT;>.In<TU;>;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassTypeSignature {
+    /// Package of the class with a trailing `/`, ex.: java/util/
+    pub package_specifier: String,
+    pub simple_class_type: SimpleClassTypeSignature,
+    /// Inner classes of a parameterized class, each one introduced by `.`
+    pub suffixes: Vec<SimpleClassTypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleClassTypeSignature {
+    pub identifier: String,
+    pub type_arguments: Vec<TypeArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeArgument {
+    /// `*`
+    Wildcard,
+    Exact { reference_type: ReferenceTypeSignature },
+    /// `+`
+    Extends { reference_type: ReferenceTypeSignature },
+    /// `-`
+    Super { reference_type: ReferenceTypeSignature },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResultSignature {
+    Void,
+    JavaType { java_type: JavaTypeSignature },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThrowsSignature {
+    ClassType { class_type: ClassTypeSignature },
+    TypeVariable { identifier: String },
+}
+
+/// Java syntax of a list of type parameters, empty when there are none.
+/// Ex.: <T extends java.lang.Number & java.lang.Comparable<T>, U extends java.lang.Object>
+pub fn type_parameters_str_java(type_parameters: &[TypeParameter]) -> String {
+    if type_parameters.is_empty() {
+        String::new()
+    } else {
+        let type_parameters: Vec<String> = type_parameters.iter().map(|t| t.str_java()).collect();
+        format!("<{}>", type_parameters.join(", "))
+    }
+}
+
+impl ClassSignature {
+    /// Type parameters, superclass and interfaces of the class.
+    /// Ex.: <T extends java.lang.Object> extends java.lang.Object implements java.lang.Runnable
+    pub fn str_java(&self) -> String {
+        let mut acc = format!("{} extends {}", type_parameters_str_java(&self.type_parameters), self.superclass.str_java());
+        if !self.interfaces.is_empty() {
+            let interfaces: Vec<String> = self.interfaces.iter().map(|i| i.str_java()).collect();
+            acc.push_str(&format!(" implements {}", interfaces.join(", ")));
+        }
+        acc
+    }
+}
+
+impl TypeParameter {
+    pub fn str_java(&self) -> String {
+        let bounds: Vec<String> = self.class_bound.iter()
+            .chain(self.interface_bounds.iter())
+            .map(|b| b.str_java())
+            .collect();
+        if bounds.is_empty() {
+            self.identifier.clone()
+        } else {
+            format!("{} extends {}", self.identifier, bounds.join(" & "))
+        }
+    }
+}
+
+impl JavaTypeSignature {
+    pub fn str_java(&self) -> String {
+        match self {
+            JavaTypeSignature::BaseType { term } => String::from(term.str_java()),
+            JavaTypeSignature::ReferenceType { reference_type } => reference_type.str_java()
+        }
+    }
+}
+
+impl ReferenceTypeSignature {
+    pub fn str_java(&self) -> String {
+        match self {
+            ReferenceTypeSignature::ClassType { class_type } => class_type.str_java(),
+            ReferenceTypeSignature::TypeVariable { identifier } => identifier.clone(),
+            ReferenceTypeSignature::ArrayType { java_type } => format!("{}[]", java_type.str_java())
+        }
+    }
+}
+
+impl ClassTypeSignature {
+    pub fn str_java(&self) -> String {
+        let mut acc = self.package_specifier.replace('/', ".");
+        acc.push_str(&self.simple_class_type.str_java());
+        for suffix in self.suffixes.iter() {
+            acc.push('.');
+            acc.push_str(&suffix.str_java());
+        }
+        acc
+    }
+}
+
+impl SimpleClassTypeSignature {
+    pub fn str_java(&self) -> String {
+        if self.type_arguments.is_empty() {
+            self.identifier.clone()
+        } else {
+            let type_arguments: Vec<String> = self.type_arguments.iter().map(|t| t.str_java()).collect();
+            format!("{}<{}>", self.identifier, type_arguments.join(", "))
+        }
+    }
+}
+
+impl TypeArgument {
+    pub fn str_java(&self) -> String {
+        match self {
+            TypeArgument::Wildcard => String::from("?"),
+            TypeArgument::Exact { reference_type } => reference_type.str_java(),
+            TypeArgument::Extends { reference_type } => format!("? extends {}", reference_type.str_java()),
+            TypeArgument::Super { reference_type } => format!("? super {}", reference_type.str_java())
+        }
+    }
+}
+
+impl ResultSignature {
+    pub fn str_java(&self) -> String {
+        match self {
+            ResultSignature::Void => String::from("void"),
+            ResultSignature::JavaType { java_type } => java_type.str_java()
+        }
+    }
+}
+
+impl ThrowsSignature {
+    pub fn str_java(&self) -> String {
+        match self {
+            ThrowsSignature::ClassType { class_type } => class_type.str_java(),
+            ThrowsSignature::TypeVariable { identifier } => identifier.clone()
+        }
+    }
+}
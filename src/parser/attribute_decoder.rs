@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Debug;
+use crate::parser::ast::CpInfo;
+use crate::parser::error::ClassFormatError;
+
+/// Value of a non-standard attribute, returned by the decoder registered for
+/// its name.
+pub trait CustomAttribute: Debug {
+    /// Lines printed below the attribute name in the verbose output.
+    fn lines(&self, cp_info: &[CpInfo]) -> Vec<String>;
+}
+
+/// Decode the `info` bytes of an attribute. The offsets of the returned
+/// errors are relative to the start of `info`.
+pub type AttributeDecoder = Box<dyn Fn(&[u8], &[CpInfo]) -> Result<Box<dyn CustomAttribute>, ClassFormatError>>;
+
+/// Decoders of non-standard attributes by attribute name. The attributes
+/// defined by the JVMS are always decoded by the parser, the attributes
+/// without a decoder are kept as raw bytes in `AttributeInfo::Unknown`.
+/// Ex.:
+/// let mut decoders = AttributeDecoders::new();
+/// decoders.register("ScalaSig", |info, _cp_info| Ok(Box::new(ScalaSig::parse(info)?)));
+/// let class_file = parse_class_file_with_decoders(&bytecode, &decoders)?;
+#[derive(Default)]
+pub struct AttributeDecoders {
+    decoders: HashMap<String, AttributeDecoder>
+}
+
+impl AttributeDecoders {
+    pub fn new() -> AttributeDecoders {
+        AttributeDecoders::default()
+    }
+
+    /// Decode the attributes named `attribute_name` with `decoder`, replacing
+    /// the decoder previously registered for that name.
+    pub fn register<F>(&mut self, attribute_name: &str, decoder: F)
+        where F: Fn(&[u8], &[CpInfo]) -> Result<Box<dyn CustomAttribute>, ClassFormatError> + 'static {
+        self.decoders.insert(String::from(attribute_name), Box::new(decoder));
+    }
+
+    pub fn get(&self, attribute_name: &str) -> Option<&AttributeDecoder> {
+        self.decoders.get(attribute_name)
+    }
+}
+
+impl Debug for AttributeDecoders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.decoders.keys()).finish()
+    }
+}
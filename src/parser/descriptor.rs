@@ -0,0 +1,151 @@
+use crate::parser::ast::{FieldType, FieldTypeTerm, MethodDescriptor, ReturnType};
+use crate::parser::error::{ClassFormatError, ClassFormatErrorKind};
+use crate::parser::ParseResult;
+
+/// Maximum number of array dimensions allowed in a descriptor.
+const MAX_ARRAY_DIMENSIONS: usize = 255;
+
+/// Parse a field descriptor. Ex.: I, [[J, Ljava/lang/String;
+pub fn parse_field_descriptor(descriptor: &str) -> Result<FieldType, ClassFormatError> {
+    let (idx, field_type) = parse_field_type(0, descriptor)?;
+    if idx != descriptor.len() {
+        return Err(invalid_descriptor(idx, descriptor));
+    }
+    Ok(field_type)
+}
+
+/// Parse a method descriptor. Ex.: (IJ[Z)V, ([Ljava/lang/String;)V
+pub fn parse_method_descriptor(descriptor: &str) -> Result<MethodDescriptor, ClassFormatError> {
+    let bytes = descriptor.as_bytes();
+    if bytes.first() != Some(&b'(') {
+        return Err(invalid_descriptor(0, descriptor));
+    }
+    let mut idx = 1;
+    let mut params: Vec<FieldType> = Vec::new();
+    loop {
+        match bytes.get(idx) {
+            Some(b')') => break,
+            Some(_) => {
+                let (i, param) = parse_field_type(idx, descriptor)?;
+                idx = i;
+                params.push(param);
+            },
+            None => return Err(invalid_descriptor(idx, descriptor))
+        }
+    }
+    let (idx, ret) = parse_return_type(idx + 1, descriptor)?;
+    if idx != descriptor.len() {
+        return Err(invalid_descriptor(idx, descriptor));
+    }
+    Ok(MethodDescriptor { params, ret })
+}
+
+fn parse_return_type(idx: usize, descriptor: &str) -> ParseResult<ReturnType> {
+    if descriptor.as_bytes().get(idx) == Some(&b'V') {
+        Ok((idx + 1, ReturnType::Void))
+    } else {
+        let (idx, field_type) = parse_field_type(idx, descriptor)?;
+        Ok((idx, ReturnType::FieldType(field_type)))
+    }
+}
+
+fn parse_field_type(idx: usize, descriptor: &str) -> ParseResult<FieldType> {
+    let bytes = descriptor.as_bytes();
+    let mut dimensions = 0;
+    while bytes.get(idx + dimensions) == Some(&b'[') {
+        dimensions += 1;
+    }
+    if dimensions > MAX_ARRAY_DIMENSIONS {
+        return Err(invalid_descriptor(idx, descriptor));
+    }
+    let (idx, mut field_type) = parse_component_type(idx + dimensions, descriptor)?;
+    for _ in 0..dimensions {
+        field_type = FieldType::ArrayType { field_type: Box::new(field_type) };
+    }
+    Ok((idx, field_type))
+}
+
+/// A base type or a class type, array types are handled by the caller.
+fn parse_component_type(idx: usize, descriptor: &str) -> ParseResult<FieldType> {
+    let term = match descriptor.as_bytes().get(idx) {
+        Some(b'B') => FieldTypeTerm::B,
+        Some(b'C') => FieldTypeTerm::C,
+        Some(b'D') => FieldTypeTerm::D,
+        Some(b'F') => FieldTypeTerm::F,
+        Some(b'I') => FieldTypeTerm::I,
+        Some(b'J') => FieldTypeTerm::J,
+        Some(b'S') => FieldTypeTerm::S,
+        Some(b'Z') => FieldTypeTerm::Z,
+        Some(b'L') => return parse_object_type(idx + 1, descriptor),
+        _ => return Err(invalid_descriptor(idx, descriptor))
+    };
+    Ok((idx + 1, FieldType::BaseType { term }))
+}
+
+/// The class name of an object type, `idx` is the position right after `L`.
+fn parse_object_type(idx: usize, descriptor: &str) -> ParseResult<FieldType> {
+    match descriptor[idx..].find(';') {
+        Some(0) | None => Err(invalid_descriptor(idx, descriptor)),
+        Some(len) => {
+            let class_name = &descriptor[idx..idx + len];
+            if class_name.contains(['.', '[']) {
+                return Err(invalid_descriptor(idx, descriptor));
+            }
+            Ok((idx + len + 1, FieldType::ObjectType { class_name: String::from(class_name) }))
+        }
+    }
+}
+
+fn invalid_descriptor(idx: usize, descriptor: &str) -> ClassFormatError {
+    ClassFormatError::new(idx, ClassFormatErrorKind::InvalidDescriptor(String::from(descriptor)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_descriptor() {
+        assert_eq!(parse_field_descriptor("[[J").unwrap().str_java(), "long[][]");
+        assert_eq!(parse_field_descriptor("Ljava/lang/String;").unwrap().str_java(), "java.lang.String");
+    }
+
+    #[test]
+    fn method_descriptor() {
+        let descriptor = parse_method_descriptor("(IJ[Ljava/lang/String;)V").unwrap();
+        let params: Vec<String> = descriptor.params.iter().map(|p| p.str_java()).collect();
+        assert_eq!(params, vec!["int", "long", "java.lang.String[]"]);
+        assert_eq!(descriptor.ret, ReturnType::Void);
+        assert_eq!(descriptor.params_slot_size(), 4);
+    }
+
+    #[test]
+    fn array_dimensions_limit() {
+        let descriptor = format!("{}I", "[".repeat(MAX_ARRAY_DIMENSIONS));
+        let field_type = parse_field_descriptor(&descriptor).unwrap();
+        assert_eq!(field_type.str_java(), format!("int{}", "[]".repeat(MAX_ARRAY_DIMENSIONS)));
+        let descriptor = format!("{}I", "[".repeat(MAX_ARRAY_DIMENSIONS + 1));
+        let err = parse_field_descriptor(&descriptor).unwrap_err();
+        assert_eq!(err.offset, 0);
+        assert_eq!(err.kind, ClassFormatErrorKind::InvalidDescriptor(descriptor));
+    }
+
+    #[test]
+    fn array_dimensions_limit_in_method_descriptor() {
+        let descriptor = format!("(I{}I)V", "[".repeat(MAX_ARRAY_DIMENSIONS + 1));
+        let err = parse_method_descriptor(&descriptor).unwrap_err();
+        assert_eq!(err.offset, 2);
+    }
+
+    #[test]
+    fn invalid_descriptors() {
+        for descriptor in ["", "V", "L;", "Ljava/lang/String", "Ljava.lang.String;", "II", "[", "()", "(I", "(V)V", "()VI"] {
+            let result = if descriptor.starts_with('(') {
+                parse_method_descriptor(descriptor).map(|_| ())
+            } else {
+                parse_field_descriptor(descriptor).map(|_| ())
+            };
+            assert!(result.is_err(), "{} should be invalid", descriptor);
+        }
+    }
+}
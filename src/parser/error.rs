@@ -0,0 +1,129 @@
+use std::fmt;
+
+/// Error returned when a class file cannot be parsed. It records the byte
+/// offset where parsing failed, the path of the structure being parsed
+/// (ex.: `methods[3].attributes[0].Code`) and the kind of failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassFormatError {
+    pub offset: usize,
+    pub path: Vec<String>,
+    pub kind: ClassFormatErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassFormatErrorKind {
+    /// The input ended before `needed` bytes could be read.
+    UnexpectedEof { needed: usize, available: usize },
+    InvalidConstantPoolTag(u8),
+    InvalidConstantPoolIndex(u16),
+    UnexpectedConstantPoolEntry { index: u16, expected: &'static str },
+    /// Malformed modified UTF-8 in a CONSTANT_Utf8.
+    InvalidUtf8,
+    InvalidOpcode(u8),
+    /// Opcode that cannot follow a `wide` prefix.
+    InvalidWideOpcode(u8),
+    InvalidArrayType(u8),
+    InvalidElementValueTag(u8),
+    InvalidTargetType(u8),
+    InvalidTypePathKind(u8),
+    /// bootstrap_method_attr_index of a CONSTANT_InvokeDynamic or
+    /// CONSTANT_Dynamic without an entry in the BootstrapMethods attribute.
+    InvalidBootstrapMethodIndex(u16),
+    InvalidBranchTarget(i64),
+    /// Frame types 128-246 are reserved.
+    InvalidStackMapFrameType(u8),
+    InvalidVerificationTypeTag(u8),
+    InvalidSwitch(String),
+    /// Malformed field or method descriptor, the offset of the error is the
+    /// position inside the descriptor.
+    InvalidDescriptor(String),
+    /// Malformed generic signature, the offset of the error is the position
+    /// inside the signature.
+    InvalidSignature(String),
+    /// Element values, signatures or descriptors nested deeper than the
+    /// limit, that would overflow the stack of the recursive parsers.
+    NestingTooDeep(usize),
+    /// A structure that is valid per the JVMS but not supported yet.
+    Unsupported(String),
+    /// The class file could not be read, with the message of the I/O error.
+    Io(String),
+}
+
+impl ClassFormatError {
+    pub fn new(offset: usize, kind: ClassFormatErrorKind) -> ClassFormatError {
+        ClassFormatError { offset, path: Vec::new(), kind }
+    }
+
+    /// Prefix the structure path with the structure enclosing the one that
+    /// failed. Errors are wrapped while they propagate, so the outermost
+    /// structure is added last.
+    pub fn within<S: Into<String>>(mut self, structure: S) -> ClassFormatError {
+        self.path.insert(0, structure.into());
+        self
+    }
+
+    pub fn structure(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+impl fmt::Display for ClassFormatErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClassFormatErrorKind::UnexpectedEof { needed, available } =>
+                write!(f, "unexpected end of input: needed {} byte(s), {} available", needed, available),
+            ClassFormatErrorKind::InvalidConstantPoolTag(tag) =>
+                write!(f, "invalid constant pool tag {}", tag),
+            ClassFormatErrorKind::InvalidConstantPoolIndex(index) =>
+                write!(f, "invalid constant pool index {}", index),
+            ClassFormatErrorKind::UnexpectedConstantPoolEntry { index, expected } =>
+                write!(f, "expected {} at constant pool index {}", expected, index),
+            ClassFormatErrorKind::InvalidUtf8 =>
+                write!(f, "invalid modified UTF-8 in CONSTANT_Utf8"),
+            ClassFormatErrorKind::InvalidOpcode(opcode) =>
+                write!(f, "invalid opcode {}", opcode),
+            ClassFormatErrorKind::InvalidWideOpcode(opcode) =>
+                write!(f, "opcode {} cannot be modified by wide", opcode),
+            ClassFormatErrorKind::InvalidArrayType(atype) =>
+                write!(f, "invalid newarray type {}", atype),
+            ClassFormatErrorKind::InvalidElementValueTag(tag) =>
+                write!(f, "invalid element_value tag {}", tag),
+            ClassFormatErrorKind::InvalidTargetType(target_type) =>
+                write!(f, "invalid type annotation target_type {}", target_type),
+            ClassFormatErrorKind::InvalidTypePathKind(type_path_kind) =>
+                write!(f, "invalid type_path_kind {}", type_path_kind),
+            ClassFormatErrorKind::InvalidBootstrapMethodIndex(index) =>
+                write!(f, "invalid bootstrap method index {}", index),
+            ClassFormatErrorKind::InvalidBranchTarget(target) =>
+                write!(f, "invalid branch target {}", target),
+            ClassFormatErrorKind::InvalidStackMapFrameType(frame_type) =>
+                write!(f, "invalid stack map frame_type {}", frame_type),
+            ClassFormatErrorKind::InvalidVerificationTypeTag(tag) =>
+                write!(f, "invalid verification_type_info tag {}", tag),
+            ClassFormatErrorKind::InvalidSwitch(reason) =>
+                write!(f, "invalid switch: {}", reason),
+            ClassFormatErrorKind::InvalidDescriptor(descriptor) =>
+                write!(f, "invalid descriptor {}", descriptor),
+            ClassFormatErrorKind::InvalidSignature(signature) =>
+                write!(f, "invalid signature {}", signature),
+            ClassFormatErrorKind::NestingTooDeep(limit) =>
+                write!(f, "nested deeper than {} levels", limit),
+            ClassFormatErrorKind::Unsupported(what) =>
+                write!(f, "unsupported {}", what),
+            ClassFormatErrorKind::Io(message) =>
+                write!(f, "read error: {}", message),
+        }
+    }
+}
+
+impl fmt::Display for ClassFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{} at offset {:#x}", self.kind, self.offset)
+        } else {
+            write!(f, "{} at offset {:#x} in {}", self.kind, self.offset, self.structure())
+        }
+    }
+}
+
+impl std::error::Error for ClassFormatError {}
@@ -0,0 +1,348 @@
+use crate::parser::ast::{AttributeInfo, ClassFile, CpInfo, FieldInfo, MethodInfo};
+use crate::parser::attribute_decoder::AttributeDecoders;
+use crate::parser::error::ClassFormatError;
+use crate::parser::{get_bytes, get_u2, get_u4, parse_attribute_info, parse_cp_info_array, parse_field_info, parse_interfaces, parse_method_info, ParseOptions, ParseResult};
+
+/// A class file whose header, constant pool and interfaces are parsed up
+/// front. Fields, methods and attributes are only located, they are parsed
+/// when accessed, ex.: to index classes by name, super class and interfaces
+/// without decoding their methods.
+#[derive(Debug)]
+pub struct LazyClassFile<'a> {
+    bytecode: &'a [u8],
+    decoders: &'a AttributeDecoders,
+    options: ParseOptions,
+    pub magic: u32,
+    pub minor_version: u16,
+    pub major_version: u16,
+    pub constant_pool_count: u16,
+    pub cp_info: Vec<CpInfo<'a>>,
+    pub access_flags: u16,
+    pub this_class: u16,
+    pub super_class: u16,
+    pub interfaces_count: u16,
+    pub interfaces: Vec<u16>,
+    pub fields: Vec<LazyMember>,
+    pub methods: Vec<LazyMember>,
+    pub attributes: Vec<LazyAttribute>
+}
+
+/// Header of a field or method, `LazyClassFile::field` and
+/// `LazyClassFile::method` parse its attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LazyMember {
+    /// Offset of the field_info or method_info in the class file
+    pub offset: usize,
+    pub access_flags: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub attributes_count: u16
+}
+
+/// Header of an attribute, `LazyClassFile::attribute` parses it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LazyAttribute {
+    /// Offset of the attribute_info in the class file
+    pub offset: usize,
+    pub attribute_name_index: u16,
+    pub attribute_length: u32
+}
+
+/// Parse the header, constant pool and interfaces of a class file and locate
+/// its fields, methods and attributes. The attributes skipped by `options`
+/// are never parsed.
+pub fn parse_class_file_lazy<'a>(bytecode: &'a [u8], decoders: &'a AttributeDecoders, options: &ParseOptions) -> Result<LazyClassFile<'a>, ClassFormatError> {
+    let idx: usize = 0;
+    let (idx, magic) = get_u4(idx, bytecode).map_err(|e| e.within("magic"))?;
+    let (idx, minor_version) = get_u2(idx, bytecode).map_err(|e| e.within("minor_version"))?;
+    let (idx, major_version) = get_u2(idx, bytecode).map_err(|e| e.within("major_version"))?;
+    let (idx, constant_pool_count) = get_u2(idx, bytecode).map_err(|e| e.within("constant_pool_count"))?;
+    let (idx, cp_info) = parse_cp_info_array(idx, constant_pool_count, bytecode)?;
+    let (idx, access_flags) = get_u2(idx, bytecode).map_err(|e| e.within("access_flags"))?;
+    let (idx, this_class) = get_u2(idx, bytecode).map_err(|e| e.within("this_class"))?;
+    let (idx, super_class) = get_u2(idx, bytecode).map_err(|e| e.within("super_class"))?;
+    let (idx, interfaces_count) = get_u2(idx, bytecode).map_err(|e| e.within("interfaces_count"))?;
+    let (idx, interfaces) = parse_interfaces(idx, interfaces_count, bytecode)?;
+    let (idx, fields_count) = get_u2(idx, bytecode).map_err(|e| e.within("fields_count"))?;
+    let (idx, fields) = locate_members(idx, fields_count, "fields", bytecode)?;
+    let (idx, methods_count) = get_u2(idx, bytecode).map_err(|e| e.within("methods_count"))?;
+    let (idx, methods) = locate_members(idx, methods_count, "methods", bytecode)?;
+    let (idx, attributes_count) = get_u2(idx, bytecode).map_err(|e| e.within("attributes_count"))?;
+    let (_, attributes) = locate_attributes(idx, attributes_count, bytecode)?;
+
+    Ok(LazyClassFile {
+        bytecode,
+        decoders,
+        options: options.clone(),
+        magic,
+        minor_version,
+        major_version,
+        constant_pool_count,
+        cp_info,
+        access_flags,
+        this_class,
+        super_class,
+        interfaces_count,
+        interfaces,
+        fields,
+        methods,
+        attributes
+    })
+}
+
+impl<'a> LazyClassFile<'a> {
+    /// Parse a field of `fields` with all its attributes.
+    pub fn field(&self, field: &LazyMember) -> Result<FieldInfo<'a>, ClassFormatError> {
+        let (_, field_info) = parse_field_info(field.offset, &self.cp_info, self.decoders, &self.options, self.bytecode)?;
+        Ok(field_info)
+    }
+
+    /// Parse a method of `methods` with all its attributes, its Code is
+    /// decoded unless skipped by the options. To parse only some of the
+    /// attributes, locate them with `member_attributes` and parse them with
+    /// `attribute`.
+    pub fn method(&self, method: &LazyMember) -> Result<MethodInfo<'a>, ClassFormatError> {
+        let (_, method_info) = parse_method_info(method.offset, &self.cp_info, self.decoders, &self.options, self.bytecode)?;
+        Ok(method_info)
+    }
+
+    /// Locate the attributes of a field or method.
+    pub fn member_attributes(&self, member: &LazyMember) -> Result<Vec<LazyAttribute>, ClassFormatError> {
+        // access_flags, name_index, descriptor_index and attributes_count
+        let (_, attributes) = locate_attributes(member.offset + 8, member.attributes_count, self.bytecode)?;
+        Ok(attributes)
+    }
+
+    /// Parse an attribute of the class or of one of its members, None when
+    /// it is skipped by the options.
+    pub fn attribute(&self, attribute: &LazyAttribute) -> Result<Option<AttributeInfo<'a>>, ClassFormatError> {
+        let (_, attribute_info) = parse_attribute_info(attribute.offset, &self.cp_info, self.decoders, &self.options, self.bytecode)?;
+        Ok(attribute_info)
+    }
+
+    /// Parse the fields, methods and attributes.
+    pub fn into_class_file(self) -> Result<ClassFile<'a>, ClassFormatError> {
+        let fields = self.fields.iter().enumerate()
+            .map(|(n, field)| self.field(field).map_err(|e| e.within(format!("fields[{}]", n))))
+            .collect::<Result<Vec<FieldInfo>, ClassFormatError>>()?;
+        let methods = self.methods.iter().enumerate()
+            .map(|(n, method)| self.method(method).map_err(|e| e.within(format!("methods[{}]", n))))
+            .collect::<Result<Vec<MethodInfo>, ClassFormatError>>()?;
+        let mut attributes: Vec<AttributeInfo> = Vec::with_capacity(self.attributes.len());
+        for (n, attribute) in self.attributes.iter().enumerate() {
+            if let Some(attribute_info) = self.attribute(attribute).map_err(|e| e.within(format!("attributes[{}]", n)))? {
+                attributes.push(attribute_info);
+            }
+        }
+        Ok(ClassFile {
+            magic: self.magic,
+            minor_version: self.minor_version,
+            major_version: self.major_version,
+            constant_pool_count: self.constant_pool_count,
+            cp_info: self.cp_info,
+            access_flags: self.access_flags,
+            this_class: self.this_class,
+            super_class: self.super_class,
+            interfaces_count: self.interfaces_count,
+            interfaces: self.interfaces,
+            fields_count: self.fields.len() as u16,
+            fields,
+            methods_count: self.methods.len() as u16,
+            methods,
+            attributes_count: self.attributes.len() as u16,
+            attributes
+        })
+    }
+}
+
+fn locate_members(idx: usize, count: u16, table_name: &str, bytecode: &[u8]) -> ParseResult<Vec<LazyMember>> {
+    let mut idx = idx;
+    let mut members: Vec<LazyMember> = Vec::with_capacity(count as usize);
+    for n in 0..count as usize {
+        let (i, member) = locate_member(idx, bytecode).map_err(|e| e.within(format!("{}[{}]", table_name, n)))?;
+        idx = i;
+        members.push(member);
+    }
+    Ok((idx, members))
+}
+
+fn locate_member(idx: usize, bytecode: &[u8]) -> ParseResult<LazyMember> {
+    let offset = idx;
+    let (idx, access_flags) = get_u2(idx, bytecode)?;
+    let (idx, name_index) = get_u2(idx, bytecode)?;
+    let (idx, descriptor_index) = get_u2(idx, bytecode)?;
+    let (idx, attributes_count) = get_u2(idx, bytecode)?;
+    let mut idx = idx;
+    for n in 0..attributes_count as usize {
+        let (i, _) = locate_attribute(idx, bytecode).map_err(|e| e.within(format!("attributes[{}]", n)))?;
+        idx = i;
+    }
+    Ok((idx, LazyMember { offset, access_flags, name_index, descriptor_index, attributes_count }))
+}
+
+fn locate_attributes(idx: usize, count: u16, bytecode: &[u8]) -> ParseResult<Vec<LazyAttribute>> {
+    let mut idx = idx;
+    let mut attributes: Vec<LazyAttribute> = Vec::with_capacity(count as usize);
+    for n in 0..count as usize {
+        let (i, attribute) = locate_attribute(idx, bytecode).map_err(|e| e.within(format!("attributes[{}]", n)))?;
+        idx = i;
+        attributes.push(attribute);
+    }
+    Ok((idx, attributes))
+}
+
+fn locate_attribute(idx: usize, bytecode: &[u8]) -> ParseResult<LazyAttribute> {
+    let offset = idx;
+    let (idx, attribute_name_index) = get_u2(idx, bytecode)?;
+    let (idx, attribute_length) = get_u4(idx, bytecode)?;
+    let (idx, _) = get_bytes(idx, attribute_length as usize, bytecode)?;
+    Ok((idx, LazyAttribute { offset, attribute_name_index, attribute_length }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast::ClassFile;
+    use crate::parser::error::ClassFormatErrorKind;
+    use crate::parser::parse_class_file;
+
+    /// public class A {
+    ///     public static final int f = 1;
+    ///     public static void m(int n) { nop; return; }
+    /// }
+    /// with the debug attributes and a StackMapTable, without the constructor.
+    fn class_file_bytes() -> Vec<u8> {
+        let mut bytes: Vec<u8> = vec![0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x34, 0x00, 0x13];
+        let utf8 = |bytes: &mut Vec<u8>, str: &str| {
+            bytes.extend([1, 0, str.len() as u8]);
+            bytes.extend(str.as_bytes());
+        };
+        bytes.extend([7, 0, 2]);
+        utf8(&mut bytes, "A");
+        bytes.extend([7, 0, 4]);
+        utf8(&mut bytes, "java/lang/Object");
+        utf8(&mut bytes, "Code");
+        utf8(&mut bytes, "m");
+        utf8(&mut bytes, "(I)V");
+        utf8(&mut bytes, "f");
+        utf8(&mut bytes, "I");
+        bytes.extend([3, 0, 0, 0, 1]);
+        utf8(&mut bytes, "ConstantValue");
+        utf8(&mut bytes, "LineNumberTable");
+        utf8(&mut bytes, "LocalVariableTable");
+        utf8(&mut bytes, "MethodParameters");
+        utf8(&mut bytes, "StackMapTable");
+        utf8(&mut bytes, "n");
+        utf8(&mut bytes, "SourceFile");
+        utf8(&mut bytes, "A.java");
+        // access_flags, this_class, super_class and interfaces_count
+        bytes.extend([0x00, 0x21, 0x00, 0x01, 0x00, 0x03, 0x00, 0x00]);
+        // The field and its ConstantValue
+        bytes.extend([0x00, 0x01, 0x00, 0x19, 0x00, 0x08, 0x00, 0x09, 0x00, 0x01]);
+        bytes.extend([0x00, 0x0B, 0x00, 0x00, 0x00, 0x02, 0x00, 0x0A]);
+        // The method, its Code: nop, return and its MethodParameters
+        bytes.extend([0x00, 0x01, 0x00, 0x09, 0x00, 0x06, 0x00, 0x07, 0x00, 0x02]);
+        bytes.extend([0x00, 0x05, 0x00, 0x00, 0x00, 0x35, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02]);
+        bytes.extend([0x00, 0xB1, 0x00, 0x00, 0x00, 0x03]);
+        bytes.extend([0x00, 0x0C, 0x00, 0x00, 0x00, 0x06, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01]);
+        bytes.extend([0x00, 0x0D, 0x00, 0x00, 0x00, 0x0C, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x10, 0x00, 0x09, 0x00, 0x00]);
+        bytes.extend([0x00, 0x0F, 0x00, 0x00, 0x00, 0x03, 0x00, 0x01, 0x01]);
+        bytes.extend([0x00, 0x0E, 0x00, 0x00, 0x00, 0x05, 0x01, 0x00, 0x10, 0x00, 0x00]);
+        // The SourceFile
+        bytes.extend([0x00, 0x01, 0x00, 0x11, 0x00, 0x00, 0x00, 0x02, 0x00, 0x12]);
+        bytes
+    }
+
+    fn parse_with_options<'a>(bytes: &'a [u8], decoders: &'a AttributeDecoders, options: &ParseOptions) -> ClassFile<'a> {
+        parse_class_file_lazy(bytes, decoders, options).unwrap().into_class_file().unwrap()
+    }
+
+    fn attribute_names(attributes: &[AttributeInfo], class_file: &ClassFile) -> Vec<String> {
+        attributes.iter()
+            .map(|attribute| match &class_file.cp_info[attribute.header().0 as usize] {
+                CpInfo::ConstantUtf8 { bytes_str, .. } => bytes_str.to_string(),
+                cp_item => panic!("Expected ConstantUtf8, found {:?}", cp_item)
+            })
+            .collect()
+    }
+
+    /// Names of the attributes of the method and of its Code.
+    fn method_attribute_names(class_file: &ClassFile) -> (Vec<String>, Vec<String>) {
+        let attributes = &class_file.methods[0].attributes;
+        let code_attributes = attributes.iter()
+            .find_map(|attribute| match attribute {
+                AttributeInfo::Code { attributes, .. } => Some(attribute_names(attributes, class_file)),
+                _ => None
+            })
+            .unwrap_or_default();
+        (attribute_names(attributes, class_file), code_attributes)
+    }
+
+    #[test]
+    fn into_class_file_parses_like_parse_class_file() {
+        let bytes = class_file_bytes();
+        let decoders = AttributeDecoders::new();
+        let class_file = parse_with_options(&bytes, &decoders, &ParseOptions::default());
+        assert_eq!(format!("{:?}", class_file), format!("{:?}", parse_class_file(&bytes).unwrap()));
+        assert_eq!(method_attribute_names(&class_file), (
+            vec![String::from("Code"), String::from("MethodParameters")],
+            vec![String::from("LineNumberTable"), String::from("LocalVariableTable"), String::from("StackMapTable")]
+        ));
+        assert_eq!(attribute_names(&class_file.attributes, &class_file), ["SourceFile"]);
+    }
+
+    #[test]
+    fn member_attribute() {
+        let bytes = class_file_bytes();
+        let decoders = AttributeDecoders::new();
+        let lazy_class_file = parse_class_file_lazy(&bytes, &decoders, &ParseOptions::default()).unwrap();
+        assert_eq!(lazy_class_file.methods[0].name_index, 6);
+        let attributes = lazy_class_file.member_attributes(&lazy_class_file.methods[0]).unwrap();
+        assert_eq!(attributes.iter().map(|attribute| attribute.attribute_name_index).collect::<Vec<u16>>(), [5, 14]);
+        let code = lazy_class_file.attribute(&attributes[0]).unwrap().unwrap();
+        assert!(matches!(code, AttributeInfo::Code { max_locals: 1, ref code, attributes_count: 3, .. } if code[..] == [0x00, 0xB1]));
+    }
+
+    #[test]
+    fn skip_debug() {
+        let bytes = class_file_bytes();
+        let decoders = AttributeDecoders::new();
+        let class_file = parse_with_options(&bytes, &decoders, &ParseOptions { skip_debug: true, ..ParseOptions::default() });
+        assert_eq!(method_attribute_names(&class_file), (vec![String::from("Code")], vec![String::from("StackMapTable")]));
+        assert!(class_file.attributes.is_empty());
+        assert_eq!(class_file.attributes_count, 1);
+        assert_eq!(attribute_names(&class_file.fields[0].attributes, &class_file), ["ConstantValue"]);
+    }
+
+    #[test]
+    fn skip_frames() {
+        let bytes = class_file_bytes();
+        let decoders = AttributeDecoders::new();
+        let class_file = parse_with_options(&bytes, &decoders, &ParseOptions { skip_frames: true, ..ParseOptions::default() });
+        assert_eq!(method_attribute_names(&class_file), (
+            vec![String::from("Code"), String::from("MethodParameters")],
+            vec![String::from("LineNumberTable"), String::from("LocalVariableTable")]
+        ));
+    }
+
+    #[test]
+    fn skip_code() {
+        let bytes = class_file_bytes();
+        let decoders = AttributeDecoders::new();
+        let class_file = parse_with_options(&bytes, &decoders, &ParseOptions { skip_code: true, ..ParseOptions::default() });
+        assert_eq!(method_attribute_names(&class_file), (vec![String::from("MethodParameters")], vec![]));
+        assert_eq!(class_file.methods[0].attributes_count, 2);
+    }
+
+    #[test]
+    fn truncated_member_table() {
+        let bytes = class_file_bytes();
+        // The field_info and its ConstantValue, cut 1 byte short
+        let field = [0x00, 0x19, 0x00, 0x08, 0x00, 0x09, 0x00, 0x01];
+        let field_end = bytes.windows(field.len()).position(|w| w == field).unwrap() + field.len() + 8;
+        let decoders = AttributeDecoders::new();
+        let err = parse_class_file_lazy(&bytes[..field_end - 1], &decoders, &ParseOptions::default()).unwrap_err();
+        assert_eq!(err.path, [String::from("fields[0]"), String::from("attributes[0]")]);
+        assert_eq!(err.kind, ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 1 });
+    }
+}
@@ -1,31 +1,88 @@
-use crate::parser::ast::{AttributeInfo, ClassFile, CpInfo, ExceptionTable, FieldInfo, LineNumberTableItem, MethodInfo};
-use crate::parser::ast::AttributeInfo::{LineNumberTable, SourceFile};
+use std::borrow::Cow;
+use crate::parser::ast::{Annotation, AttributeInfo, BootstrapMethod, ClassFile, CpInfo, ElementValue, ElementValuePair, ExceptionTable, FieldInfo, InnerClass, LineNumberTableItem, LocalVariableTableItem, LocalVariableTypeTableItem, LocalvarTargetItem, MethodInfo, MethodParameter, ModuleExports, ModuleOpens, ModuleProvides, ModuleRequires, RecordComponentInfo, StackMapFrame, TargetInfo, TargetType, TypeAnnotation, TypePathEntry, TypePathKind, VerificationTypeInfo};
+use crate::parser::ast::AttributeInfo::{AnnotationDefault, BootstrapMethods, ConstantValue, Custom, Deprecated, EnclosingMethod, Exceptions, InnerClasses, LineNumberTable, LocalVariableTable, LocalVariableTypeTable, MethodParameters, Module, ModuleMainClass, ModulePackages, NestHost, NestMembers, PermittedSubclasses, Record, RuntimeInvisibleAnnotations, RuntimeInvisibleParameterAnnotations, RuntimeInvisibleTypeAnnotations, RuntimeVisibleAnnotations, RuntimeVisibleParameterAnnotations, RuntimeVisibleTypeAnnotations, Signature, SourceDebugExtension, SourceFile, StackMapTable, Synthetic, Unknown};
+use crate::parser::attribute_decoder::AttributeDecoders;
+use crate::parser::error::{ClassFormatError, ClassFormatErrorKind};
+use crate::parser::modified_utf8::decode_modified_utf8;
 
 pub mod access_flags;
 pub mod ast;
+pub mod attribute_decoder;
+pub mod descriptor;
+pub mod error;
+pub mod lazy;
+pub mod modified_utf8;
 pub mod opcodes;
 pub mod parser_helper;
+pub mod signature;
+pub mod stream;
+pub mod validate;
 
-pub fn parse_class_file(bytecode: &Vec<u8>) -> ClassFile {
+/// Result of parsing a structure: the index right after the structure and
+/// the structure itself.
+pub type ParseResult<T> = Result<(usize, T), ClassFormatError>;
+
+/// Maximum nesting of array and annotation element values, deeper values
+/// would overflow the stack of the recursive parser.
+const MAX_ELEMENT_VALUE_DEPTH: usize = 256;
+
+/// Attributes left out of the parsed class file, like ASM's SKIP_CODE,
+/// SKIP_DEBUG and SKIP_FRAMES. The skipped attributes are not in the
+/// `attributes` of the class, its members and their Code but still count in
+/// `attributes_count`.
+#[derive(Debug, Default, Clone)]
+pub struct ParseOptions {
+    /// Skip the Code of methods.
+    pub skip_code: bool,
+    /// Skip SourceFile, SourceDebugExtension, LineNumberTable,
+    /// LocalVariableTable, LocalVariableTypeTable and MethodParameters.
+    pub skip_debug: bool,
+    /// Skip StackMapTable.
+    pub skip_frames: bool,
+}
+
+impl ParseOptions {
+    pub fn skips(&self, attribute_name: &str) -> bool {
+        match attribute_name {
+            "Code" => self.skip_code,
+            "SourceFile" | "SourceDebugExtension" | "LineNumberTable" | "LocalVariableTable" |
+            "LocalVariableTypeTable" | "MethodParameters" => self.skip_debug,
+            "StackMapTable" => self.skip_frames,
+            _ => false
+        }
+    }
+}
+
+pub fn parse_class_file(bytecode: &[u8]) -> Result<ClassFile<'_>, ClassFormatError> {
+    parse_class_file_with_decoders(bytecode, &AttributeDecoders::new())
+}
+
+/// Parse a class file decoding its non-standard attributes with `decoders`.
+pub fn parse_class_file_with_decoders<'a>(bytecode: &'a [u8], decoders: &AttributeDecoders) -> Result<ClassFile<'a>, ClassFormatError> {
+    parse_class_file_with_options(bytecode, decoders, &ParseOptions::default())
+}
+
+/// Parse a class file leaving out the attributes skipped by `options`.
+pub fn parse_class_file_with_options<'a>(bytecode: &'a [u8], decoders: &AttributeDecoders, options: &ParseOptions) -> Result<ClassFile<'a>, ClassFormatError> {
     let idx: usize = 0;
-    let (idx, magic) = get_u4(idx, bytecode);
-    let (idx, minor_version) = get_u2(idx, bytecode);
-    let (idx, major_version) = get_u2(idx, bytecode);
-    let (idx, constant_pool_count) = get_u2(idx, bytecode);
-    let (idx, cp_info) = parse_cp_info_array(idx, constant_pool_count, bytecode);
-    let (idx, access_flags) = get_u2(idx, bytecode);
-    let (idx, this_class) = get_u2(idx, bytecode);
-    let (idx, super_class) = get_u2(idx, bytecode);
-    let (idx, interfaces_count) = get_u2(idx, bytecode);
-    let (idx, interfaces) = parse_interfaces(idx, interfaces_count, bytecode);
-    let (idx, fields_count) = get_u2(idx, bytecode);
-    let (idx, fields) = parse_fields(idx, fields_count, bytecode);
-    let (idx, methods_count) = get_u2(idx, bytecode);
-    let (idx, methods) = parse_methods(idx, methods_count, &cp_info,bytecode);
-    let (idx, attributes_count) = get_u2(idx, bytecode);
-    let (_, attributes) = parse_attributes(idx, attributes_count, &cp_info, bytecode);
-
-    ClassFile {
+    let (idx, magic) = get_u4(idx, bytecode).map_err(|e| e.within("magic"))?;
+    let (idx, minor_version) = get_u2(idx, bytecode).map_err(|e| e.within("minor_version"))?;
+    let (idx, major_version) = get_u2(idx, bytecode).map_err(|e| e.within("major_version"))?;
+    let (idx, constant_pool_count) = get_u2(idx, bytecode).map_err(|e| e.within("constant_pool_count"))?;
+    let (idx, cp_info) = parse_cp_info_array(idx, constant_pool_count, bytecode)?;
+    let (idx, access_flags) = get_u2(idx, bytecode).map_err(|e| e.within("access_flags"))?;
+    let (idx, this_class) = get_u2(idx, bytecode).map_err(|e| e.within("this_class"))?;
+    let (idx, super_class) = get_u2(idx, bytecode).map_err(|e| e.within("super_class"))?;
+    let (idx, interfaces_count) = get_u2(idx, bytecode).map_err(|e| e.within("interfaces_count"))?;
+    let (idx, interfaces) = parse_interfaces(idx, interfaces_count, bytecode)?;
+    let (idx, fields_count) = get_u2(idx, bytecode).map_err(|e| e.within("fields_count"))?;
+    let (idx, fields) = parse_fields(idx, fields_count, &cp_info, decoders, options, bytecode)?;
+    let (idx, methods_count) = get_u2(idx, bytecode).map_err(|e| e.within("methods_count"))?;
+    let (idx, methods) = parse_methods(idx, methods_count, &cp_info, decoders, options, bytecode)?;
+    let (idx, attributes_count) = get_u2(idx, bytecode).map_err(|e| e.within("attributes_count"))?;
+    let (_, attributes) = parse_attributes(idx, attributes_count, &cp_info, decoders, options, bytecode)?;
+
+    Ok(ClassFile {
         magic,
         minor_version,
         major_version,
@@ -42,242 +99,373 @@ pub fn parse_class_file(bytecode: &Vec<u8>) -> ClassFile {
         methods,
         attributes_count,
         attributes
-    }
+    })
 }
 
-fn parse_cp_info_array(idx: usize, constant_pool_count: u16, bytecode: &Vec<u8>) -> (usize, Vec<CpInfo>) {
-    let mut cp_infos: Vec<CpInfo> = Vec::new();
-    let dummy = CpInfo::ConstantUtf8 { tag: 0, length: 0, bytes: vec![], bytes_str: String::from("Dummy Value") };
-    cp_infos.push(dummy);
+fn parse_cp_info_array(idx: usize, constant_pool_count: u16, bytecode: &[u8]) -> ParseResult<Vec<CpInfo<'_>>> {
+    let mut cp_infos: Vec<CpInfo> = Vec::with_capacity(constant_pool_count as usize);
+    cp_infos.push(CpInfo::Unusable);
     let mut idx = idx;
-    for _ in 1..constant_pool_count {
-        let (new_idx, cp_info) = parse_cp_info(idx, bytecode);
+    let mut i: u16 = 1;
+    while i < constant_pool_count {
+        let (new_idx, cp_info) = parse_cp_info(idx, bytecode).map_err(|e| e.within(format!("constant_pool[{}]", i)))?;
+        // CONSTANT_Long and CONSTANT_Double take up two entries in the
+        // constant pool, the second one is valid but unusable.
+        let two_slots = matches!(cp_info, CpInfo::ConstantLong { .. } | CpInfo::ConstantDouble { .. });
+        if two_slots && i + 1 == constant_pool_count {
+            return Err(two_slots_in_last_entry(idx, i));
+        }
         idx = new_idx;
         cp_infos.push(cp_info);
+        if two_slots {
+            cp_infos.push(CpInfo::Unusable);
+            i += 1;
+        }
+        i += 1;
     }
-    (idx, cp_infos)
+    Ok((idx, cp_infos))
+}
+
+/// Error for a CONSTANT_Long or CONSTANT_Double at `index`, the last entry
+/// of the constant pool, whose second entry would be past its end.
+fn two_slots_in_last_entry(idx: usize, index: u16) -> ClassFormatError {
+    ClassFormatError::new(idx, ClassFormatErrorKind::InvalidConstantPoolIndex(index + 1))
+        .within(format!("constant_pool[{}]", index))
 }
 
-fn parse_cp_info(idx: usize, bytecode: &Vec<u8>) -> (usize, CpInfo) {
-    let (idx, tag) = get_u1(idx, bytecode);
+fn parse_cp_info(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, tag) = get_u1(idx, bytecode)?;
     match tag {
         7 => parse_constant_class(idx, bytecode),
         9 => parse_constant_fieldref(idx, bytecode),
         10 => parse_constant_methodref(idx, bytecode),
-        11 => todo!("CONSTANT_InterfaceMethodref"), // CONSTANT_InterfaceMethodref
+        11 => parse_constant_interface_methodref(idx, bytecode),
         8 => parse_constant_string(idx, bytecode),
-        3 => todo!("CONSTANT_Integer"), // CONSTANT_Integer
-        4 => todo!("CONSTANT_Float"), // CONSTANT_Float
-        5 => todo!("CONSTANT_Long"), // CONSTANT_Long
-        6 => todo!(), // CONSTANT_Double
+        3 => parse_constant_integer(idx, bytecode),
+        4 => parse_constant_float(idx, bytecode),
+        5 => parse_constant_long(idx, bytecode),
+        6 => parse_constant_double(idx, bytecode),
         12 => parse_constant_name_and_type(idx, bytecode),
         1 => parse_constant_utf8(idx, bytecode),
-        15 => todo!(), // CONSTANT_MethodHandle
-        16 => todo!(), // CONSTANT_MethodType
-        17 => todo!(), // CONSTANT_Dynamic
-        18 => todo!(), // CONSTANT_InvokeDynamic
-        19 => todo!(), // CONSTANT_Module
-        20 => todo!(), // CONSTANT_Package
-        _ => todo!() // fail hard
+        15 => parse_constant_method_handle(idx, bytecode),
+        16 => parse_constant_method_type(idx, bytecode),
+        17 => parse_constant_dynamic(idx, bytecode),
+        18 => parse_constant_invoke_dynamic(idx, bytecode),
+        19 => parse_constant_module(idx, bytecode),
+        20 => parse_constant_package(idx, bytecode),
+        _ => Err(ClassFormatError::new(idx - 1, ClassFormatErrorKind::InvalidConstantPoolTag(tag)))
     }
 }
 
 /// 7 CONSTANT_Class
-fn parse_constant_class(idx: usize, bytecode: &Vec<u8>) -> (usize, CpInfo) {
-    let (idx, name_index) = get_u2(idx, bytecode);
-    (idx, CpInfo::ConstantClass { tag: 7, name_index })
+fn parse_constant_class(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, name_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantClass { tag: 7, name_index }))
 }
 
 /// 9 CONSTANT_Fieldref
-fn parse_constant_fieldref(idx: usize, bytecode: &Vec<u8>) -> (usize, CpInfo) {
-    let (idx, class_index) = get_u2(idx, bytecode);
-    let (idx, name_and_type_index) = get_u2(idx, bytecode);
-    (idx, CpInfo::ConstantFieldref { tag: 9, class_index, name_and_type_index })
+fn parse_constant_fieldref(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, class_index) = get_u2(idx, bytecode)?;
+    let (idx, name_and_type_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantFieldref { tag: 9, class_index, name_and_type_index }))
 }
 
 /// 10 CONSTANT_Methodref
-fn parse_constant_methodref(idx: usize, bytecode: &Vec<u8>) -> (usize, CpInfo) {
-    let (idx, class_index) = get_u2(idx, bytecode);
-    let (idx, name_and_type_index) = get_u2(idx, bytecode);
-    (idx, CpInfo::ConstantMethodref { tag: 10, class_index, name_and_type_index })
+fn parse_constant_methodref(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, class_index) = get_u2(idx, bytecode)?;
+    let (idx, name_and_type_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantMethodref { tag: 10, class_index, name_and_type_index }))
+}
+
+/// 11 CONSTANT_InterfaceMethodref
+fn parse_constant_interface_methodref(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, class_index) = get_u2(idx, bytecode)?;
+    let (idx, name_and_type_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantInterfaceMethodref { tag: 11, class_index, name_and_type_index }))
 }
 
 /// 8 CONSTANT_String
-fn parse_constant_string(idx: usize, bytecode: &Vec<u8>) -> (usize, CpInfo) {
-    let (idx, string_index) = get_u2(idx, bytecode);
-    (idx, CpInfo::ConstantString { tag: 8, string_index })
+fn parse_constant_string(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, string_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantString { tag: 8, string_index }))
+}
+
+/// 3 CONSTANT_Integer
+fn parse_constant_integer(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, bytes) = get_u4(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantInteger { tag: 3, bytes }))
+}
+
+/// 4 CONSTANT_Float
+fn parse_constant_float(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, bytes) = get_u4(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantFloat { tag: 4, bytes }))
+}
+
+/// 5 CONSTANT_Long
+fn parse_constant_long(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, high_bytes) = get_u4(idx, bytecode)?;
+    let (idx, low_bytes) = get_u4(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantLong { tag: 5, high_bytes, low_bytes }))
+}
+
+/// 6 CONSTANT_Double
+fn parse_constant_double(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, high_bytes) = get_u4(idx, bytecode)?;
+    let (idx, low_bytes) = get_u4(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantDouble { tag: 6, high_bytes, low_bytes }))
 }
 
 /// 12 CONSTANT_NameAndType
-fn parse_constant_name_and_type(idx: usize, bytecode: &Vec<u8>) -> (usize, CpInfo) {
-    let (idx, name_index) = get_u2(idx, bytecode);
-    let (idx, descriptor_index) = get_u2(idx, bytecode);
-    (idx, CpInfo::ConstantNameAndType { tag: 12, name_index, descriptor_index })
+fn parse_constant_name_and_type(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, name_index) = get_u2(idx, bytecode)?;
+    let (idx, descriptor_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantNameAndType { tag: 12, name_index, descriptor_index }))
 }
 
 /// 1 CONSTANT_Utf8
-fn parse_constant_utf8(idx: usize, bytecode: &Vec<u8>) -> (usize, CpInfo) {
-    let (idx, length) = get_u2(idx, bytecode);
-    let len = length as usize;
-    let mut bytes: Vec<u8> = Vec::with_capacity(len);
-    for i in 0..len {
-        bytes.push(bytecode[idx + i])
-    }
-    let bytes_str: String = String::from_utf8(bytes.clone()).expect("Invalid UTF-8");
-    (idx + len, CpInfo::ConstantUtf8 { tag: 1, length, bytes, bytes_str })
+fn parse_constant_utf8(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, length) = get_u2(idx, bytecode)?;
+    let (new_idx, bytes) = get_bytes(idx, length as usize, bytecode)?;
+    let bytes_str = decode_modified_utf8(bytes).map_err(|mut e| {
+        e.offset += idx;
+        e
+    })?;
+    Ok((new_idx, CpInfo::ConstantUtf8 { tag: 1, length, bytes: Cow::Borrowed(bytes), bytes_str }))
+}
+
+/// 15 CONSTANT_MethodHandle
+fn parse_constant_method_handle(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, reference_kind) = get_u1(idx, bytecode)?;
+    let (idx, reference_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantMethodHandle { tag: 15, reference_kind, reference_index }))
+}
+
+/// 16 CONSTANT_MethodType
+fn parse_constant_method_type(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, descriptor_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantMethodType { tag: 16, descriptor_index }))
+}
+
+/// 17 CONSTANT_Dynamic
+fn parse_constant_dynamic(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, bootstrap_method_attr_index) = get_u2(idx, bytecode)?;
+    let (idx, name_and_type_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantDynamic { tag: 17, bootstrap_method_attr_index, name_and_type_index }))
+}
+
+/// 18 CONSTANT_InvokeDynamic
+fn parse_constant_invoke_dynamic(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, bootstrap_method_attr_index) = get_u2(idx, bytecode)?;
+    let (idx, name_and_type_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantInvokeDynamic { tag: 18, bootstrap_method_attr_index, name_and_type_index }))
+}
+
+/// 19 CONSTANT_Module
+fn parse_constant_module(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, name_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantModule { tag: 19, name_index }))
+}
+
+/// 20 CONSTANT_Package
+fn parse_constant_package(idx: usize, bytecode: &[u8]) -> ParseResult<CpInfo<'_>> {
+    let (idx, name_index) = get_u2(idx, bytecode)?;
+    Ok((idx, CpInfo::ConstantPackage { tag: 20, name_index }))
 }
 
-fn parse_interfaces(idx: usize, interfaces_count: u16, bytecode: &Vec<u8>) -> (usize, Vec<u16>) {
+fn parse_interfaces(idx: usize, interfaces_count: u16, bytecode: &[u8]) -> ParseResult<Vec<u16>> {
     let count = interfaces_count as usize;
     let mut v: Vec<u16> = Vec::with_capacity(count);
     let mut idx = idx;
-    for _ in 0..count {
-        let (i, interface) = get_u2(idx, bytecode);
+    for n in 0..count {
+        let (i, interface) = get_u2(idx, bytecode).map_err(|e| e.within(format!("interfaces[{}]", n)))?;
         idx = i;
         v.push(interface);
     }
-    (idx, v)
+    Ok((idx, v))
 }
 
-fn parse_fields(idx: usize, fields_count: u16, bytecode: &Vec<u8>) -> (usize, Vec<FieldInfo>) {
+fn parse_fields<'a>(idx: usize, fields_count: u16, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions, bytecode: &'a [u8]) -> ParseResult<Vec<FieldInfo<'a>>> {
     let count = fields_count as usize;
     let mut idx = idx;
     let mut fields: Vec<FieldInfo> = Vec::with_capacity(count);
-    for _ in 0..count {
-        let (i, field_info) = parse_field_info(idx, bytecode);
+    for n in 0..count {
+        let (i, field_info) = parse_field_info(idx, cp_info, decoders, options, bytecode).map_err(|e| e.within(format!("fields[{}]", n)))?;
         idx = i;
         fields.push(field_info);
     }
-    (idx, fields)
+    Ok((idx, fields))
 }
 
-fn parse_field_info(_idx: usize, _bytecode: &Vec<u8>) -> (usize, FieldInfo) {
-    todo!()
+fn parse_field_info<'a>(idx: usize, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions, bytecode: &'a [u8]) -> ParseResult<FieldInfo<'a>> {
+    let (idx, access_flags) = get_u2(idx, bytecode)?;
+    let (idx, name_index) = get_u2(idx, bytecode)?;
+    let (idx, descriptor_index) = get_u2(idx, bytecode)?;
+    let (idx, attributes_count) = get_u2(idx, bytecode)?;
+    let (idx, attributes) = parse_attributes(idx, attributes_count, cp_info, decoders, options, bytecode)?;
+    let field_info = FieldInfo { access_flags, name_index, descriptor_index, attributes_count, attributes };
+    Ok((idx, field_info))
 }
 
-fn parse_methods(idx: usize, methods_count: u16, cp_info: &Vec<CpInfo>, bytecode: &Vec<u8>) -> (usize, Vec<MethodInfo>) {
+fn parse_methods<'a>(idx: usize, methods_count: u16, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions, bytecode: &'a [u8]) -> ParseResult<Vec<MethodInfo<'a>>> {
     let mut idx = idx;
     let count = methods_count as usize;
     let mut attributes: Vec<MethodInfo> = Vec::with_capacity(count);
-    for _ in 0..count {
-        let (i, method_info) = parse_method_info(idx, cp_info, &bytecode);
+    for n in 0..count {
+        let (i, method_info) = parse_method_info(idx, cp_info, decoders, options, bytecode).map_err(|e| e.within(format!("methods[{}]", n)))?;
         idx = i;
         attributes.push(method_info);
     }
-    (idx, attributes)
+    Ok((idx, attributes))
 }
 
-fn parse_method_info(idx: usize, cp_info: &Vec<CpInfo>, bytecode: &Vec<u8>) -> (usize, MethodInfo) {
-    let (idx, access_flags) = get_u2(idx, bytecode);
-    let (idx, name_index) = get_u2(idx, bytecode);
-    let (idx, descriptor_index) = get_u2(idx, bytecode);
-    let (idx, attributes_count) = get_u2(idx, bytecode);
-    let (idx, attributes) = parse_attributes(idx, attributes_count, cp_info, bytecode);
+fn parse_method_info<'a>(idx: usize, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions, bytecode: &'a [u8]) -> ParseResult<MethodInfo<'a>> {
+    let (idx, access_flags) = get_u2(idx, bytecode)?;
+    let (idx, name_index) = get_u2(idx, bytecode)?;
+    let (idx, descriptor_index) = get_u2(idx, bytecode)?;
+    let (idx, attributes_count) = get_u2(idx, bytecode)?;
+    let (idx, attributes) = parse_attributes(idx, attributes_count, cp_info, decoders, options, bytecode)?;
     let method_info = MethodInfo { access_flags, name_index, descriptor_index, attributes_count, attributes };
-    (idx, method_info)
+    Ok((idx, method_info))
 }
 
-fn parse_attributes(idx: usize, attributes_count: u16, cp_info: &Vec<CpInfo>, bytecode: &Vec<u8>) -> (usize, Vec<AttributeInfo>) {
+fn parse_attributes<'a>(idx: usize, attributes_count: u16, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions, bytecode: &'a [u8]) -> ParseResult<Vec<AttributeInfo<'a>>> {
     let mut idx = idx;
     let count = attributes_count as usize;
     let mut attributes: Vec<AttributeInfo> = Vec::with_capacity(count);
-    for _ in 0..count {
-        let (i, attribute_info) = parse_attribute_info(idx, cp_info, bytecode);
+    for n in 0..count {
+        let (i, attribute_info) = parse_attribute_info(idx, cp_info, decoders, options, bytecode).map_err(|e| e.within(format!("attributes[{}]", n)))?;
         idx = i;
-        attributes.push(attribute_info);
+        if let Some(attribute_info) = attribute_info {
+            attributes.push(attribute_info);
+        }
     }
-    (idx, attributes)
+    Ok((idx, attributes))
 }
 
-fn parse_attribute_info(idx: usize, cp_info: &Vec<CpInfo>, bytecode: &Vec<u8>) -> (usize, AttributeInfo) {
-    let (idx, attribute_name_index) = get_u2(idx, bytecode);
-    let (idx, attribute_length) = get_u4(idx, bytecode);
+/// Parse an attribute, None when it is skipped by `options`.
+fn parse_attribute_info<'a>(idx: usize, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions, bytecode: &'a [u8]) -> ParseResult<Option<AttributeInfo<'a>>> {
+    let (idx, attribute_name_index) = get_u2(idx, bytecode)?;
+    let (idx, attribute_length) = get_u4(idx, bytecode)?;
     let cp_info_idx = attribute_name_index as usize;
-    let cp_item = cp_info.get(cp_info_idx).expect("Failed to retrieve constant_pool item");
+    let cp_item = cp_info.get(cp_info_idx)
+        .ok_or_else(|| ClassFormatError::new(idx - 6, ClassFormatErrorKind::InvalidConstantPoolIndex(attribute_name_index)))?;
     let (idx, attribute_info): (usize, AttributeInfo) = if let CpInfo::ConstantUtf8 { tag: _tag, length: _length, bytes: _bytes, bytes_str } = cp_item {
-        match bytes_str.as_str() {
-            "AnnotationDefault" => todo!(),
-            "BootstrapMethods" => todo!(),
-            "Code" => parse_attribute_info_code(idx, attribute_name_index, attribute_length, cp_info, bytecode),
-            "ConstantValue" => todo!(),
-            "Deprecated" => todo!(),
-            "EnclosingMethod" => todo!(),
-            "Exceptions" => todo!(),
-            "InnerClasses" => todo!(),
+        let attribute_name: &str = bytes_str;
+        let (end, _) = get_bytes(idx, attribute_length as usize, bytecode).map_err(|e| e.within(attribute_name))?;
+        if options.skips(attribute_name) {
+            return Ok((end, None));
+        }
+        // Like the lazy and stream parsers, the attribute is parsed within its
+        // attribute_length and parsing continues after it. validate reports
+        // the attributes shorter than their attribute_length.
+        let bytecode = &bytecode[..end];
+        let parsed = match attribute_name {
+            "Code" => parse_attribute_info_code(idx, attribute_name_index, attribute_length, cp_info, decoders, options, bytecode),
             "LineNumberTable" => parse_attribute_info_line_number_table(idx, attribute_name_index, attribute_length, bytecode),
-            "LocalVariableTable" => todo!(),
-            "LocalVariableTypeTable" => todo!(),
-            "MethodParameters" => todo!(),
-            "Module" => todo!(),
-            "ModuleMainClass" => todo!(),
-            "ModulePackages" => todo!(),
-            "NestHost" => todo!(),
-            "NestMembers" => todo!(),
-            "PermittedSubclasses" => todo!(),
-            "Record" => todo!(),
-            "RuntimeInvisibleAnnotations" => todo!(),
-            "RuntimeInvisibleParameterAnnotations" => todo!(),
-            "RuntimeInvisibleTypeAnnotations" => todo!(),
-            "RuntimeVisibleAnnotations" => todo!(),
-            "RuntimeVisibleParameterAnnotations" => todo!(),
-            "RuntimeVisibleTypeAnnotations" => todo!(),
-            "Signature" => todo!(),
-            "SourceDebugExtension" => todo!(),
+            "LocalVariableTable" => parse_attribute_info_local_variable_table(idx, attribute_name_index, attribute_length, bytecode),
+            "LocalVariableTypeTable" => parse_attribute_info_local_variable_type_table(idx, attribute_name_index, attribute_length, bytecode),
+            "StackMapTable" => parse_attribute_info_stack_map_table(idx, attribute_name_index, attribute_length, bytecode),
+            "Module" => parse_attribute_info_module(idx, attribute_name_index, attribute_length, bytecode),
+            "ModulePackages" => parse_attribute_info_module_packages(idx, attribute_name_index, attribute_length, bytecode),
+            "ModuleMainClass" => parse_attribute_info_module_main_class(idx, attribute_name_index, attribute_length, bytecode),
+            "NestHost" => parse_attribute_info_nest_host(idx, attribute_name_index, attribute_length, bytecode),
+            "NestMembers" => parse_attribute_info_classes(idx, attribute_name_index, attribute_length, attribute_name, bytecode),
+            "PermittedSubclasses" => parse_attribute_info_classes(idx, attribute_name_index, attribute_length, attribute_name, bytecode),
+            "Record" => parse_attribute_info_record(idx, attribute_name_index, attribute_length, cp_info, decoders, options, bytecode),
             "SourceFile" => parse_attribute_info_source_file(idx, attribute_name_index, attribute_length, bytecode),
-            "StackMapTable" => todo!(),
-            "Synthetic" => todo!(),
-            _ => todo!()
-        }
+            "ConstantValue" => parse_attribute_info_constant_value(idx, attribute_name_index, attribute_length, bytecode),
+            "Signature" => parse_attribute_info_signature(idx, attribute_name_index, attribute_length, bytecode),
+            "Synthetic" => Ok((idx, Synthetic { attribute_name_index, attribute_length })),
+            "Deprecated" => Ok((idx, Deprecated { attribute_name_index, attribute_length })),
+            "RuntimeVisibleAnnotations" => parse_attribute_info_annotations(idx, attribute_name_index, attribute_length, true, bytecode),
+            "RuntimeInvisibleAnnotations" => parse_attribute_info_annotations(idx, attribute_name_index, attribute_length, false, bytecode),
+            "RuntimeVisibleParameterAnnotations" => parse_attribute_info_parameter_annotations(idx, attribute_name_index, attribute_length, true, bytecode),
+            "RuntimeInvisibleParameterAnnotations" => parse_attribute_info_parameter_annotations(idx, attribute_name_index, attribute_length, false, bytecode),
+            "AnnotationDefault" => parse_attribute_info_annotation_default(idx, attribute_name_index, attribute_length, bytecode),
+            "RuntimeVisibleTypeAnnotations" => parse_attribute_info_type_annotations(idx, attribute_name_index, attribute_length, true, bytecode),
+            "RuntimeInvisibleTypeAnnotations" => parse_attribute_info_type_annotations(idx, attribute_name_index, attribute_length, false, bytecode),
+            "Exceptions" => parse_attribute_info_exceptions(idx, attribute_name_index, attribute_length, bytecode),
+            "MethodParameters" => parse_attribute_info_method_parameters(idx, attribute_name_index, attribute_length, bytecode),
+            "BootstrapMethods" => parse_attribute_info_bootstrap_methods(idx, attribute_name_index, attribute_length, bytecode),
+            "InnerClasses" => parse_attribute_info_inner_classes(idx, attribute_name_index, attribute_length, bytecode),
+            "EnclosingMethod" => parse_attribute_info_enclosing_method(idx, attribute_name_index, attribute_length, bytecode),
+            "SourceDebugExtension" => parse_attribute_info_source_debug_extension(idx, attribute_name_index, attribute_length, bytecode),
+            _ => parse_attribute_info_unknown(idx, attribute_name_index, attribute_length, attribute_name, cp_info, decoders, bytecode)
+        };
+        let (_, attribute_info) = parsed.map_err(|e| e.within(attribute_name))?;
+        (end, attribute_info)
     } else {
-        todo!()
+        let kind = ClassFormatErrorKind::UnexpectedConstantPoolEntry { index: attribute_name_index, expected: "CONSTANT_Utf8" };
+        return Err(ClassFormatError::new(idx - 6, kind));
     };
-    (idx, attribute_info)
+    Ok((idx, Some(attribute_info)))
 }
 
-fn parse_attribute_info_code(idx: usize, attribute_name_index: u16, attribute_length: u32, cp_info: &Vec<CpInfo>, bytecode: &Vec<u8>) -> (usize, AttributeInfo) {
-    let (idx, max_stack) = get_u2(idx, bytecode);
-    let (idx, max_locals) = get_u2(idx, bytecode);
-    let (idx, code_length) = get_u4(idx, bytecode);
-    let mut code: Vec<u8> = Vec::with_capacity(code_length as usize);
-    for i in 0..(code_length as usize) {
-        let (_, byte) = get_u1(idx + i, bytecode);
-        code.push(byte);
-    }
-    let idx = idx + code_length as usize;
-    let (idx, exception_table_length) = get_u2(idx, bytecode);
+/// Attribute not defined by the JVMS, decoded by the decoder registered for
+/// its name or else kept as raw bytes.
+fn parse_attribute_info_unknown<'a>(idx: usize, attribute_name_index: u16, attribute_length: u32, attribute_name: &str, cp_info: &[CpInfo], decoders: &AttributeDecoders, bytecode: &'a [u8]) -> ParseResult<AttributeInfo<'a>> {
+    let (idx_next, info) = get_bytes(idx, attribute_length as usize, bytecode)?;
+    let attribute_info = match decoders.get(attribute_name) {
+        Some(decoder) => {
+            let value = decoder(info, cp_info).map_err(|mut e| {
+                e.offset += idx;
+                e
+            })?;
+            Custom { attribute_name_index, attribute_length, info: Cow::Borrowed(info), value }
+        },
+        None => Unknown { attribute_name_index, attribute_length, info: Cow::Borrowed(info) }
+    };
+    Ok((idx_next, attribute_info))
+}
+
+fn parse_attribute_info_code<'a>(idx: usize, attribute_name_index: u16, attribute_length: u32, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions, bytecode: &'a [u8]) -> ParseResult<AttributeInfo<'a>> {
+    let (idx, max_stack) = get_u2(idx, bytecode)?;
+    let (idx, max_locals) = get_u2(idx, bytecode)?;
+    let (idx, code_length) = get_u4(idx, bytecode)?;
+    let (idx, code) = get_bytes(idx, code_length as usize, bytecode)?;
+    let (idx, exception_table_length) = get_u2(idx, bytecode)?;
     let mut exceptions: Vec<ExceptionTable> = Vec::with_capacity(exception_table_length as usize);
-    for i in 0..(exception_table_length as usize) {
-        let (_, exception_table) = parse_exception_table(idx + i, bytecode);
+    let mut idx = idx;
+    for n in 0..(exception_table_length as usize) {
+        let (i, exception_table) = parse_exception_table(idx, bytecode)
+            .map_err(|e| e.within(format!("exception_table[{}]", n)))?;
+        idx = i;
         exceptions.push(exception_table);
     }
-    let idx = idx + exception_table_length as usize;
-    let (idx, attributes_count) = get_u2(idx, bytecode);
-    let (idx, attributes) = parse_attributes(idx, attributes_count, cp_info, bytecode);
+    let (idx, attributes_count) = get_u2(idx, bytecode)?;
+    let (idx, attributes) = parse_attributes(idx, attributes_count, cp_info, decoders, options, bytecode)?;
     let attribute_info_code = AttributeInfo::Code {
         attribute_name_index,
         attribute_length,
         max_stack,
         max_locals,
         code_length,
-        code,
+        code: Cow::Borrowed(code),
         exception_table_length,
         exception_table: exceptions,
         attributes_count,
         attributes
     };
-    (idx, attribute_info_code)
+    Ok((idx, attribute_info_code))
 }
 
-fn parse_exception_table(_idx: usize, _bytecode: &Vec<u8>) -> (usize, ExceptionTable) {
-    todo!()
+fn parse_exception_table(idx: usize, bytecode: &[u8]) -> ParseResult<ExceptionTable> {
+    let (idx, start_pc) = get_u2(idx, bytecode)?;
+    let (idx, end_pc) = get_u2(idx, bytecode)?;
+    let (idx, handler_pc) = get_u2(idx, bytecode)?;
+    let (idx, catch_type) = get_u2(idx, bytecode)?;
+    let exception_table = ExceptionTable { start_pc, end_pc, handler_pc, catch_type };
+    Ok((idx, exception_table))
 }
 
-fn parse_attribute_info_line_number_table(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &Vec<u8>) -> (usize, AttributeInfo) {
-    let (idx, line_number_table_length) = get_u2(idx, bytecode);
+fn parse_attribute_info_line_number_table(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, line_number_table_length) = get_u2(idx, bytecode)?;
     let mut line_number_tables: Vec<LineNumberTableItem> = Vec::with_capacity(line_number_table_length as usize);
     let mut idx = idx;
-    for _ in 0..line_number_table_length as usize {
-        let (i, line_number_table_item) = parse_attribute_info_line_number_table_item(idx, bytecode);
+    for n in 0..line_number_table_length as usize {
+        let (i, line_number_table_item) = parse_attribute_info_line_number_table_item(idx, bytecode)
+            .map_err(|e| e.within(format!("line_number_table[{}]", n)))?;
         idx = i;
         line_number_tables.push(line_number_table_item);
     }
@@ -287,41 +475,1093 @@ fn parse_attribute_info_line_number_table(idx: usize, attribute_name_index: u16,
         line_number_table_length,
         line_number_tables
     };
-    (idx, line_number_table)
+    Ok((idx, line_number_table))
 }
 
-fn parse_attribute_info_line_number_table_item(idx: usize, bytecode: &Vec<u8>) -> (usize, LineNumberTableItem) {
-    let (idx, start_pc) = get_u2(idx, bytecode);
-    let (idx, line_number) = get_u2(idx, bytecode);
+fn parse_attribute_info_line_number_table_item(idx: usize, bytecode: &[u8]) -> ParseResult<LineNumberTableItem> {
+    let (idx, start_pc) = get_u2(idx, bytecode)?;
+    let (idx, line_number) = get_u2(idx, bytecode)?;
     let  line_number_table_item = LineNumberTableItem {
         start_pc,
         line_number
     };
-    (idx, line_number_table_item)
+    Ok((idx, line_number_table_item))
+}
+
+fn parse_attribute_info_local_variable_table(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, local_variable_table_length) = get_u2(idx, bytecode)?;
+    let mut local_variable_table: Vec<LocalVariableTableItem> = Vec::with_capacity(local_variable_table_length as usize);
+    let mut idx = idx;
+    for n in 0..local_variable_table_length as usize {
+        let (i, [start_pc, length, name_index, descriptor_index, index]) = parse_local_variable_item(idx, bytecode)
+            .map_err(|e| e.within(format!("local_variable_table[{}]", n)))?;
+        idx = i;
+        local_variable_table.push(LocalVariableTableItem { start_pc, length, name_index, descriptor_index, index });
+    }
+    Ok((idx, LocalVariableTable { attribute_name_index, attribute_length, local_variable_table_length, local_variable_table }))
+}
+
+fn parse_attribute_info_local_variable_type_table(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, local_variable_type_table_length) = get_u2(idx, bytecode)?;
+    let mut local_variable_type_table: Vec<LocalVariableTypeTableItem> = Vec::with_capacity(local_variable_type_table_length as usize);
+    let mut idx = idx;
+    for n in 0..local_variable_type_table_length as usize {
+        let (i, [start_pc, length, name_index, signature_index, index]) = parse_local_variable_item(idx, bytecode)
+            .map_err(|e| e.within(format!("local_variable_type_table[{}]", n)))?;
+        idx = i;
+        local_variable_type_table.push(LocalVariableTypeTableItem { start_pc, length, name_index, signature_index, index });
+    }
+    Ok((idx, LocalVariableTypeTable { attribute_name_index, attribute_length, local_variable_type_table_length, local_variable_type_table }))
 }
 
-fn parse_attribute_info_source_file(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &Vec<u8>) -> (usize, AttributeInfo) {
-    let (idx, sourcefile_index) = get_u2(idx, bytecode);
+/// Both tables have the same layout: start_pc, length, name_index,
+/// descriptor_index or signature_index and index.
+fn parse_local_variable_item(idx: usize, bytecode: &[u8]) -> ParseResult<[u16; 5]> {
+    let (idx, start_pc) = get_u2(idx, bytecode)?;
+    let (idx, length) = get_u2(idx, bytecode)?;
+    let (idx, name_index) = get_u2(idx, bytecode)?;
+    let (idx, descriptor_index) = get_u2(idx, bytecode)?;
+    let (idx, index) = get_u2(idx, bytecode)?;
+    Ok((idx, [start_pc, length, name_index, descriptor_index, index]))
+}
+
+fn parse_attribute_info_stack_map_table(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, number_of_entries) = get_u2(idx, bytecode)?;
+    let mut entries: Vec<StackMapFrame> = Vec::with_capacity(number_of_entries as usize);
+    let mut idx = idx;
+    for n in 0..number_of_entries as usize {
+        let (i, frame) = parse_stack_map_frame(idx, bytecode).map_err(|e| e.within(format!("entries[{}]", n)))?;
+        idx = i;
+        entries.push(frame);
+    }
+    Ok((idx, StackMapTable { attribute_name_index, attribute_length, number_of_entries, entries }))
+}
+
+fn parse_stack_map_frame(idx: usize, bytecode: &[u8]) -> ParseResult<StackMapFrame> {
+    let (idx, frame_type) = get_u1(idx, bytecode)?;
+    match frame_type {
+        0..=63 => Ok((idx, StackMapFrame::SameFrame { frame_type })),
+        64..=127 => {
+            let (idx, stack) = parse_verification_type_info(idx, bytecode).map_err(|e| e.within("stack"))?;
+            Ok((idx, StackMapFrame::SameLocals1StackItemFrame { frame_type, stack }))
+        },
+        247 => {
+            let (idx, offset_delta) = get_u2(idx, bytecode)?;
+            let (idx, stack) = parse_verification_type_info(idx, bytecode).map_err(|e| e.within("stack"))?;
+            Ok((idx, StackMapFrame::SameLocals1StackItemFrameExtended { frame_type, offset_delta, stack }))
+        },
+        248..=250 => {
+            let (idx, offset_delta) = get_u2(idx, bytecode)?;
+            Ok((idx, StackMapFrame::ChopFrame { frame_type, offset_delta }))
+        },
+        251 => {
+            let (idx, offset_delta) = get_u2(idx, bytecode)?;
+            Ok((idx, StackMapFrame::SameFrameExtended { frame_type, offset_delta }))
+        },
+        252..=254 => {
+            let (idx, offset_delta) = get_u2(idx, bytecode)?;
+            let (idx, locals) = parse_verification_type_infos(idx, frame_type as u16 - 251, "locals", bytecode)?;
+            Ok((idx, StackMapFrame::AppendFrame { frame_type, offset_delta, locals }))
+        },
+        255 => {
+            let (idx, offset_delta) = get_u2(idx, bytecode)?;
+            let (idx, number_of_locals) = get_u2(idx, bytecode)?;
+            let (idx, locals) = parse_verification_type_infos(idx, number_of_locals, "locals", bytecode)?;
+            let (idx, number_of_stack_items) = get_u2(idx, bytecode)?;
+            let (idx, stack) = parse_verification_type_infos(idx, number_of_stack_items, "stack", bytecode)?;
+            Ok((idx, StackMapFrame::FullFrame { frame_type, offset_delta, number_of_locals, locals, number_of_stack_items, stack }))
+        },
+        _ => Err(ClassFormatError::new(idx - 1, ClassFormatErrorKind::InvalidStackMapFrameType(frame_type)))
+    }
+}
+
+fn parse_verification_type_infos(idx: usize, count: u16, structure: &str, bytecode: &[u8]) -> ParseResult<Vec<VerificationTypeInfo>> {
+    let mut verification_type_infos: Vec<VerificationTypeInfo> = Vec::with_capacity(count as usize);
+    let mut idx = idx;
+    for n in 0..count as usize {
+        let (i, verification_type_info) = parse_verification_type_info(idx, bytecode).map_err(|e| e.within(format!("{}[{}]", structure, n)))?;
+        idx = i;
+        verification_type_infos.push(verification_type_info);
+    }
+    Ok((idx, verification_type_infos))
+}
+
+fn parse_verification_type_info(idx: usize, bytecode: &[u8]) -> ParseResult<VerificationTypeInfo> {
+    let (idx, tag) = get_u1(idx, bytecode)?;
+    match tag {
+        0 => Ok((idx, VerificationTypeInfo::Top)),
+        1 => Ok((idx, VerificationTypeInfo::Integer)),
+        2 => Ok((idx, VerificationTypeInfo::Float)),
+        3 => Ok((idx, VerificationTypeInfo::Double)),
+        4 => Ok((idx, VerificationTypeInfo::Long)),
+        5 => Ok((idx, VerificationTypeInfo::Null)),
+        6 => Ok((idx, VerificationTypeInfo::UninitializedThis)),
+        7 => {
+            let (idx, cpool_index) = get_u2(idx, bytecode)?;
+            Ok((idx, VerificationTypeInfo::Object { cpool_index }))
+        },
+        8 => {
+            let (idx, offset) = get_u2(idx, bytecode)?;
+            Ok((idx, VerificationTypeInfo::Uninitialized { offset }))
+        },
+        _ => Err(ClassFormatError::new(idx - 1, ClassFormatErrorKind::InvalidVerificationTypeTag(tag)))
+    }
+}
+
+fn parse_attribute_info_source_file(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, sourcefile_index) = get_u2(idx, bytecode)?;
     let source_file = SourceFile { attribute_name_index, attribute_length, sourcefile_index };
-    (idx, source_file)
+    Ok((idx, source_file))
+}
+
+fn parse_attribute_info_source_debug_extension(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, debug_extension) = get_bytes(idx, attribute_length as usize, bytecode)?;
+    let source_debug_extension = SourceDebugExtension { attribute_name_index, attribute_length, debug_extension: Cow::Borrowed(debug_extension) };
+    Ok((idx, source_debug_extension))
+}
+
+fn parse_attribute_info_constant_value(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, constantvalue_index) = get_u2(idx, bytecode)?;
+    let constant_value = ConstantValue { attribute_name_index, attribute_length, constantvalue_index };
+    Ok((idx, constant_value))
+}
+
+fn parse_attribute_info_signature(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, signature_index) = get_u2(idx, bytecode)?;
+    let signature = Signature { attribute_name_index, attribute_length, signature_index };
+    Ok((idx, signature))
+}
+
+fn parse_attribute_info_bootstrap_methods(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, num_bootstrap_methods) = get_u2(idx, bytecode)?;
+    let mut bootstrap_methods: Vec<BootstrapMethod> = Vec::with_capacity(num_bootstrap_methods as usize);
+    let mut idx = idx;
+    for n in 0..num_bootstrap_methods as usize {
+        let (i, bootstrap_method) = parse_bootstrap_method(idx, bytecode).map_err(|e| e.within(format!("bootstrap_methods[{}]", n)))?;
+        idx = i;
+        bootstrap_methods.push(bootstrap_method);
+    }
+    Ok((idx, BootstrapMethods { attribute_name_index, attribute_length, num_bootstrap_methods, bootstrap_methods }))
+}
+
+fn parse_bootstrap_method(idx: usize, bytecode: &[u8]) -> ParseResult<BootstrapMethod> {
+    let (idx, bootstrap_method_ref) = get_u2(idx, bytecode)?;
+    let (idx, num_bootstrap_arguments) = get_u2(idx, bytecode)?;
+    let mut bootstrap_arguments: Vec<u16> = Vec::with_capacity(num_bootstrap_arguments as usize);
+    let mut idx = idx;
+    for _ in 0..num_bootstrap_arguments {
+        let (i, bootstrap_argument) = get_u2(idx, bytecode)?;
+        idx = i;
+        bootstrap_arguments.push(bootstrap_argument);
+    }
+    Ok((idx, BootstrapMethod { bootstrap_method_ref, num_bootstrap_arguments, bootstrap_arguments }))
+}
+
+fn parse_attribute_info_module(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, module_name_index) = get_u2(idx, bytecode)?;
+    let (idx, module_flags) = get_u2(idx, bytecode)?;
+    let (idx, module_version_index) = get_u2(idx, bytecode)?;
+
+    let (idx, requires_count) = get_u2(idx, bytecode)?;
+    let mut requires: Vec<ModuleRequires> = Vec::with_capacity(requires_count as usize);
+    let mut idx = idx;
+    for n in 0..requires_count as usize {
+        let (i, module_requires) = parse_module_requires(idx, bytecode).map_err(|e| e.within(format!("requires[{}]", n)))?;
+        idx = i;
+        requires.push(module_requires);
+    }
+
+    let (idx, exports_count) = get_u2(idx, bytecode)?;
+    let mut exports: Vec<ModuleExports> = Vec::with_capacity(exports_count as usize);
+    let mut idx = idx;
+    for n in 0..exports_count as usize {
+        let (i, (exports_index, exports_flags, exports_to_count, exports_to_index)) = parse_module_package_directive(idx, bytecode)
+            .map_err(|e| e.within(format!("exports[{}]", n)))?;
+        idx = i;
+        exports.push(ModuleExports { exports_index, exports_flags, exports_to_count, exports_to_index });
+    }
+
+    let (idx, opens_count) = get_u2(idx, bytecode)?;
+    let mut opens: Vec<ModuleOpens> = Vec::with_capacity(opens_count as usize);
+    let mut idx = idx;
+    for n in 0..opens_count as usize {
+        let (i, (opens_index, opens_flags, opens_to_count, opens_to_index)) = parse_module_package_directive(idx, bytecode)
+            .map_err(|e| e.within(format!("opens[{}]", n)))?;
+        idx = i;
+        opens.push(ModuleOpens { opens_index, opens_flags, opens_to_count, opens_to_index });
+    }
+
+    let (idx, uses_count) = get_u2(idx, bytecode)?;
+    let (idx, uses_index) = get_u2_list(idx, uses_count, bytecode).map_err(|e| e.within("uses_index"))?;
+
+    let (idx, provides_count) = get_u2(idx, bytecode)?;
+    let mut provides: Vec<ModuleProvides> = Vec::with_capacity(provides_count as usize);
+    let mut idx = idx;
+    for n in 0..provides_count as usize {
+        let (i, module_provides) = parse_module_provides(idx, bytecode).map_err(|e| e.within(format!("provides[{}]", n)))?;
+        idx = i;
+        provides.push(module_provides);
+    }
+
+    let module = Module {
+        attribute_name_index,
+        attribute_length,
+        module_name_index,
+        module_flags,
+        module_version_index,
+        requires_count,
+        requires,
+        exports_count,
+        exports,
+        opens_count,
+        opens,
+        uses_count,
+        uses_index,
+        provides_count,
+        provides
+    };
+    Ok((idx, module))
+}
+
+fn parse_module_requires(idx: usize, bytecode: &[u8]) -> ParseResult<ModuleRequires> {
+    let (idx, requires_index) = get_u2(idx, bytecode)?;
+    let (idx, requires_flags) = get_u2(idx, bytecode)?;
+    let (idx, requires_version_index) = get_u2(idx, bytecode)?;
+    Ok((idx, ModuleRequires { requires_index, requires_flags, requires_version_index }))
+}
+
+/// exports and opens share the same layout: package index, flags and the
+/// modules the package is exported or opened to.
+fn parse_module_package_directive(idx: usize, bytecode: &[u8]) -> ParseResult<(u16, u16, u16, Vec<u16>)> {
+    let (idx, package_index) = get_u2(idx, bytecode)?;
+    let (idx, flags) = get_u2(idx, bytecode)?;
+    let (idx, to_count) = get_u2(idx, bytecode)?;
+    let (idx, to_index) = get_u2_list(idx, to_count, bytecode)?;
+    Ok((idx, (package_index, flags, to_count, to_index)))
+}
+
+fn parse_module_provides(idx: usize, bytecode: &[u8]) -> ParseResult<ModuleProvides> {
+    let (idx, provides_index) = get_u2(idx, bytecode)?;
+    let (idx, provides_with_count) = get_u2(idx, bytecode)?;
+    let (idx, provides_with_index) = get_u2_list(idx, provides_with_count, bytecode)?;
+    Ok((idx, ModuleProvides { provides_index, provides_with_count, provides_with_index }))
+}
+
+fn parse_attribute_info_module_packages(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, package_count) = get_u2(idx, bytecode)?;
+    let (idx, package_index) = get_u2_list(idx, package_count, bytecode).map_err(|e| e.within("package_index"))?;
+    Ok((idx, ModulePackages { attribute_name_index, attribute_length, package_count, package_index }))
+}
+
+fn parse_attribute_info_module_main_class(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, main_class_index) = get_u2(idx, bytecode)?;
+    Ok((idx, ModuleMainClass { attribute_name_index, attribute_length, main_class_index }))
+}
+
+fn parse_attribute_info_exceptions(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, number_of_exceptions) = get_u2(idx, bytecode)?;
+    let (idx, exception_index_table) = get_u2_list(idx, number_of_exceptions, bytecode).map_err(|e| e.within("exception_index_table"))?;
+    Ok((idx, Exceptions { attribute_name_index, attribute_length, number_of_exceptions, exception_index_table }))
+}
+
+fn parse_attribute_info_method_parameters(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, parameters_count) = get_u1(idx, bytecode)?;
+    let mut parameters: Vec<MethodParameter> = Vec::with_capacity(parameters_count as usize);
+    let mut idx = idx;
+    for n in 0..parameters_count {
+        let (idx_next, name_index) = get_u2(idx, bytecode).map_err(|e| e.within(format!("parameters[{}]", n)))?;
+        let (idx_next, access_flags) = get_u2(idx_next, bytecode).map_err(|e| e.within(format!("parameters[{}]", n)))?;
+        parameters.push(MethodParameter { name_index, access_flags });
+        idx = idx_next;
+    }
+    Ok((idx, MethodParameters { attribute_name_index, attribute_length, parameters_count, parameters }))
+}
+
+fn parse_attribute_info_inner_classes(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, number_of_classes) = get_u2(idx, bytecode)?;
+    let mut classes: Vec<InnerClass> = Vec::with_capacity(number_of_classes as usize);
+    let mut idx = idx;
+    for n in 0..number_of_classes {
+        let (idx_next, inner_class) = parse_inner_class(idx, bytecode).map_err(|e| e.within(format!("classes[{}]", n)))?;
+        classes.push(inner_class);
+        idx = idx_next;
+    }
+    Ok((idx, InnerClasses { attribute_name_index, attribute_length, number_of_classes, classes }))
+}
+
+fn parse_inner_class(idx: usize, bytecode: &[u8]) -> ParseResult<InnerClass> {
+    let (idx, inner_class_info_index) = get_u2(idx, bytecode)?;
+    let (idx, outer_class_info_index) = get_u2(idx, bytecode)?;
+    let (idx, inner_name_index) = get_u2(idx, bytecode)?;
+    let (idx, inner_class_access_flags) = get_u2(idx, bytecode)?;
+    Ok((idx, InnerClass { inner_class_info_index, outer_class_info_index, inner_name_index, inner_class_access_flags }))
+}
+
+fn parse_attribute_info_enclosing_method(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, class_index) = get_u2(idx, bytecode)?;
+    let (idx, method_index) = get_u2(idx, bytecode)?;
+    Ok((idx, EnclosingMethod { attribute_name_index, attribute_length, class_index, method_index }))
+}
+
+fn parse_attribute_info_nest_host(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, host_class_index) = get_u2(idx, bytecode)?;
+    Ok((idx, NestHost { attribute_name_index, attribute_length, host_class_index }))
+}
+
+/// NestMembers or PermittedSubclasses, both are a table of classes.
+fn parse_attribute_info_classes<'a>(idx: usize, attribute_name_index: u16, attribute_length: u32, attribute_name: &str, bytecode: &'a [u8]) -> ParseResult<AttributeInfo<'a>> {
+    let (idx, number_of_classes) = get_u2(idx, bytecode)?;
+    let (idx, classes) = get_u2_list(idx, number_of_classes, bytecode).map_err(|e| e.within("classes"))?;
+    let attribute_info = if attribute_name == "NestMembers" {
+        NestMembers { attribute_name_index, attribute_length, number_of_classes, classes }
+    } else {
+        PermittedSubclasses { attribute_name_index, attribute_length, number_of_classes, classes }
+    };
+    Ok((idx, attribute_info))
+}
+
+fn parse_attribute_info_record<'a>(idx: usize, attribute_name_index: u16, attribute_length: u32, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions, bytecode: &'a [u8]) -> ParseResult<AttributeInfo<'a>> {
+    let (idx, components_count) = get_u2(idx, bytecode)?;
+    let mut components: Vec<RecordComponentInfo> = Vec::with_capacity(components_count as usize);
+    let mut idx = idx;
+    for n in 0..components_count as usize {
+        let (i, component) = parse_record_component_info(idx, cp_info, decoders, options, bytecode).map_err(|e| e.within(format!("components[{}]", n)))?;
+        idx = i;
+        components.push(component);
+    }
+    Ok((idx, Record { attribute_name_index, attribute_length, components_count, components }))
+}
+
+fn parse_record_component_info<'a>(idx: usize, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions, bytecode: &'a [u8]) -> ParseResult<RecordComponentInfo<'a>> {
+    let (idx, name_index) = get_u2(idx, bytecode)?;
+    let (idx, descriptor_index) = get_u2(idx, bytecode)?;
+    let (idx, attributes_count) = get_u2(idx, bytecode)?;
+    let (idx, attributes) = parse_attributes(idx, attributes_count, cp_info, decoders, options, bytecode)?;
+    Ok((idx, RecordComponentInfo { name_index, descriptor_index, attributes_count, attributes }))
+}
+
+/// RuntimeVisibleAnnotations or RuntimeInvisibleAnnotations
+fn parse_attribute_info_annotations(idx: usize, attribute_name_index: u16, attribute_length: u32, visible: bool, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, num_annotations) = get_u2(idx, bytecode)?;
+    let (idx, annotations) = parse_annotations(idx, num_annotations, bytecode)?;
+    let annotations = if visible {
+        RuntimeVisibleAnnotations { attribute_name_index, attribute_length, num_annotations, annotations }
+    } else {
+        RuntimeInvisibleAnnotations { attribute_name_index, attribute_length, num_annotations, annotations }
+    };
+    Ok((idx, annotations))
+}
+
+/// RuntimeVisibleParameterAnnotations or RuntimeInvisibleParameterAnnotations
+fn parse_attribute_info_parameter_annotations(idx: usize, attribute_name_index: u16, attribute_length: u32, visible: bool, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, num_parameters) = get_u1(idx, bytecode)?;
+    let mut parameter_annotations: Vec<Vec<Annotation>> = Vec::with_capacity(num_parameters as usize);
+    let mut idx = idx;
+    for n in 0..num_parameters as usize {
+        let (i, num_annotations) = get_u2(idx, bytecode).map_err(|e| e.within(format!("parameter_annotations[{}]", n)))?;
+        let (i, annotations) = parse_annotations(i, num_annotations, bytecode)
+            .map_err(|e| e.within(format!("parameter_annotations[{}]", n)))?;
+        idx = i;
+        parameter_annotations.push(annotations);
+    }
+    let parameter_annotations = if visible {
+        RuntimeVisibleParameterAnnotations { attribute_name_index, attribute_length, num_parameters, parameter_annotations }
+    } else {
+        RuntimeInvisibleParameterAnnotations { attribute_name_index, attribute_length, num_parameters, parameter_annotations }
+    };
+    Ok((idx, parameter_annotations))
+}
+
+fn parse_attribute_info_annotation_default(idx: usize, attribute_name_index: u16, attribute_length: u32, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, default_value) = parse_element_value(idx, 0, bytecode).map_err(|e| e.within("default_value"))?;
+    Ok((idx, AnnotationDefault { attribute_name_index, attribute_length, default_value }))
+}
+
+fn parse_annotations(idx: usize, num_annotations: u16, bytecode: &[u8]) -> ParseResult<Vec<Annotation>> {
+    let mut annotations: Vec<Annotation> = Vec::with_capacity(num_annotations as usize);
+    let mut idx = idx;
+    for n in 0..num_annotations as usize {
+        let (i, annotation) = parse_annotation(idx, 0, bytecode).map_err(|e| e.within(format!("annotations[{}]", n)))?;
+        idx = i;
+        annotations.push(annotation);
+    }
+    Ok((idx, annotations))
+}
+
+/// `depth` is the number of element values enclosing the annotation.
+fn parse_annotation(idx: usize, depth: usize, bytecode: &[u8]) -> ParseResult<Annotation> {
+    let (idx, type_index) = get_u2(idx, bytecode)?;
+    let (idx, num_element_value_pairs) = get_u2(idx, bytecode)?;
+    let mut element_value_pairs: Vec<ElementValuePair> = Vec::with_capacity(num_element_value_pairs as usize);
+    let mut idx = idx;
+    for n in 0..num_element_value_pairs as usize {
+        let (i, element_name_index) = get_u2(idx, bytecode).map_err(|e| e.within(format!("element_value_pairs[{}]", n)))?;
+        let (i, value) = parse_element_value(i, depth, bytecode).map_err(|e| e.within(format!("element_value_pairs[{}]", n)))?;
+        idx = i;
+        element_value_pairs.push(ElementValuePair { element_name_index, value });
+    }
+    Ok((idx, Annotation { type_index, element_value_pairs }))
+}
+
+/// RuntimeVisibleTypeAnnotations or RuntimeInvisibleTypeAnnotations
+fn parse_attribute_info_type_annotations(idx: usize, attribute_name_index: u16, attribute_length: u32, visible: bool, bytecode: &[u8]) -> ParseResult<AttributeInfo<'_>> {
+    let (idx, num_annotations) = get_u2(idx, bytecode)?;
+    let mut annotations: Vec<TypeAnnotation> = Vec::with_capacity(num_annotations as usize);
+    let mut idx = idx;
+    for n in 0..num_annotations as usize {
+        let (i, annotation) = parse_type_annotation(idx, bytecode).map_err(|e| e.within(format!("annotations[{}]", n)))?;
+        idx = i;
+        annotations.push(annotation);
+    }
+    let annotations = if visible {
+        RuntimeVisibleTypeAnnotations { attribute_name_index, attribute_length, num_annotations, annotations }
+    } else {
+        RuntimeInvisibleTypeAnnotations { attribute_name_index, attribute_length, num_annotations, annotations }
+    };
+    Ok((idx, annotations))
 }
 
-pub fn get_u1(idx: usize, bytecode: &Vec<u8>) -> (usize, u8) {
-    (idx + 1, bytecode[idx])
+fn parse_type_annotation(idx: usize, bytecode: &[u8]) -> ParseResult<TypeAnnotation> {
+    let (idx, target_type_value) = get_u1(idx, bytecode)?;
+    let target_type = TargetType::from_value(target_type_value)
+        .ok_or_else(|| ClassFormatError::new(idx - 1, ClassFormatErrorKind::InvalidTargetType(target_type_value)))?;
+    let (idx, target_info) = parse_target_info(idx, &target_type, bytecode).map_err(|e| e.within("target_info"))?;
+    let (idx, type_path) = parse_type_path(idx, bytecode).map_err(|e| e.within("type_path"))?;
+    let (idx, annotation) = parse_annotation(idx, 0, bytecode)?;
+    Ok((idx, TypeAnnotation { target_type, target_info, type_path, annotation }))
 }
 
-pub fn get_u2(idx: usize, bytecode: &Vec<u8>) -> (usize, u16) {
+fn parse_target_info(idx: usize, target_type: &TargetType, bytecode: &[u8]) -> ParseResult<TargetInfo> {
+    match target_type {
+        TargetType::ClassTypeParameter | TargetType::MethodTypeParameter => {
+            let (idx, type_parameter_index) = get_u1(idx, bytecode)?;
+            Ok((idx, TargetInfo::TypeParameter { type_parameter_index }))
+        },
+        TargetType::ClassExtends => {
+            let (idx, supertype_index) = get_u2(idx, bytecode)?;
+            Ok((idx, TargetInfo::Supertype { supertype_index }))
+        },
+        TargetType::ClassTypeParameterBound | TargetType::MethodTypeParameterBound => {
+            let (idx, type_parameter_index) = get_u1(idx, bytecode)?;
+            let (idx, bound_index) = get_u1(idx, bytecode)?;
+            Ok((idx, TargetInfo::TypeParameterBound { type_parameter_index, bound_index }))
+        },
+        TargetType::Field | TargetType::MethodReturn | TargetType::MethodReceiver => Ok((idx, TargetInfo::Empty)),
+        TargetType::MethodFormalParameter => {
+            let (idx, formal_parameter_index) = get_u1(idx, bytecode)?;
+            Ok((idx, TargetInfo::FormalParameter { formal_parameter_index }))
+        },
+        TargetType::Throws => {
+            let (idx, throws_type_index) = get_u2(idx, bytecode)?;
+            Ok((idx, TargetInfo::Throws { throws_type_index }))
+        },
+        TargetType::LocalVariable | TargetType::ResourceVariable => {
+            let (idx, table_length) = get_u2(idx, bytecode)?;
+            let mut table: Vec<LocalvarTargetItem> = Vec::with_capacity(table_length as usize);
+            let mut idx = idx;
+            for n in 0..table_length as usize {
+                let (i, start_pc) = get_u2(idx, bytecode).map_err(|e| e.within(format!("table[{}]", n)))?;
+                let (i, length) = get_u2(i, bytecode).map_err(|e| e.within(format!("table[{}]", n)))?;
+                let (i, index) = get_u2(i, bytecode).map_err(|e| e.within(format!("table[{}]", n)))?;
+                idx = i;
+                table.push(LocalvarTargetItem { start_pc, length, index });
+            }
+            Ok((idx, TargetInfo::Localvar { table_length, table }))
+        },
+        TargetType::ExceptionParameter => {
+            let (idx, exception_table_index) = get_u2(idx, bytecode)?;
+            Ok((idx, TargetInfo::Catch { exception_table_index }))
+        },
+        TargetType::Instanceof | TargetType::New | TargetType::ConstructorReference | TargetType::MethodReference => {
+            let (idx, offset) = get_u2(idx, bytecode)?;
+            Ok((idx, TargetInfo::Offset { offset }))
+        },
+        TargetType::Cast |
+        TargetType::ConstructorInvocationTypeArgument |
+        TargetType::MethodInvocationTypeArgument |
+        TargetType::ConstructorReferenceTypeArgument |
+        TargetType::MethodReferenceTypeArgument => {
+            let (idx, offset) = get_u2(idx, bytecode)?;
+            let (idx, type_argument_index) = get_u1(idx, bytecode)?;
+            Ok((idx, TargetInfo::TypeArgument { offset, type_argument_index }))
+        }
+    }
+}
+
+fn parse_type_path(idx: usize, bytecode: &[u8]) -> ParseResult<Vec<TypePathEntry>> {
+    let (idx, path_length) = get_u1(idx, bytecode)?;
+    let mut type_path: Vec<TypePathEntry> = Vec::with_capacity(path_length as usize);
+    let mut idx = idx;
+    for _ in 0..path_length {
+        let (i, type_path_kind_value) = get_u1(idx, bytecode)?;
+        let type_path_kind = TypePathKind::from_value(type_path_kind_value)
+            .ok_or_else(|| ClassFormatError::new(idx, ClassFormatErrorKind::InvalidTypePathKind(type_path_kind_value)))?;
+        let (i, type_argument_index) = get_u1(i, bytecode)?;
+        idx = i;
+        type_path.push(TypePathEntry { type_path_kind, type_argument_index });
+    }
+    Ok((idx, type_path))
+}
+
+/// Nested arrays and annotations are limited to `MAX_ELEMENT_VALUE_DEPTH`
+/// levels, `depth` is the number of element values enclosing this one.
+fn parse_element_value(idx: usize, depth: usize, bytecode: &[u8]) -> ParseResult<ElementValue> {
+    if depth >= MAX_ELEMENT_VALUE_DEPTH {
+        return Err(ClassFormatError::new(idx, ClassFormatErrorKind::NestingTooDeep(MAX_ELEMENT_VALUE_DEPTH)));
+    }
+    let (idx, tag) = get_u1(idx, bytecode)?;
+    match tag {
+        b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's' => {
+            let (idx, const_value_index) = get_u2(idx, bytecode)?;
+            Ok((idx, ElementValue::ConstValue { tag, const_value_index }))
+        },
+        b'e' => {
+            let (idx, type_name_index) = get_u2(idx, bytecode)?;
+            let (idx, const_name_index) = get_u2(idx, bytecode)?;
+            Ok((idx, ElementValue::EnumConstValue { type_name_index, const_name_index }))
+        },
+        b'c' => {
+            let (idx, class_info_index) = get_u2(idx, bytecode)?;
+            Ok((idx, ElementValue::ClassInfo { class_info_index }))
+        },
+        b'@' => {
+            let (idx, annotation_value) = parse_annotation(idx, depth + 1, bytecode)?;
+            Ok((idx, ElementValue::AnnotationValue { annotation_value }))
+        },
+        b'[' => {
+            let (idx, num_values) = get_u2(idx, bytecode)?;
+            let mut values: Vec<ElementValue> = Vec::with_capacity(num_values as usize);
+            let mut idx = idx;
+            for n in 0..num_values as usize {
+                let (i, value) = parse_element_value(idx, depth + 1, bytecode).map_err(|e| e.within(format!("values[{}]", n)))?;
+                idx = i;
+                values.push(value);
+            }
+            Ok((idx, ElementValue::ArrayValue { num_values, values }))
+        },
+        _ => Err(ClassFormatError::new(idx - 1, ClassFormatErrorKind::InvalidElementValueTag(tag)))
+    }
+}
+
+/// Bounds check for reading `len` bytes at `idx`.
+fn ensure_available(idx: usize, len: usize, bytecode: &[u8]) -> Result<(), ClassFormatError> {
+    let available = bytecode.len().saturating_sub(idx);
+    if available < len {
+        Err(ClassFormatError::new(idx, ClassFormatErrorKind::UnexpectedEof { needed: len, available }))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn get_bytes(idx: usize, len: usize, bytecode: &[u8]) -> ParseResult<&[u8]> {
+    ensure_available(idx, len, bytecode)?;
+    Ok((idx + len, &bytecode[idx..idx + len]))
+}
+
+pub fn get_u1(idx: usize, bytecode: &[u8]) -> ParseResult<u8> {
+    ensure_available(idx, 1, bytecode)?;
+    Ok((idx + 1, bytecode[idx]))
+}
+
+pub fn get_u2(idx: usize, bytecode: &[u8]) -> ParseResult<u16> {
+    ensure_available(idx, 2, bytecode)?;
     let u0 = (bytecode[idx] as u16) << 8;
     let u1 = bytecode[idx + 1] as u16;
     let r: u16 = u0 | u1;
-    (idx + 2, r)
+    Ok((idx + 2, r))
+}
+
+/// `count` consecutive u2, ex.: a table of constant pool indexes.
+pub fn get_u2_list(idx: usize, count: u16, bytecode: &[u8]) -> ParseResult<Vec<u16>> {
+    ensure_available(idx, 2 * count as usize, bytecode)?;
+    let mut list: Vec<u16> = Vec::with_capacity(count as usize);
+    let mut idx = idx;
+    for _ in 0..count {
+        let (i, value) = get_u2(idx, bytecode)?;
+        idx = i;
+        list.push(value);
+    }
+    Ok((idx, list))
 }
 
-fn get_u4(idx: usize, bytecode: &Vec<u8>) -> (usize, u32) {
+pub fn get_u4(idx: usize, bytecode: &[u8]) -> ParseResult<u32> {
+    ensure_available(idx, 4, bytecode)?;
     let u0 = (bytecode[idx] as u32) << 24;
     let u1 = (bytecode[idx + 1] as u32) << 16;
     let u2 = (bytecode[idx + 2] as u32) << 8;
     let u3 = bytecode[idx + 3] as u32;
     let r: u32 = u0 | u1 | u2 | u3;
-    (idx + 4, r)
+    Ok((idx + 4, r))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::ast::stack_map_frame_offsets;
+    use crate::parser::attribute_decoder::CustomAttribute;
+    use super::*;
+
+    /// An int element value enclosed in `depth` arrays of one value.
+    fn nested_arrays(depth: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = [b'[', 0, 1].repeat(depth);
+        bytes.extend([b'I', 0, 1]);
+        bytes
+    }
+
+    /// An int element value enclosed in `depth` annotations of one element.
+    fn nested_annotations(depth: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = [b'@', 0, 1, 0, 1, 0, 2].repeat(depth);
+        bytes.extend([b'I', 0, 1]);
+        bytes
+    }
+
+    fn utf8(str: &str) -> CpInfo<'_> {
+        CpInfo::ConstantUtf8 { tag: 1, length: str.len() as u16, bytes: Cow::Borrowed(str.as_bytes()), bytes_str: Cow::Borrowed(str) }
+    }
+
+    fn path(structures: &[&str]) -> Vec<String> {
+        structures.iter().map(|structure| structure.to_string()).collect()
+    }
+
+    /// Parse the attribute `name` with `info`, its name is the only entry of
+    /// the constant pool.
+    fn parse_attribute(name: &str, info: &[u8]) -> Result<AttributeInfo<'static>, ClassFormatError> {
+        parse_attribute_with_decoders(name, info, &AttributeDecoders::new())
+    }
+
+    fn parse_attribute_with_decoders(name: &str, info: &[u8], decoders: &AttributeDecoders) -> Result<AttributeInfo<'static>, ClassFormatError> {
+        let mut bytes: Vec<u8> = vec![0, 1];
+        bytes.extend((info.len() as u32).to_be_bytes());
+        bytes.extend(info);
+        let cp_info = vec![CpInfo::Unusable, utf8(name)];
+        let (idx, attribute_info) = parse_attribute_info(0, &cp_info, decoders, &ParseOptions::default(), &bytes)?;
+        assert_eq!(idx, bytes.len());
+        Ok(attribute_info.unwrap().into_owned())
+    }
+
+    #[test]
+    fn element_value_depth_limit() {
+        let bytes = nested_arrays(MAX_ELEMENT_VALUE_DEPTH - 1);
+        let (idx, _) = parse_element_value(0, 0, &bytes).unwrap();
+        assert_eq!(idx, bytes.len());
+        let err = parse_element_value(0, 0, &nested_arrays(MAX_ELEMENT_VALUE_DEPTH)).unwrap_err();
+        assert_eq!(err.offset, 3 * MAX_ELEMENT_VALUE_DEPTH);
+        assert_eq!(err.path.len(), MAX_ELEMENT_VALUE_DEPTH);
+        assert_eq!(err.kind, ClassFormatErrorKind::NestingTooDeep(MAX_ELEMENT_VALUE_DEPTH));
+    }
+
+    #[test]
+    fn annotation_depth_limit() {
+        assert!(parse_element_value(0, 0, &nested_annotations(MAX_ELEMENT_VALUE_DEPTH - 1)).is_ok());
+        let err = parse_element_value(0, 0, &nested_annotations(MAX_ELEMENT_VALUE_DEPTH)).unwrap_err();
+        assert_eq!(err.kind, ClassFormatErrorKind::NestingTooDeep(MAX_ELEMENT_VALUE_DEPTH));
+    }
+
+    #[test]
+    fn deep_element_values_do_not_overflow() {
+        let value = nested_arrays(200_000);
+        // RuntimeInvisibleAnnotations with one annotation of one element
+        let mut bytes: Vec<u8> = vec![0, 1];
+        bytes.extend((2 + 6 + value.len() as u32).to_be_bytes());
+        bytes.extend([0, 1, 0, 2, 0, 1, 0, 2]);
+        bytes.extend(value);
+        let cp_info = vec![CpInfo::Unusable, utf8("RuntimeInvisibleAnnotations")];
+        let err = parse_attribute_info(0, &cp_info, &AttributeDecoders::new(), &ParseOptions::default(), &bytes).unwrap_err();
+        assert_eq!(err.path[..3], [String::from("RuntimeInvisibleAnnotations"), String::from("annotations[0]"), String::from("element_value_pairs[0]")]);
+        assert_eq!(err.kind, ClassFormatErrorKind::NestingTooDeep(MAX_ELEMENT_VALUE_DEPTH));
+    }
+
+    #[test]
+    fn long_and_double_take_two_entries() {
+        // 1 Long, 2 unusable, 3 Utf8, 4 Double, 5 unusable and 6 Integer
+        let mut bytes: Vec<u8> = vec![5, 0, 0, 0, 1, 0, 0, 0, 2];
+        bytes.extend([1, 0, 1, b'a']);
+        bytes.extend([6, 0x3F, 0xF0, 0, 0, 0, 0, 0, 0]);
+        bytes.extend([3, 0, 0, 0, 7]);
+        let (idx, cp_info) = parse_cp_info_array(0, 7, &bytes).unwrap();
+        assert_eq!(idx, bytes.len());
+        assert_eq!(cp_info.len(), 7);
+        assert!(matches!(cp_info[1], CpInfo::ConstantLong { high_bytes, low_bytes, .. } if CpInfo::long_value(high_bytes, low_bytes) == 0x1_0000_0002));
+        assert!(matches!(cp_info[2], CpInfo::Unusable));
+        assert!(matches!(&cp_info[3], CpInfo::ConstantUtf8 { bytes_str, .. } if bytes_str == "a"));
+        assert!(matches!(cp_info[4], CpInfo::ConstantDouble { high_bytes, low_bytes, .. } if CpInfo::double_value(high_bytes, low_bytes) == 1.0));
+        assert!(matches!(cp_info[5], CpInfo::Unusable));
+        assert!(matches!(cp_info[6], CpInfo::ConstantInteger { bytes: 7, .. }));
+    }
+
+    #[test]
+    fn long_and_double_in_last_entry() {
+        let bytes = [1, 0, 1, b'a', 5, 0, 0, 0, 0, 0, 0, 0, 1];
+        let err = parse_cp_info_array(0, 3, &bytes).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 4, path: vec![String::from("constant_pool[2]")], kind: ClassFormatErrorKind::InvalidConstantPoolIndex(3) });
+        let err = parse_cp_info_array(0, 2, &[6, 0x3F, 0xF0, 0, 0, 0, 0, 0, 0]).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 0, path: vec![String::from("constant_pool[1]")], kind: ClassFormatErrorKind::InvalidConstantPoolIndex(2) });
+    }
+
+    #[test]
+    fn field_info_with_constant_value() {
+        // static final int with a ConstantValue of #4
+        let bytes = [0, 0x18, 0, 2, 0, 3, 0, 1, 0, 1, 0, 0, 0, 2, 0, 4];
+        let cp_info = vec![CpInfo::Unusable, utf8("ConstantValue")];
+        let (idx, field_info) = parse_field_info(0, &cp_info, &AttributeDecoders::new(), &ParseOptions::default(), &bytes).unwrap();
+        assert_eq!(idx, bytes.len());
+        assert_eq!((field_info.access_flags, field_info.name_index, field_info.descriptor_index, field_info.attributes_count), (0x18, 2, 3, 1));
+        assert!(matches!(field_info.attributes[..], [AttributeInfo::ConstantValue { attribute_name_index: 1, attribute_length: 2, constantvalue_index: 4 }]));
+    }
+
+    #[test]
+    fn field_info_truncated() {
+        let bytes = [0, 0x18, 0, 2, 0, 3, 0, 1, 0, 1, 0, 0, 0, 2, 0];
+        let cp_info = vec![CpInfo::Unusable, utf8("ConstantValue")];
+        let err = parse_fields(0, 1, &cp_info, &AttributeDecoders::new(), &ParseOptions::default(), &bytes).unwrap_err();
+        assert_eq!(err.offset, 14);
+        assert_eq!(err.path, path(&["fields[0]", "attributes[0]", "ConstantValue"]));
+        assert_eq!(err.kind, ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 1 });
+    }
+
+    /// Code of `return` with max_stack 2, max_locals 1 and a handler of #3
+    /// for [0, 1) at 1.
+    const CODE: [u8; 21] = [0, 2, 0, 1, 0, 0, 0, 1, 0xb1, 0, 1, 0, 0, 0, 1, 0, 1, 0, 3, 0, 0];
+
+    #[test]
+    fn code_exception_table() {
+        let code = parse_attribute("Code", &CODE).unwrap();
+        let AttributeInfo::Code { max_stack, max_locals, code, exception_table_length, exception_table, attributes, .. } = code else {
+            panic!("expected Code, got {:?}", code);
+        };
+        assert_eq!((max_stack, max_locals, &code[..], exception_table_length), (2, 1, &[0xb1][..], 1));
+        assert!(matches!(exception_table[..], [ExceptionTable { start_pc: 0, end_pc: 1, handler_pc: 1, catch_type: 3 }]));
+        assert!(attributes.is_empty());
+    }
+
+    #[test]
+    fn code_exception_table_truncated() {
+        let err = parse_attribute("Code", &CODE[..16]).unwrap_err();
+        assert_eq!(err.offset, 6 + 15);
+        assert_eq!(err.path, path(&["Code", "exception_table[0]"]));
+        assert_eq!(err.kind, ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 1 });
+    }
+
+    /// A type annotation of #2 on the first type argument of the local
+    /// variable 1 for [2, 7).
+    const LOCAL_VARIABLE_TYPE_ANNOTATION: [u8; 18] = [0, 1, 0x40, 0, 1, 0, 2, 0, 5, 0, 1, 1, 3, 0, 0, 2, 0, 0];
+
+    #[test]
+    fn type_annotations() {
+        let annotations = parse_attribute("RuntimeVisibleTypeAnnotations", &LOCAL_VARIABLE_TYPE_ANNOTATION).unwrap();
+        let AttributeInfo::RuntimeVisibleTypeAnnotations { num_annotations: 1, annotations, .. } = annotations else {
+            panic!("expected RuntimeVisibleTypeAnnotations, got {:?}", annotations);
+        };
+        assert_eq!(annotations, [TypeAnnotation {
+            target_type: TargetType::LocalVariable,
+            target_info: TargetInfo::Localvar { table_length: 1, table: vec![LocalvarTargetItem { start_pc: 2, length: 5, index: 1 }] },
+            type_path: vec![TypePathEntry { type_path_kind: TypePathKind::TypeArgument, type_argument_index: 0 }],
+            annotation: Annotation { type_index: 2, element_value_pairs: vec![] },
+        }]);
+        let annotations = parse_attribute("RuntimeInvisibleTypeAnnotations", &LOCAL_VARIABLE_TYPE_ANNOTATION).unwrap();
+        assert!(matches!(annotations, AttributeInfo::RuntimeInvisibleTypeAnnotations { num_annotations: 1, .. }));
+    }
+
+    #[test]
+    fn type_annotations_truncated() {
+        let err = parse_attribute("RuntimeVisibleTypeAnnotations", &LOCAL_VARIABLE_TYPE_ANNOTATION[..8]).unwrap_err();
+        assert_eq!(err.offset, 6 + 7);
+        assert_eq!(err.path, path(&["RuntimeVisibleTypeAnnotations", "annotations[0]", "target_info", "table[0]"]));
+        assert_eq!(err.kind, ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 1 });
+        let mut info = LOCAL_VARIABLE_TYPE_ANNOTATION.to_vec();
+        info[12] = 4;
+        let err = parse_attribute("RuntimeVisibleTypeAnnotations", &info).unwrap_err();
+        assert_eq!(err.offset, 6 + 12);
+        assert_eq!(err.path, path(&["RuntimeVisibleTypeAnnotations", "annotations[0]", "type_path"]));
+        assert_eq!(err.kind, ClassFormatErrorKind::InvalidTypePathKind(4));
+    }
+
+    #[test]
+    fn bootstrap_methods() {
+        let info = [0, 2, 0, 5, 0, 2, 0, 6, 0, 7, 0, 8, 0, 0];
+        let bootstrap_methods = parse_attribute("BootstrapMethods", &info).unwrap();
+        let AttributeInfo::BootstrapMethods { num_bootstrap_methods: 2, bootstrap_methods, .. } = bootstrap_methods else {
+            panic!("expected BootstrapMethods, got {:?}", bootstrap_methods);
+        };
+        assert_eq!(bootstrap_methods, [
+            BootstrapMethod { bootstrap_method_ref: 5, num_bootstrap_arguments: 2, bootstrap_arguments: vec![6, 7] },
+            BootstrapMethod { bootstrap_method_ref: 8, num_bootstrap_arguments: 0, bootstrap_arguments: vec![] },
+        ]);
+    }
+
+    #[test]
+    fn bootstrap_methods_truncated() {
+        let err = parse_attribute("BootstrapMethods", &[0, 1, 0, 5, 0, 2, 0, 6]).unwrap_err();
+        assert_eq!(err.offset, 6 + 8);
+        assert_eq!(err.path, path(&["BootstrapMethods", "bootstrap_methods[0]"]));
+        assert_eq!(err.kind, ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 0 });
+    }
+
+    #[test]
+    fn stack_map_table() {
+        let info = [0, 7, 3, 66, 1, 247, 0, 100, 7, 0, 7, 249, 0, 4, 251, 1, 44, 253, 0, 1, 4, 8, 0, 5, 255, 0, 2, 0, 1, 6, 0, 1, 5];
+        let stack_map_table = parse_attribute("StackMapTable", &info).unwrap();
+        let AttributeInfo::StackMapTable { number_of_entries: 7, entries, .. } = stack_map_table else {
+            panic!("expected StackMapTable, got {:?}", stack_map_table);
+        };
+        assert_eq!(entries, [
+            StackMapFrame::SameFrame { frame_type: 3 },
+            StackMapFrame::SameLocals1StackItemFrame { frame_type: 66, stack: VerificationTypeInfo::Integer },
+            StackMapFrame::SameLocals1StackItemFrameExtended { frame_type: 247, offset_delta: 100, stack: VerificationTypeInfo::Object { cpool_index: 7 } },
+            StackMapFrame::ChopFrame { frame_type: 249, offset_delta: 4 },
+            StackMapFrame::SameFrameExtended { frame_type: 251, offset_delta: 300 },
+            StackMapFrame::AppendFrame { frame_type: 253, offset_delta: 1, locals: vec![VerificationTypeInfo::Long, VerificationTypeInfo::Uninitialized { offset: 5 }] },
+            StackMapFrame::FullFrame {
+                frame_type: 255,
+                offset_delta: 2,
+                number_of_locals: 1,
+                locals: vec![VerificationTypeInfo::UninitializedThis],
+                number_of_stack_items: 1,
+                stack: vec![VerificationTypeInfo::Null]
+            },
+        ]);
+        assert_eq!(stack_map_frame_offsets(&entries), [3, 6, 107, 112, 413, 415, 418]);
+    }
+
+    #[test]
+    fn stack_map_table_truncated() {
+        let err = parse_attribute("StackMapTable", &[0, 1, 255, 0, 2, 0, 2, 6]).unwrap_err();
+        assert_eq!(err.offset, 6 + 8);
+        assert_eq!(err.path, path(&["StackMapTable", "entries[0]", "locals[1]"]));
+        assert_eq!(err.kind, ClassFormatErrorKind::UnexpectedEof { needed: 1, available: 0 });
+        let err = parse_attribute("StackMapTable", &[0, 2, 0, 128]).unwrap_err();
+        assert_eq!(err.offset, 6 + 3);
+        assert_eq!(err.path, path(&["StackMapTable", "entries[1]"]));
+        assert_eq!(err.kind, ClassFormatErrorKind::InvalidStackMapFrameType(128));
+    }
+
+    /// Module #2 version #3 requiring #4, exporting #6 to #7, opening #8,
+    /// using #9 and providing #10 with #11 and #12.
+    const MODULE: [u8; 46] = [
+        0, 2, 0, 0, 0, 3,
+        0, 1, 0, 4, 0x80, 0, 0, 5,
+        0, 1, 0, 6, 0, 0, 0, 1, 0, 7,
+        0, 1, 0, 8, 0, 0, 0, 0,
+        0, 1, 0, 9,
+        0, 1, 0, 10, 0, 2, 0, 11, 0, 12,
+    ];
+
+    #[test]
+    fn module() {
+        let module = parse_attribute("Module", &MODULE).unwrap();
+        let AttributeInfo::Module { module_name_index, module_flags, module_version_index, requires, exports, opens, uses_index, provides, .. } = module else {
+            panic!("expected Module, got {:?}", module);
+        };
+        assert_eq!((module_name_index, module_flags, module_version_index), (2, 0, 3));
+        assert_eq!(requires, [ModuleRequires { requires_index: 4, requires_flags: 0x8000, requires_version_index: 5 }]);
+        assert_eq!(exports, [ModuleExports { exports_index: 6, exports_flags: 0, exports_to_count: 1, exports_to_index: vec![7] }]);
+        assert_eq!(opens, [ModuleOpens { opens_index: 8, opens_flags: 0, opens_to_count: 0, opens_to_index: vec![] }]);
+        assert_eq!(uses_index, [9]);
+        assert_eq!(provides, [ModuleProvides { provides_index: 10, provides_with_count: 2, provides_with_index: vec![11, 12] }]);
+    }
+
+    #[test]
+    fn module_truncated() {
+        let err = parse_attribute("Module", &MODULE[..MODULE.len() - 1]).unwrap_err();
+        assert_eq!(err.offset, 6 + 42);
+        assert_eq!(err.path, path(&["Module", "provides[0]"]));
+        assert_eq!(err.kind, ClassFormatErrorKind::UnexpectedEof { needed: 4, available: 3 });
+    }
+
+    #[test]
+    fn module_packages_and_main_class() {
+        let module_packages = parse_attribute("ModulePackages", &[0, 2, 0, 3, 0, 4]).unwrap();
+        assert!(matches!(module_packages, AttributeInfo::ModulePackages { package_count: 2, ref package_index, .. } if package_index == &[3, 4]));
+        let module_main_class = parse_attribute("ModuleMainClass", &[0, 5]).unwrap();
+        assert!(matches!(module_main_class, AttributeInfo::ModuleMainClass { main_class_index: 5, .. }));
+        let err = parse_attribute("ModulePackages", &[0, 2, 0, 3]).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6 + 2, path: path(&["ModulePackages", "package_index"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 4, available: 2 } });
+        let err = parse_attribute("ModuleMainClass", &[0]).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6, path: path(&["ModuleMainClass"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 1 } });
+    }
+
+    #[test]
+    fn record() {
+        let record = parse_attribute("Record", &[0, 2, 0, 3, 0, 4, 0, 0, 0, 5, 0, 6, 0, 0]).unwrap();
+        let AttributeInfo::Record { components_count: 2, components, .. } = record else {
+            panic!("expected Record, got {:?}", record);
+        };
+        assert!(matches!(components[..], [
+            RecordComponentInfo { name_index: 3, descriptor_index: 4, attributes_count: 0, .. },
+            RecordComponentInfo { name_index: 5, descriptor_index: 6, attributes_count: 0, .. },
+        ]));
+        let err = parse_attribute("Record", &[0, 1, 0, 3, 0, 4, 0, 1, 0, 1, 0]).unwrap_err();
+        assert_eq!(err.offset, 6 + 10);
+        assert_eq!(err.path, path(&["Record", "components[0]", "attributes[0]"]));
+        assert_eq!(err.kind, ClassFormatErrorKind::UnexpectedEof { needed: 4, available: 1 });
+    }
+
+    #[test]
+    fn nest_and_permitted_subclasses() {
+        let nest_host = parse_attribute("NestHost", &[0, 7]).unwrap();
+        assert!(matches!(nest_host, AttributeInfo::NestHost { host_class_index: 7, .. }));
+        let nest_members = parse_attribute("NestMembers", &[0, 2, 0, 8, 0, 9]).unwrap();
+        assert!(matches!(nest_members, AttributeInfo::NestMembers { number_of_classes: 2, ref classes, .. } if classes == &[8, 9]));
+        let permitted_subclasses = parse_attribute("PermittedSubclasses", &[0, 2, 0, 8, 0, 9]).unwrap();
+        assert!(matches!(permitted_subclasses, AttributeInfo::PermittedSubclasses { number_of_classes: 2, ref classes, .. } if classes == &[8, 9]));
+        let err = parse_attribute("PermittedSubclasses", &[0, 2, 0, 8]).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6 + 2, path: path(&["PermittedSubclasses", "classes"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 4, available: 2 } });
+        let err = parse_attribute("NestHost", &[]).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6, path: path(&["NestHost"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 0 } });
+    }
+
+    #[test]
+    fn inner_classes_and_enclosing_method() {
+        let inner_classes = parse_attribute("InnerClasses", &[0, 2, 0, 3, 0, 2, 0, 5, 0, 9, 0, 6, 0, 0, 0, 0, 0, 0]).unwrap();
+        let AttributeInfo::InnerClasses { number_of_classes: 2, classes, .. } = inner_classes else {
+            panic!("expected InnerClasses, got {:?}", inner_classes);
+        };
+        assert_eq!(classes, [
+            InnerClass { inner_class_info_index: 3, outer_class_info_index: 2, inner_name_index: 5, inner_class_access_flags: 9 },
+            InnerClass { inner_class_info_index: 6, outer_class_info_index: 0, inner_name_index: 0, inner_class_access_flags: 0 },
+        ]);
+        let enclosing_method = parse_attribute("EnclosingMethod", &[0, 2, 0, 0]).unwrap();
+        assert!(matches!(enclosing_method, AttributeInfo::EnclosingMethod { class_index: 2, method_index: 0, .. }));
+        let err = parse_attribute("InnerClasses", &[0, 2, 0, 3, 0, 2, 0, 5, 0, 9, 0, 6, 0]).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6 + 12, path: path(&["InnerClasses", "classes[1]"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 1 } });
+        let err = parse_attribute("EnclosingMethod", &[0, 2]).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6 + 2, path: path(&["EnclosingMethod"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 0 } });
+    }
+
+    #[test]
+    fn local_variable_tables() {
+        let info = [0, 1, 0, 2, 0, 10, 0, 3, 0, 4, 0, 1];
+        let local_variable_table = parse_attribute("LocalVariableTable", &info).unwrap();
+        let AttributeInfo::LocalVariableTable { local_variable_table_length: 1, local_variable_table, .. } = local_variable_table else {
+            panic!("expected LocalVariableTable, got {:?}", local_variable_table);
+        };
+        assert!(matches!(local_variable_table[..], [LocalVariableTableItem { start_pc: 2, length: 10, name_index: 3, descriptor_index: 4, index: 1 }]));
+        let local_variable_type_table = parse_attribute("LocalVariableTypeTable", &info).unwrap();
+        let AttributeInfo::LocalVariableTypeTable { local_variable_type_table_length: 1, local_variable_type_table, .. } = local_variable_type_table else {
+            panic!("expected LocalVariableTypeTable, got {:?}", local_variable_type_table);
+        };
+        assert!(matches!(local_variable_type_table[..], [LocalVariableTypeTableItem { start_pc: 2, length: 10, name_index: 3, signature_index: 4, index: 1 }]));
+    }
+
+    #[test]
+    fn local_variable_tables_truncated() {
+        let info = [0, 1, 0, 2, 0, 10, 0, 3, 0, 4, 0];
+        let err = parse_attribute("LocalVariableTable", &info).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6 + 10, path: path(&["LocalVariableTable", "local_variable_table[0]"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 1 } });
+        let err = parse_attribute("LocalVariableTypeTable", &info).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6 + 10, path: path(&["LocalVariableTypeTable", "local_variable_type_table[0]"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 1 } });
+    }
+
+    #[test]
+    fn exceptions_and_method_parameters() {
+        let exceptions = parse_attribute("Exceptions", &[0, 2, 0, 3, 0, 4]).unwrap();
+        assert!(matches!(exceptions, AttributeInfo::Exceptions { number_of_exceptions: 2, ref exception_index_table, .. } if exception_index_table == &[3, 4]));
+        let method_parameters = parse_attribute("MethodParameters", &[2, 0, 5, 0, 0x10, 0, 0, 0x10, 0]).unwrap();
+        let AttributeInfo::MethodParameters { parameters_count: 2, parameters, .. } = method_parameters else {
+            panic!("expected MethodParameters, got {:?}", method_parameters);
+        };
+        assert_eq!(parameters, [MethodParameter { name_index: 5, access_flags: 0x10 }, MethodParameter { name_index: 0, access_flags: 0x1000 }]);
+    }
+
+    #[test]
+    fn exceptions_and_method_parameters_truncated() {
+        let err = parse_attribute("Exceptions", &[0, 2, 0, 3]).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6 + 2, path: path(&["Exceptions", "exception_index_table"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 4, available: 2 } });
+        let err = parse_attribute("MethodParameters", &[2, 0, 5, 0, 0x10, 0, 0]).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6 + 7, path: path(&["MethodParameters", "parameters[1]"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 0 } });
+    }
+
+    #[derive(Debug)]
+    struct Range(u16, u16);
+
+    impl CustomAttribute for Range {
+        fn lines(&self, _cp_info: &[CpInfo]) -> Vec<String> {
+            vec![format!("{} to {}", self.0, self.1)]
+        }
+    }
+
+    fn range_decoders() -> AttributeDecoders {
+        let mut decoders = AttributeDecoders::new();
+        decoders.register("Range", |info, cp_info| {
+            assert_eq!(cp_info.len(), 2);
+            let (idx, start) = get_u2(0, info)?;
+            let (idx, end) = get_u2(idx, info)?;
+            assert_eq!(idx, info.len());
+            Ok(Box::new(Range(start, end)))
+        });
+        decoders
+    }
+
+    #[test]
+    fn unknown_attribute_keeps_info() {
+        let info = [0xCA, 0xFE, 0, 1, 2];
+        let unknown = parse_attribute("Range", &info).unwrap();
+        assert!(matches!(unknown, AttributeInfo::Unknown { attribute_name_index: 1, attribute_length: 5, ref info } if info[..] == [0xCA, 0xFE, 0, 1, 2]));
+        let unknown = parse_attribute_with_decoders("Other", &info, &range_decoders()).unwrap();
+        assert!(matches!(unknown, AttributeInfo::Unknown { .. }));
+    }
+
+    #[test]
+    fn decoded_attribute() {
+        let custom = parse_attribute_with_decoders("Range", &[0, 3, 1, 0], &range_decoders()).unwrap();
+        let AttributeInfo::Custom { attribute_length: 4, info, value, .. } = custom else {
+            panic!("expected Custom, got {:?}", custom);
+        };
+        assert_eq!(info[..], [0, 3, 1, 0]);
+        assert_eq!(value.lines(&[]), ["3 to 256"]);
+    }
+
+    #[test]
+    fn decoder_error_offset() {
+        // The decoder fails at offset 2 of info, 6 bytes past the attribute
+        let err = parse_attribute_with_decoders("Range", &[0, 3, 1], &range_decoders()).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 6 + 2, path: path(&["Range"]), kind: ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 1 } });
+    }
+
+    /// public class A { public static void m() { return; } }, without the
+    /// constructor. #8 is the string "a\0b", its NUL is encoded as C0 80.
+    fn class_file_bytes() -> Vec<u8> {
+        let mut bytes: Vec<u8> = vec![0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x34, 0x00, 0x09];
+        bytes.extend([7, 0, 2]);
+        bytes.extend([1, 0, 1, b'A']);
+        bytes.extend([7, 0, 4]);
+        bytes.extend([1, 0, 16]);
+        bytes.extend(b"java/lang/Object");
+        bytes.extend([1, 0, 4]);
+        bytes.extend(b"Code");
+        bytes.extend([1, 0, 1, b'm']);
+        bytes.extend([1, 0, 3]);
+        bytes.extend(b"()V");
+        bytes.extend([1, 0, 4, b'a', 0xC0, 0x80, b'b']);
+        // access_flags, this_class, super_class, interfaces_count and
+        // fields_count
+        bytes.extend([0x00, 0x21, 0x00, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00]);
+        // methods_count and the method with its Code
+        bytes.extend([0x00, 0x01, 0x00, 0x09, 0x00, 0x06, 0x00, 0x07, 0x00, 0x01]);
+        bytes.extend([0x00, 0x05, 0x00, 0x00, 0x00, 0x0D, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0xB1, 0x00, 0x00, 0x00, 0x00]);
+        // attributes_count
+        bytes.extend([0x00, 0x00]);
+        bytes
+    }
+
+    #[test]
+    fn into_owned_outlives_bytes() {
+        let bytes = class_file_bytes();
+        let borrowed = format!("{:?}", parse_class_file(&bytes).unwrap());
+        let owned = {
+            let bytes = class_file_bytes();
+            parse_class_file(&bytes).unwrap().into_owned()
+        };
+        assert_eq!(format!("{:?}", owned), borrowed);
+        assert!(matches!(&owned.methods[0].attributes[0], AttributeInfo::Code { code: Cow::Owned(code), .. } if code[..] == [0xB1]));
+    }
+
+    #[test]
+    fn utf8_borrows_ascii_strings() {
+        let bytes = class_file_bytes();
+        let class_file = parse_class_file(&bytes).unwrap();
+        assert!(matches!(&class_file.cp_info[2], CpInfo::ConstantUtf8 { bytes: Cow::Borrowed(_), bytes_str: Cow::Borrowed("A"), .. }));
+        // C0 80 is decoded to NUL, the string no longer matches the bytes
+        assert!(matches!(&class_file.cp_info[8], CpInfo::ConstantUtf8 { bytes: Cow::Borrowed(b"a\xC0\x80b"), bytes_str: Cow::Owned(str), .. } if str == "a\0b"));
+        assert!(matches!(&class_file.methods[0].attributes[0], AttributeInfo::Code { code: Cow::Borrowed(_), .. }));
+    }
 }
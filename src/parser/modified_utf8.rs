@@ -0,0 +1,145 @@
+use std::borrow::Cow;
+use crate::parser::error::{ClassFormatError, ClassFormatErrorKind};
+
+/// Decode the modified UTF-8 of a CONSTANT_Utf8 into a string. Unpaired
+/// surrogates, valid in Java strings but not in Rust ones, are replaced by
+/// U+FFFD, use `decode_modified_utf8_utf16` to keep them. Most strings are
+/// also valid UTF-8 and are borrowed from `bytes`. Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.4.7
+pub fn decode_modified_utf8(bytes: &[u8]) -> Result<Cow<'_, str>, ClassFormatError> {
+    // Modified UTF-8 differs from UTF-8 on NUL and supplementary characters,
+    // that are encoded on two and six bytes, both rejected by from_utf8. The
+    // four-byte UTF-8 sequences are not modified UTF-8.
+    if let Ok(str) = std::str::from_utf8(bytes) {
+        if bytes.iter().all(|b| *b < 0xF0) {
+            return Ok(Cow::Borrowed(str));
+        }
+    }
+    let utf16 = decode_modified_utf8_utf16(bytes)?;
+    Ok(Cow::Owned(char::decode_utf16(utf16).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()))
+}
+
+/// Decode modified UTF-8 into the UTF-16 code units of the Java string.
+/// Characters take one to three bytes: NUL is encoded on two bytes (C0 80)
+/// and supplementary characters as two surrogates of three bytes each. Like
+/// java.io.DataInputStream.readUTF, NUL bytes and overlong encodings are
+/// accepted. The offset of the error is the position inside `bytes`.
+pub fn decode_modified_utf8_utf16(bytes: &[u8]) -> Result<Vec<u16>, ClassFormatError> {
+    let mut utf16: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let b0 = bytes[idx] as u16;
+        let (unit, len) = match b0 >> 4 {
+            0..=7 => (b0, 1),
+            12 | 13 => {
+                let b1 = continuation_byte(idx + 1, bytes)?;
+                (((b0 & 0x1F) << 6) | b1, 2)
+            },
+            14 => {
+                let b1 = continuation_byte(idx + 1, bytes)?;
+                let b2 = continuation_byte(idx + 2, bytes)?;
+                (((b0 & 0x0F) << 12) | (b1 << 6) | b2, 3)
+            },
+            // A continuation byte without a leading byte, or a byte in
+            // F0-FF that never appears in modified UTF-8
+            _ => return Err(ClassFormatError::new(idx, ClassFormatErrorKind::InvalidUtf8))
+        };
+        utf16.push(unit);
+        idx += len;
+    }
+    Ok(utf16)
+}
+
+/// Low 6 bits of the continuation byte at `idx`.
+fn continuation_byte(idx: usize, bytes: &[u8]) -> Result<u16, ClassFormatError> {
+    match bytes.get(idx) {
+        Some(b) if b & 0xC0 == 0x80 => Ok((b & 0x3F) as u16),
+        _ => Err(ClassFormatError::new(idx, ClassFormatErrorKind::InvalidUtf8))
+    }
+}
+
+/// Encode a string in modified UTF-8, the inverse of `decode_modified_utf8`.
+pub fn encode_modified_utf8(str: &str) -> Vec<u8> {
+    let utf16: Vec<u16> = str.encode_utf16().collect();
+    encode_modified_utf8_utf16(&utf16)
+}
+
+/// Encode UTF-16 code units in modified UTF-8, surrogates are encoded one by
+/// one whether they are paired or not.
+pub fn encode_modified_utf8_utf16(utf16: &[u16]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(utf16.len());
+    for unit in utf16.iter().copied() {
+        match unit {
+            0x0001..=0x007F => bytes.push(unit as u8),
+            0x0000 | 0x0080..=0x07FF => {
+                bytes.push(0xC0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3F) as u8);
+            },
+            _ => {
+                bytes.push(0xE0 | (unit >> 12) as u8);
+                bytes.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+                bytes.push(0x80 | (unit & 0x3F) as u8);
+            }
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_ascii_is_borrowed() {
+        let str = decode_modified_utf8(b"java/lang/Object").unwrap();
+        assert!(matches!(str, Cow::Borrowed("java/lang/Object")));
+    }
+
+    #[test]
+    fn decode_encoded_nul() {
+        assert_eq!(decode_modified_utf8(&[b'a', 0xC0, 0x80, b'b']).unwrap(), "a\0b");
+        assert_eq!(encode_modified_utf8("a\0b"), vec![b'a', 0xC0, 0x80, b'b']);
+    }
+
+    #[test]
+    fn decode_two_and_three_byte_characters() {
+        // é and 中
+        let bytes = [0xC3, 0xA9, 0xE4, 0xB8, 0xAD];
+        assert_eq!(decode_modified_utf8(&bytes).unwrap(), "é中");
+        assert_eq!(encode_modified_utf8("é中"), bytes.to_vec());
+    }
+
+    #[test]
+    fn decode_surrogate_pair() {
+        // U+1F600 as the surrogates D83D and DE00
+        let bytes = [0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80];
+        assert_eq!(decode_modified_utf8(&bytes).unwrap(), "\u{1F600}");
+        assert_eq!(decode_modified_utf8_utf16(&bytes).unwrap(), vec![0xD83D, 0xDE00]);
+        assert_eq!(encode_modified_utf8("\u{1F600}"), bytes.to_vec());
+    }
+
+    #[test]
+    fn decode_unpaired_surrogate() {
+        let bytes = [b'a', 0xED, 0xA0, 0xBD];
+        assert_eq!(decode_modified_utf8(&bytes).unwrap(), "a\u{FFFD}");
+        let utf16 = decode_modified_utf8_utf16(&bytes).unwrap();
+        assert_eq!(utf16, vec![0x61, 0xD83D]);
+        assert_eq!(encode_modified_utf8_utf16(&utf16), bytes.to_vec());
+    }
+
+    #[test]
+    fn decode_four_byte_utf8() {
+        let err = decode_modified_utf8(&[b'a', 0xF0, 0x9F, 0x98, 0x80]).unwrap_err();
+        assert_eq!(err.offset, 1);
+        assert_eq!(err.kind, ClassFormatErrorKind::InvalidUtf8);
+    }
+
+    #[test]
+    fn decode_invalid_sequences() {
+        // Truncated character, invalid continuation byte and continuation
+        // byte without a leading byte
+        assert_eq!(decode_modified_utf8(&[0xE4, 0xB8]).unwrap_err().offset, 2);
+        assert_eq!(decode_modified_utf8(&[0xC3, b'A']).unwrap_err().offset, 1);
+        assert_eq!(decode_modified_utf8(&[b'a', 0x80]).unwrap_err().offset, 1);
+    }
+}
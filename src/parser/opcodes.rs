@@ -1,5 +1,10 @@
 #![allow(dead_code)]
 
+use std::fmt;
+use crate::parser::{get_u1, get_u2, get_u4, ParseResult};
+use crate::parser::error::{ClassFormatError, ClassFormatErrorKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Opcode {
     // Constants
     Nop,
@@ -223,16 +228,16 @@ impl Opcode {
     pub fn value(&self) -> u8 {
         match &self {
             // Constants
-            Opcode::Nop => 00,
-            Opcode::AconstNull => 01,
-            Opcode::IconstM1 => 02,
-            Opcode::Iconst0 => 03,
-            Opcode::Iconst1 => 04,
-            Opcode::Iconst2 => 05,
-            Opcode::Iconst3 => 06,
-            Opcode::Iconst4 => 07,
-            Opcode::Iconst5 => 08,
-            Opcode::Lconst0 => 09,
+            Opcode::Nop => 0,
+            Opcode::AconstNull => 1,
+            Opcode::IconstM1 => 2,
+            Opcode::Iconst0 => 3,
+            Opcode::Iconst1 => 4,
+            Opcode::Iconst2 => 5,
+            Opcode::Iconst3 => 6,
+            Opcode::Iconst4 => 7,
+            Opcode::Iconst5 => 8,
+            Opcode::Lconst0 => 9,
             Opcode::Lconst1 => 10,
             Opcode::Fconst0 => 11,
             Opcode::Fconst1 => 12,
@@ -661,226 +666,952 @@ impl Opcode {
             Opcode::Impdep2 => "impdep2",
         }
     }
+
+    /// Layout of the operands following the opcode in the `code` array.
+    pub fn operand_format(&self) -> OperandFormat {
+        match self {
+            Opcode::Bipush => OperandFormat::Byte,
+            Opcode::Sipush => OperandFormat::Short,
+            Opcode::Ldc => OperandFormat::ConstantPoolIndexU1,
+            Opcode::Iload | Opcode::Lload | Opcode::Fload | Opcode::Dload | Opcode::Aload |
+            Opcode::Istore | Opcode::Lstore | Opcode::Fstore | Opcode::Dstore | Opcode::Astore |
+            Opcode::Ret => OperandFormat::LocalIndex,
+            Opcode::LdcW | Opcode::Ldc2W |
+            Opcode::Getstatic | Opcode::Putstatic | Opcode::Getfield | Opcode::Putfield |
+            Opcode::Invokevirtual | Opcode::Invokespecial | Opcode::Invokestatic |
+            Opcode::New | Opcode::Anewarray | Opcode::Checkcast | Opcode::Instanceof => OperandFormat::ConstantPoolIndex,
+            Opcode::Invokeinterface => OperandFormat::InvokeInterface,
+            Opcode::Invokedynamic => OperandFormat::InvokeDynamic,
+            Opcode::Ifeq | Opcode::Ifne | Opcode::Iflt | Opcode::Ifge | Opcode::Ifgt | Opcode::Ifle |
+            Opcode::IfIcmpeq | Opcode::IfIcmpne | Opcode::IfIcmplt | Opcode::IfIcmpge | Opcode::IfIcmpgt | Opcode::IfIcmple |
+            Opcode::IfAcmpeq | Opcode::IfAcmpne | Opcode::Goto | Opcode::Jsr |
+            Opcode::Ifnull | Opcode::Ifnonnull => OperandFormat::Branch,
+            Opcode::GotoW | Opcode::JsrW => OperandFormat::BranchWide,
+            Opcode::Iinc => OperandFormat::Iinc,
+            Opcode::Newarray => OperandFormat::NewArray,
+            Opcode::Multianewarray => OperandFormat::MultiANewArray,
+            Opcode::Tableswitch => OperandFormat::TableSwitch,
+            Opcode::Lookupswitch => OperandFormat::LookupSwitch,
+            Opcode::Wide => OperandFormat::Wide,
+            _ => OperandFormat::None
+        }
+    }
+
+    /// Operand stack slots popped and pushed by the instruction, long and
+    /// double values take two slots.
+    pub fn stack_effect(&self) -> StackEffect {
+        match self {
+            // Constants
+            Opcode::Nop => StackEffect::fixed(0, 0),
+            Opcode::AconstNull |
+            Opcode::IconstM1 | Opcode::Iconst0 | Opcode::Iconst1 | Opcode::Iconst2 |
+            Opcode::Iconst3 | Opcode::Iconst4 | Opcode::Iconst5 |
+            Opcode::Fconst0 | Opcode::Fconst1 | Opcode::Fconst2 |
+            Opcode::Bipush | Opcode::Sipush => StackEffect::fixed(0, 1),
+            Opcode::Lconst0 | Opcode::Lconst1 | Opcode::Dconst0 | Opcode::Dconst1 => StackEffect::fixed(0, 2),
+            Opcode::Ldc | Opcode::LdcW => StackEffect::dependent(0, 1),
+            Opcode::Ldc2W => StackEffect::dependent(0, 2),
+            // Loads
+            Opcode::Iload | Opcode::Fload | Opcode::Aload |
+            Opcode::Iload0 | Opcode::Iload1 | Opcode::Iload2 | Opcode::Iload3 |
+            Opcode::Fload0 | Opcode::Fload1 | Opcode::Fload2 | Opcode::Fload3 |
+            Opcode::Aload0 | Opcode::Aload1 | Opcode::Aload2 | Opcode::Aload3 => StackEffect::fixed(0, 1),
+            Opcode::Lload | Opcode::Dload |
+            Opcode::Lload0 | Opcode::Lload1 | Opcode::Lload2 | Opcode::Lload3 |
+            Opcode::Dload0 | Opcode::Dload1 | Opcode::Dload2 | Opcode::Dload3 => StackEffect::fixed(0, 2),
+            Opcode::Iaload | Opcode::Faload | Opcode::Aaload |
+            Opcode::Baload | Opcode::Caload | Opcode::Saload => StackEffect::fixed(2, 1),
+            Opcode::Laload | Opcode::Daload => StackEffect::fixed(2, 2),
+            // Stores
+            Opcode::Istore | Opcode::Fstore | Opcode::Astore |
+            Opcode::Istore0 | Opcode::Istore1 | Opcode::Istore2 | Opcode::Istore3 |
+            Opcode::Fstore0 | Opcode::Fstore1 | Opcode::Fstore2 | Opcode::Fstore3 |
+            Opcode::Astore0 | Opcode::Astore1 | Opcode::Astore2 | Opcode::Astore3 => StackEffect::fixed(1, 0),
+            Opcode::Lstore | Opcode::Dstore |
+            Opcode::Lstore0 | Opcode::Lstore1 | Opcode::Lstore2 | Opcode::Lstore3 |
+            Opcode::Dstore0 | Opcode::Dstore1 | Opcode::Dstore2 | Opcode::Dstore3 => StackEffect::fixed(2, 0),
+            Opcode::Iastore | Opcode::Fastore | Opcode::Aastore |
+            Opcode::Bastore | Opcode::Castore | Opcode::Sastore => StackEffect::fixed(3, 0),
+            Opcode::Lastore | Opcode::Dastore => StackEffect::fixed(4, 0),
+            // Stack
+            Opcode::Pop => StackEffect::fixed(1, 0),
+            Opcode::Pop2 => StackEffect::fixed(2, 0),
+            Opcode::Dup => StackEffect::fixed(1, 2),
+            Opcode::DupX1 => StackEffect::fixed(2, 3),
+            Opcode::DupX2 => StackEffect::fixed(3, 4),
+            Opcode::Dup2 => StackEffect::fixed(2, 4),
+            Opcode::Dup2X1 => StackEffect::fixed(3, 5),
+            Opcode::Dup2X2 => StackEffect::fixed(4, 6),
+            Opcode::Swap => StackEffect::fixed(2, 2),
+            // Math
+            Opcode::Iadd | Opcode::Isub | Opcode::Imul | Opcode::Idiv | Opcode::Irem |
+            Opcode::Fadd | Opcode::Fsub | Opcode::Fmul | Opcode::Fdiv | Opcode::Frem |
+            Opcode::Ishl | Opcode::Ishr | Opcode::Iushr |
+            Opcode::Iand | Opcode::Ior | Opcode::Ixor => StackEffect::fixed(2, 1),
+            Opcode::Ladd | Opcode::Lsub | Opcode::Lmul | Opcode::Ldiv | Opcode::Lrem |
+            Opcode::Dadd | Opcode::Dsub | Opcode::Dmul | Opcode::Ddiv | Opcode::Drem |
+            Opcode::Land | Opcode::Lor | Opcode::Lxor => StackEffect::fixed(4, 2),
+            Opcode::Lshl | Opcode::Lshr | Opcode::Lushr => StackEffect::fixed(3, 2),
+            Opcode::Ineg | Opcode::Fneg => StackEffect::fixed(1, 1),
+            Opcode::Lneg | Opcode::Dneg => StackEffect::fixed(2, 2),
+            Opcode::Iinc => StackEffect::fixed(0, 0),
+            // Conversions
+            Opcode::I2f | Opcode::F2i | Opcode::I2b | Opcode::I2c | Opcode::I2s => StackEffect::fixed(1, 1),
+            Opcode::I2l | Opcode::I2d | Opcode::F2l | Opcode::F2d => StackEffect::fixed(1, 2),
+            Opcode::L2i | Opcode::L2f | Opcode::D2i | Opcode::D2f => StackEffect::fixed(2, 1),
+            Opcode::L2d | Opcode::D2l => StackEffect::fixed(2, 2),
+            // Comparisons
+            Opcode::Lcmp | Opcode::Dcmpl | Opcode::Dcmpg => StackEffect::fixed(4, 1),
+            Opcode::Fcmpl | Opcode::Fcmpg => StackEffect::fixed(2, 1),
+            Opcode::Ifeq | Opcode::Ifne | Opcode::Iflt | Opcode::Ifge | Opcode::Ifgt | Opcode::Ifle => StackEffect::fixed(1, 0),
+            Opcode::IfIcmpeq | Opcode::IfIcmpne | Opcode::IfIcmplt | Opcode::IfIcmpge | Opcode::IfIcmpgt | Opcode::IfIcmple |
+            Opcode::IfAcmpeq | Opcode::IfAcmpne => StackEffect::fixed(2, 0),
+            // Control
+            Opcode::Goto | Opcode::GotoW | Opcode::Ret => StackEffect::fixed(0, 0),
+            Opcode::Jsr | Opcode::JsrW => StackEffect::fixed(0, 1),
+            Opcode::Tableswitch | Opcode::Lookupswitch => StackEffect::fixed(1, 0),
+            Opcode::Ireturn | Opcode::Freturn | Opcode::Areturn => StackEffect::fixed(1, 0),
+            Opcode::Lreturn | Opcode::Dreturn => StackEffect::fixed(2, 0),
+            Opcode::Return => StackEffect::fixed(0, 0),
+            // References
+            Opcode::Getstatic => StackEffect::dependent(0, 0),
+            Opcode::Putstatic => StackEffect::dependent(0, 0),
+            Opcode::Getfield => StackEffect::dependent(1, 0),
+            Opcode::Putfield => StackEffect::dependent(1, 0),
+            Opcode::Invokevirtual | Opcode::Invokespecial | Opcode::Invokeinterface => StackEffect::dependent(1, 0),
+            Opcode::Invokestatic | Opcode::Invokedynamic => StackEffect::dependent(0, 0),
+            Opcode::New => StackEffect::fixed(0, 1),
+            Opcode::Newarray | Opcode::Anewarray | Opcode::Arraylength |
+            Opcode::Checkcast | Opcode::Instanceof => StackEffect::fixed(1, 1),
+            Opcode::Athrow => StackEffect::fixed(1, 0),
+            Opcode::Monitorenter | Opcode::Monitorexit => StackEffect::fixed(1, 0),
+            // Extended
+            Opcode::Wide => StackEffect::fixed(0, 0),
+            Opcode::Multianewarray => StackEffect::dependent(0, 1),
+            Opcode::Ifnull | Opcode::Ifnonnull => StackEffect::fixed(1, 0),
+            // Reserved
+            Opcode::Breakpoint | Opcode::Impdep1 | Opcode::Impdep2 => StackEffect::fixed(0, 0),
+        }
+    }
+
+    pub fn category(&self) -> OpcodeCategory {
+        match self {
+            Opcode::Nop | Opcode::Wide => OpcodeCategory::Other,
+            Opcode::AconstNull |
+            Opcode::IconstM1 | Opcode::Iconst0 | Opcode::Iconst1 | Opcode::Iconst2 |
+            Opcode::Iconst3 | Opcode::Iconst4 | Opcode::Iconst5 |
+            Opcode::Lconst0 | Opcode::Lconst1 | Opcode::Fconst0 | Opcode::Fconst1 | Opcode::Fconst2 |
+            Opcode::Dconst0 | Opcode::Dconst1 | Opcode::Bipush | Opcode::Sipush |
+            Opcode::Ldc | Opcode::LdcW | Opcode::Ldc2W => OpcodeCategory::Constant,
+            Opcode::Iload | Opcode::Lload | Opcode::Fload | Opcode::Dload | Opcode::Aload |
+            Opcode::Iload0 | Opcode::Iload1 | Opcode::Iload2 | Opcode::Iload3 |
+            Opcode::Lload0 | Opcode::Lload1 | Opcode::Lload2 | Opcode::Lload3 |
+            Opcode::Fload0 | Opcode::Fload1 | Opcode::Fload2 | Opcode::Fload3 |
+            Opcode::Dload0 | Opcode::Dload1 | Opcode::Dload2 | Opcode::Dload3 |
+            Opcode::Aload0 | Opcode::Aload1 | Opcode::Aload2 | Opcode::Aload3 |
+            Opcode::Iaload | Opcode::Laload | Opcode::Faload | Opcode::Daload |
+            Opcode::Aaload | Opcode::Baload | Opcode::Caload | Opcode::Saload => OpcodeCategory::Load,
+            Opcode::Istore | Opcode::Lstore | Opcode::Fstore | Opcode::Dstore | Opcode::Astore |
+            Opcode::Istore0 | Opcode::Istore1 | Opcode::Istore2 | Opcode::Istore3 |
+            Opcode::Lstore0 | Opcode::Lstore1 | Opcode::Lstore2 | Opcode::Lstore3 |
+            Opcode::Fstore0 | Opcode::Fstore1 | Opcode::Fstore2 | Opcode::Fstore3 |
+            Opcode::Dstore0 | Opcode::Dstore1 | Opcode::Dstore2 | Opcode::Dstore3 |
+            Opcode::Astore0 | Opcode::Astore1 | Opcode::Astore2 | Opcode::Astore3 |
+            Opcode::Iastore | Opcode::Lastore | Opcode::Fastore | Opcode::Dastore |
+            Opcode::Aastore | Opcode::Bastore | Opcode::Castore | Opcode::Sastore => OpcodeCategory::Store,
+            Opcode::Pop | Opcode::Pop2 | Opcode::Dup | Opcode::DupX1 | Opcode::DupX2 |
+            Opcode::Dup2 | Opcode::Dup2X1 | Opcode::Dup2X2 | Opcode::Swap => OpcodeCategory::Stack,
+            Opcode::Iadd | Opcode::Ladd | Opcode::Fadd | Opcode::Dadd |
+            Opcode::Isub | Opcode::Lsub | Opcode::Fsub | Opcode::Dsub |
+            Opcode::Imul | Opcode::Lmul | Opcode::Fmul | Opcode::Dmul |
+            Opcode::Idiv | Opcode::Ldiv | Opcode::Fdiv | Opcode::Ddiv |
+            Opcode::Irem | Opcode::Lrem | Opcode::Frem | Opcode::Drem |
+            Opcode::Ineg | Opcode::Lneg | Opcode::Fneg | Opcode::Dneg |
+            Opcode::Ishl | Opcode::Lshl | Opcode::Ishr | Opcode::Lshr | Opcode::Iushr | Opcode::Lushr |
+            Opcode::Iand | Opcode::Land | Opcode::Ior | Opcode::Lor | Opcode::Ixor | Opcode::Lxor |
+            Opcode::Iinc => OpcodeCategory::Math,
+            Opcode::I2l | Opcode::I2f | Opcode::I2d | Opcode::L2i | Opcode::L2f | Opcode::L2d |
+            Opcode::F2i | Opcode::F2l | Opcode::F2d | Opcode::D2i | Opcode::D2l | Opcode::D2f |
+            Opcode::I2b | Opcode::I2c | Opcode::I2s => OpcodeCategory::Conversion,
+            Opcode::Lcmp | Opcode::Fcmpl | Opcode::Fcmpg | Opcode::Dcmpl | Opcode::Dcmpg => OpcodeCategory::Comparison,
+            Opcode::Ifeq | Opcode::Ifne | Opcode::Iflt | Opcode::Ifge | Opcode::Ifgt | Opcode::Ifle |
+            Opcode::IfIcmpeq | Opcode::IfIcmpne | Opcode::IfIcmplt | Opcode::IfIcmpge | Opcode::IfIcmpgt | Opcode::IfIcmple |
+            Opcode::IfAcmpeq | Opcode::IfAcmpne | Opcode::Ifnull | Opcode::Ifnonnull |
+            Opcode::Goto | Opcode::GotoW | Opcode::Jsr | Opcode::JsrW | Opcode::Ret |
+            Opcode::Tableswitch | Opcode::Lookupswitch => OpcodeCategory::Branch,
+            Opcode::Ireturn | Opcode::Lreturn | Opcode::Freturn | Opcode::Dreturn |
+            Opcode::Areturn | Opcode::Return => OpcodeCategory::Return,
+            Opcode::Getstatic | Opcode::Putstatic | Opcode::Getfield | Opcode::Putfield => OpcodeCategory::Field,
+            Opcode::Invokevirtual | Opcode::Invokespecial | Opcode::Invokestatic |
+            Opcode::Invokeinterface | Opcode::Invokedynamic => OpcodeCategory::Invoke,
+            Opcode::New | Opcode::Newarray | Opcode::Anewarray | Opcode::Multianewarray | Opcode::Arraylength |
+            Opcode::Checkcast | Opcode::Instanceof => OpcodeCategory::Object,
+            Opcode::Athrow => OpcodeCategory::Throw,
+            Opcode::Monitorenter | Opcode::Monitorexit => OpcodeCategory::Monitor,
+            Opcode::Breakpoint | Opcode::Impdep1 | Opcode::Impdep2 => OpcodeCategory::Reserved,
+        }
+    }
+
+    /// The instruction transfers control somewhere else than the next
+    /// instruction (branches, switches, returns and athrow), so it is the
+    /// last instruction of its basic block.
+    pub fn ends_basic_block(&self) -> bool {
+        matches!(self.category(), OpcodeCategory::Branch | OpcodeCategory::Return | OpcodeCategory::Throw)
+    }
+
+    /// Local variable index implied by the `_<n>` loads and stores.
+    /// Ex.: iload_1 -> 1
+    pub fn implicit_local_index(&self) -> Option<u16> {
+        match self {
+            Opcode::Iload0 | Opcode::Lload0 | Opcode::Fload0 | Opcode::Dload0 | Opcode::Aload0 |
+            Opcode::Istore0 | Opcode::Lstore0 | Opcode::Fstore0 | Opcode::Dstore0 | Opcode::Astore0 => Some(0),
+            Opcode::Iload1 | Opcode::Lload1 | Opcode::Fload1 | Opcode::Dload1 | Opcode::Aload1 |
+            Opcode::Istore1 | Opcode::Lstore1 | Opcode::Fstore1 | Opcode::Dstore1 | Opcode::Astore1 => Some(1),
+            Opcode::Iload2 | Opcode::Lload2 | Opcode::Fload2 | Opcode::Dload2 | Opcode::Aload2 |
+            Opcode::Istore2 | Opcode::Lstore2 | Opcode::Fstore2 | Opcode::Dstore2 | Opcode::Astore2 => Some(2),
+            Opcode::Iload3 | Opcode::Lload3 | Opcode::Fload3 | Opcode::Dload3 | Opcode::Aload3 |
+            Opcode::Istore3 | Opcode::Lstore3 | Opcode::Fstore3 | Opcode::Dstore3 | Opcode::Astore3 => Some(3),
+            _ => None
+        }
+    }
+
+    /// jsr, jsr_w and ret cannot appear in class files of version 51.0 or
+    /// above; breakpoint, impdep1 and impdep2 are reserved and cannot appear
+    /// in any class file.
+    pub fn is_deprecated(&self) -> bool {
+        matches!(self, Opcode::Jsr | Opcode::JsrW | Opcode::Ret | Opcode::Breakpoint | Opcode::Impdep1 | Opcode::Impdep2)
+    }
+}
+
+/// Layout of the operands of an opcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandFormat {
+    None,
+    /// Signed byte (bipush).
+    Byte,
+    /// Signed short (sipush).
+    Short,
+    /// u1 local variable index, u2 when modified by wide.
+    LocalIndex,
+    /// u1 constant pool index (ldc).
+    ConstantPoolIndexU1,
+    /// u2 constant pool index.
+    ConstantPoolIndex,
+    /// s2 branch offset.
+    Branch,
+    /// s4 branch offset (goto_w, jsr_w).
+    BranchWide,
+    /// u1 local variable index and s1 constant, u2 and s2 when modified by
+    /// wide.
+    Iinc,
+    /// u1 array type.
+    NewArray,
+    /// u2 constant pool index and u1 dimensions.
+    MultiANewArray,
+    /// u2 constant pool index, u1 count and a zero byte.
+    InvokeInterface,
+    /// u2 constant pool index and two zero bytes.
+    InvokeDynamic,
+    /// Variable length, 4-byte aligned.
+    TableSwitch,
+    /// Variable length, 4-byte aligned.
+    LookupSwitch,
+    /// Prefix modifying the operands of the next instruction.
+    Wide,
+}
+
+/// Static effect of an instruction on the operand stack, in slots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackEffect {
+    pub pop: u8,
+    pub push: u8,
+    /// The instruction pops or pushes more values than `pop` and `push`
+    /// depending on the descriptor or the constant it references (field and
+    /// method instructions, ldc) or on its operands (multianewarray).
+    pub depends_on_operand: bool,
+}
+
+impl StackEffect {
+    fn fixed(pop: u8, push: u8) -> StackEffect {
+        StackEffect { pop, push, depends_on_operand: false }
+    }
+
+    fn dependent(pop: u8, push: u8) -> StackEffect {
+        StackEffect { pop, push, depends_on_operand: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpcodeCategory {
+    Constant,
+    Load,
+    Store,
+    Stack,
+    Math,
+    Conversion,
+    Comparison,
+    Branch,
+    Return,
+    Field,
+    Invoke,
+    Object,
+    Throw,
+    Monitor,
+    Reserved,
+    Other,
+}
+
+/// Error for the opcodes that are not assigned by the JVMS (203 to 253).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnassignedOpcode(pub u8);
+
+impl fmt::Display for UnassignedOpcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unassigned opcode {}", self.0)
+    }
+}
+
+impl std::error::Error for UnassignedOpcode {}
+
+impl TryFrom<u8> for Opcode {
+    type Error = UnassignedOpcode;
+
+    fn try_from(opcode: u8) -> Result<Opcode, UnassignedOpcode> {
+        let opcode = match opcode {
+            // Constants
+            0 => Opcode::Nop,
+            1 => Opcode::AconstNull,
+            2 => Opcode::IconstM1,
+            3 => Opcode::Iconst0,
+            4 => Opcode::Iconst1,
+            5 => Opcode::Iconst2,
+            6 => Opcode::Iconst3,
+            7 => Opcode::Iconst4,
+            8 => Opcode::Iconst5,
+            9 => Opcode::Lconst0,
+            10 => Opcode::Lconst1,
+            11 => Opcode::Fconst0,
+            12 => Opcode::Fconst1,
+            13 => Opcode::Fconst2,
+            14 => Opcode::Dconst0,
+            15 => Opcode::Dconst1,
+            16 => Opcode::Bipush,
+            17 => Opcode::Sipush,
+            18 => Opcode::Ldc,
+            19 => Opcode::LdcW,
+            20 => Opcode::Ldc2W,
+            // Loads
+            21 => Opcode::Iload,
+            22 => Opcode::Lload,
+            23 => Opcode::Fload,
+            24 => Opcode::Dload,
+            25 => Opcode::Aload,
+            26 => Opcode::Iload0,
+            27 => Opcode::Iload1,
+            28 => Opcode::Iload2,
+            29 => Opcode::Iload3,
+            30 => Opcode::Lload0,
+            31 => Opcode::Lload1,
+            32 => Opcode::Lload2,
+            33 => Opcode::Lload3,
+            34 => Opcode::Fload0,
+            35 => Opcode::Fload1,
+            36 => Opcode::Fload2,
+            37 => Opcode::Fload3,
+            38 => Opcode::Dload0,
+            39 => Opcode::Dload1,
+            40 => Opcode::Dload2,
+            41 => Opcode::Dload3,
+            42 => Opcode::Aload0,
+            43 => Opcode::Aload1,
+            44 => Opcode::Aload2,
+            45 => Opcode::Aload3,
+            46 => Opcode::Iaload,
+            47 => Opcode::Laload,
+            48 => Opcode::Faload,
+            49 => Opcode::Daload,
+            50 => Opcode::Aaload,
+            51 => Opcode::Baload,
+            52 => Opcode::Caload,
+            53 => Opcode::Saload,
+            // Stores
+            54 => Opcode::Istore,
+            55 => Opcode::Lstore,
+            56 => Opcode::Fstore,
+            57 => Opcode::Dstore,
+            58 => Opcode::Astore,
+            59 => Opcode::Istore0,
+            60 => Opcode::Istore1,
+            61 => Opcode::Istore2,
+            62 => Opcode::Istore3,
+            63 => Opcode::Lstore0,
+            64 => Opcode::Lstore1,
+            65 => Opcode::Lstore2,
+            66 => Opcode::Lstore3,
+            67 => Opcode::Fstore0,
+            68 => Opcode::Fstore1,
+            69 => Opcode::Fstore2,
+            70 => Opcode::Fstore3,
+            71 => Opcode::Dstore0,
+            72 => Opcode::Dstore1,
+            73 => Opcode::Dstore2,
+            74 => Opcode::Dstore3,
+            75 => Opcode::Astore0,
+            76 => Opcode::Astore1,
+            77 => Opcode::Astore2,
+            78 => Opcode::Astore3,
+            79 => Opcode::Iastore,
+            80 => Opcode::Lastore,
+            81 => Opcode::Fastore,
+            82 => Opcode::Dastore,
+            83 => Opcode::Aastore,
+            84 => Opcode::Bastore,
+            85 => Opcode::Castore,
+            86 => Opcode::Sastore,
+            // Stack
+            87 => Opcode::Pop,
+            88 => Opcode::Pop2,
+            89 => Opcode::Dup,
+            90 => Opcode::DupX1,
+            91 => Opcode::DupX2,
+            92 => Opcode::Dup2,
+            93 => Opcode::Dup2X1,
+            94 => Opcode::Dup2X2,
+            95 => Opcode::Swap,
+            // Math
+            96 => Opcode::Iadd,
+            97 => Opcode::Ladd,
+            98 => Opcode::Fadd,
+            99 => Opcode::Dadd,
+            100 => Opcode::Isub,
+            101 => Opcode::Lsub,
+            102 => Opcode::Fsub,
+            103 => Opcode::Dsub,
+            104 => Opcode::Imul,
+            105 => Opcode::Lmul,
+            106 => Opcode::Fmul,
+            107 => Opcode::Dmul,
+            108 => Opcode::Idiv,
+            109 => Opcode::Ldiv,
+            110 => Opcode::Fdiv,
+            111 => Opcode::Ddiv,
+            112 => Opcode::Irem,
+            113 => Opcode::Lrem,
+            114 => Opcode::Frem,
+            115 => Opcode::Drem,
+            116 => Opcode::Ineg,
+            117 => Opcode::Lneg,
+            118 => Opcode::Fneg,
+            119 => Opcode::Dneg,
+            120 => Opcode::Ishl,
+            121 => Opcode::Lshl,
+            122 => Opcode::Ishr,
+            123 => Opcode::Lshr,
+            124 => Opcode::Iushr,
+            125 => Opcode::Lushr,
+            126 => Opcode::Iand,
+            127 => Opcode::Land,
+            128 => Opcode::Ior,
+            129 => Opcode::Lor,
+            130 => Opcode::Ixor,
+            131 => Opcode::Lxor,
+            132 => Opcode::Iinc,
+            // Conversions
+            133 => Opcode::I2l,
+            134 => Opcode::I2f,
+            135 => Opcode::I2d,
+            136 => Opcode::L2i,
+            137 => Opcode::L2f,
+            138 => Opcode::L2d,
+            139 => Opcode::F2i,
+            140 => Opcode::F2l,
+            141 => Opcode::F2d,
+            142 => Opcode::D2i,
+            143 => Opcode::D2l,
+            144 => Opcode::D2f,
+            145 => Opcode::I2b,
+            146 => Opcode::I2c,
+            147 => Opcode::I2s,
+            // Comparisons
+            148 => Opcode::Lcmp,
+            149 => Opcode::Fcmpl,
+            150 => Opcode::Fcmpg,
+            151 => Opcode::Dcmpl,
+            152 => Opcode::Dcmpg,
+            153 => Opcode::Ifeq,
+            154 => Opcode::Ifne,
+            155 => Opcode::Iflt,
+            156 => Opcode::Ifge,
+            157 => Opcode::Ifgt,
+            158 => Opcode::Ifle,
+            159 => Opcode::IfIcmpeq,
+            160 => Opcode::IfIcmpne,
+            161 => Opcode::IfIcmplt,
+            162 => Opcode::IfIcmpge,
+            163 => Opcode::IfIcmpgt,
+            164 => Opcode::IfIcmple,
+            165 => Opcode::IfAcmpeq,
+            166 => Opcode::IfAcmpne,
+            // Control
+            167 => Opcode::Goto,
+            168 => Opcode::Jsr,
+            169 => Opcode::Ret,
+            170 => Opcode::Tableswitch,
+            171 => Opcode::Lookupswitch,
+            172 => Opcode::Ireturn,
+            173 => Opcode::Lreturn,
+            174 => Opcode::Freturn,
+            175 => Opcode::Dreturn,
+            176 => Opcode::Areturn,
+            177 => Opcode::Return,
+            // References
+            178 => Opcode::Getstatic,
+            179 => Opcode::Putstatic,
+            180 => Opcode::Getfield,
+            181 => Opcode::Putfield,
+            182 => Opcode::Invokevirtual,
+            183 => Opcode::Invokespecial,
+            184 => Opcode::Invokestatic,
+            185 => Opcode::Invokeinterface,
+            186 => Opcode::Invokedynamic,
+            187 => Opcode::New,
+            188 => Opcode::Newarray,
+            189 => Opcode::Anewarray,
+            190 => Opcode::Arraylength,
+            191 => Opcode::Athrow,
+            192 => Opcode::Checkcast,
+            193 => Opcode::Instanceof,
+            194 => Opcode::Monitorenter,
+            195 => Opcode::Monitorexit,
+            // Extended
+            196 => Opcode::Wide,
+            197 => Opcode::Multianewarray,
+            198 => Opcode::Ifnull,
+            199 => Opcode::Ifnonnull,
+            200 => Opcode::GotoW,
+            201 => Opcode::JsrW,
+            // Reserved
+            202 => Opcode::Breakpoint,
+            254 => Opcode::Impdep1,
+            255 => Opcode::Impdep2,
+            _ => return Err(UnassignedOpcode(opcode))
+        };
+        Ok(opcode)
+    }
+}
+/// Instruction decoded from the `code` array of a Code attribute.
+#[derive(Debug)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    /// The instruction is prefixed by `wide`, its local variable index (and
+    /// `iinc` constant) was read with two bytes.
+    pub wide: bool,
+    pub operand: Operand,
+}
+
+impl Instruction {
+    /// Index of the local variable read or written by a load, a store or
+    /// iinc. Array loads and stores and `ret` do not access a variable.
+    pub fn local_variable_index(&self) -> Option<u16> {
+        match (self.opcode.category(), &self.operand) {
+            (_, Operand::Iinc { index, value: _value }) => Some(*index),
+            (OpcodeCategory::Load | OpcodeCategory::Store, Operand::LocalIndex(index)) => Some(*index),
+            (OpcodeCategory::Load | OpcodeCategory::Store, _) => self.opcode.implicit_local_index(),
+            _ => None
+        }
+    }
 }
 
-pub fn get_opcode(opcode: u8) -> Opcode {
-    match opcode {
-        // Constants
-        00 => Opcode::Nop,
-        01 => Opcode::AconstNull,
-        02 => Opcode::IconstM1,
-        03 => Opcode::Iconst0,
-        04 => Opcode::Iconst1,
-        05 => Opcode::Iconst2,
-        06 => Opcode::Iconst3,
-        07 => Opcode::Iconst4,
-        08 => Opcode::Iconst5,
-        09 => Opcode::Lconst0,
-        10 => Opcode::Lconst1,
-        11 => Opcode::Fconst0,
-        12 => Opcode::Fconst1,
-        13 => Opcode::Fconst2,
-        14 => Opcode::Dconst0,
-        15 => Opcode::Dconst1,
-        16 => Opcode::Bipush,
-        17 => Opcode::Sipush,
-        18 => Opcode::Ldc,
-        19 => Opcode::LdcW,
-        20 => Opcode::Ldc2W,
-        // Loads
-        21 => Opcode::Iload,
-        22 => Opcode::Lload,
-        23 => Opcode::Fload,
-        24 => Opcode::Dload,
-        25 => Opcode::Aload,
-        26 => Opcode::Iload0,
-        27 => Opcode::Iload1,
-        28 => Opcode::Iload2,
-        29 => Opcode::Iload3,
-        30 => Opcode::Lload0,
-        31 => Opcode::Lload1,
-        32 => Opcode::Lload2,
-        33 => Opcode::Lload3,
-        34 => Opcode::Fload0,
-        35 => Opcode::Fload1,
-        36 => Opcode::Fload2,
-        37 => Opcode::Fload3,
-        38 => Opcode::Dload0,
-        39 => Opcode::Dload1,
-        40 => Opcode::Dload2,
-        41 => Opcode::Dload3,
-        42 => Opcode::Aload0,
-        43 => Opcode::Aload1,
-        44 => Opcode::Aload2,
-        45 => Opcode::Aload3,
-        46 => Opcode::Iaload,
-        47 => Opcode::Laload,
-        48 => Opcode::Faload,
-        49 => Opcode::Daload,
-        50 => Opcode::Aaload,
-        51 => Opcode::Baload,
-        52 => Opcode::Caload,
-        53 => Opcode::Saload,
-        // Stores
-        54 => Opcode::Istore,
-        55 => Opcode::Lstore,
-        56 => Opcode::Fstore,
-        57 => Opcode::Dstore,
-        58 => Opcode::Astore,
-        59 => Opcode::Istore0,
-        60 => Opcode::Istore1,
-        61 => Opcode::Istore2,
-        62 => Opcode::Istore3,
-        63 => Opcode::Lstore0,
-        64 => Opcode::Lstore1,
-        65 => Opcode::Lstore2,
-        66 => Opcode::Lstore3,
-        67 => Opcode::Fstore0,
-        68 => Opcode::Fstore1,
-        69 => Opcode::Fstore2,
-        70 => Opcode::Fstore3,
-        71 => Opcode::Dstore0,
-        72 => Opcode::Dstore1,
-        73 => Opcode::Dstore2,
-        74 => Opcode::Dstore3,
-        75 => Opcode::Astore0,
-        76 => Opcode::Astore1,
-        77 => Opcode::Astore2,
-        78 => Opcode::Astore3,
-        79 => Opcode::Iastore,
-        80 => Opcode::Lastore,
-        81 => Opcode::Fastore,
-        82 => Opcode::Dastore,
-        83 => Opcode::Aastore,
-        84 => Opcode::Bastore,
-        85 => Opcode::Castore,
-        86 => Opcode::Sastore,
-        // Stack
-        87 => Opcode::Pop,
-        88 => Opcode::Pop2,
-        89 => Opcode::Dup,
-        90 => Opcode::DupX1,
-        91 => Opcode::DupX2,
-        92 => Opcode::Dup2,
-        93 => Opcode::Dup2X1,
-        94 => Opcode::Dup2X2,
-        95 => Opcode::Swap,
-        // Math
-        96 => Opcode::Iadd,
-        97 => Opcode::Ladd,
-        98 => Opcode::Fadd,
-        99 => Opcode::Dadd,
-        100 => Opcode::Isub,
-        101 => Opcode::Lsub,
-        102 => Opcode::Fsub,
-        103 => Opcode::Dsub,
-        104 => Opcode::Imul,
-        105 => Opcode::Lmul,
-        106 => Opcode::Fmul,
-        107 => Opcode::Dmul,
-        108 => Opcode::Idiv,
-        109 => Opcode::Ldiv,
-        110 => Opcode::Fdiv,
-        111 => Opcode::Ddiv,
-        112 => Opcode::Irem,
-        113 => Opcode::Lrem,
-        114 => Opcode::Frem,
-        115 => Opcode::Drem,
-        116 => Opcode::Ineg,
-        117 => Opcode::Lneg,
-        118 => Opcode::Fneg,
-        119 => Opcode::Dneg,
-        120 => Opcode::Ishl,
-        121 => Opcode::Lshl,
-        122 => Opcode::Ishr,
-        123 => Opcode::Lshr,
-        124 => Opcode::Iushr,
-        125 => Opcode::Lushr,
-        126 => Opcode::Iand,
-        127 => Opcode::Land,
-        128 => Opcode::Ior,
-        129 => Opcode::Lor,
-        130 => Opcode::Ixor,
-        131 => Opcode::Lxor,
-        132 => Opcode::Iinc,
-        // Conversions
-        133 => Opcode::I2l,
-        134 => Opcode::I2f,
-        135 => Opcode::I2d,
-        136 => Opcode::L2i,
-        137 => Opcode::L2f,
-        138 => Opcode::L2d,
-        139 => Opcode::F2i,
-        140 => Opcode::F2l,
-        141 => Opcode::F2d,
-        142 => Opcode::D2i,
-        143 => Opcode::D2l,
-        144 => Opcode::D2f,
-        145 => Opcode::I2b,
-        146 => Opcode::I2c,
-        147 => Opcode::I2s,
-        // Comparisons
-        148 => Opcode::Lcmp,
-        149 => Opcode::Fcmpl,
-        150 => Opcode::Fcmpg,
-        151 => Opcode::Dcmpl,
-        152 => Opcode::Dcmpg,
-        153 => Opcode::Ifeq,
-        154 => Opcode::Ifne,
-        155 => Opcode::Iflt,
-        156 => Opcode::Ifge,
-        157 => Opcode::Ifgt,
-        158 => Opcode::Ifle,
-        159 => Opcode::IfIcmpeq,
-        160 => Opcode::IfIcmpne,
-        161 => Opcode::IfIcmplt,
-        162 => Opcode::IfIcmpge,
-        163 => Opcode::IfIcmpgt,
-        164 => Opcode::IfIcmple,
-        165 => Opcode::IfAcmpeq,
-        166 => Opcode::IfAcmpne,
-        // Control
-        167 => Opcode::Goto,
-        168 => Opcode::Jsr,
-        169 => Opcode::Ret,
-        170 => Opcode::Tableswitch,
-        171 => Opcode::Lookupswitch,
-        172 => Opcode::Ireturn,
-        173 => Opcode::Lreturn,
-        174 => Opcode::Freturn,
-        175 => Opcode::Dreturn,
-        176 => Opcode::Areturn,
-        177 => Opcode::Return,
-        // References
-        178 => Opcode::Getstatic,
-        179 => Opcode::Putstatic,
-        180 => Opcode::Getfield,
-        181 => Opcode::Putfield,
-        182 => Opcode::Invokevirtual,
-        183 => Opcode::Invokespecial,
-        184 => Opcode::Invokestatic,
-        185 => Opcode::Invokeinterface,
-        186 => Opcode::Invokedynamic,
-        187 => Opcode::New,
-        188 => Opcode::Newarray,
-        189 => Opcode::Anewarray,
-        190 => Opcode::Arraylength,
-        191 => Opcode::Athrow,
-        192 => Opcode::Checkcast,
-        193 => Opcode::Instanceof,
-        194 => Opcode::Monitorenter,
-        195 => Opcode::Monitorexit,
-        // Extended
-        196 => Opcode::Wide,
-        197 => Opcode::Multianewarray,
-        198 => Opcode::Ifnull,
-        199 => Opcode::Ifnonnull,
-        200 => Opcode::GotoW,
-        201 => Opcode::JsrW,
-        // Reserved
-        202 => Opcode::Breakpoint,
-        254 => Opcode::Impdep1,
-        255 => Opcode::Impdep2,
-        _ => panic!("Invalid opcode {}", opcode)
-    }
-}
\ No newline at end of file
+/// Operands of an instruction. Branch targets are absolute pcs in the `code`
+/// array.
+#[derive(Debug)]
+pub enum Operand {
+    None,
+    /// bipush
+    Byte(i8),
+    /// sipush
+    Short(i16),
+    /// Loads, stores and ret.
+    LocalIndex(u16),
+    /// ldc, field and method instructions, new, anewarray, checkcast,
+    /// instanceof and invokedynamic.
+    ConstantPoolIndex(u16),
+    /// Conditional branches, goto, jsr and their wide versions.
+    BranchTarget(u32),
+    Iinc { index: u16, value: i16 },
+    NewArray(ArrayType),
+    MultiANewArray { index: u16, dimensions: u8 },
+    InvokeInterface { index: u16, count: u8 },
+    TableSwitch { default: u32, low: i32, high: i32, targets: Vec<u32> },
+    LookupSwitch { default: u32, pairs: Vec<(i32, u32)> },
+}
+
+/// `atype` operand of the `newarray` instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayType {
+    Boolean,
+    Char,
+    Float,
+    Double,
+    Byte,
+    Short,
+    Int,
+    Long,
+}
+
+impl ArrayType {
+    pub fn from_value(atype: u8) -> Option<ArrayType> {
+        match atype {
+            4 => Some(ArrayType::Boolean),
+            5 => Some(ArrayType::Char),
+            6 => Some(ArrayType::Float),
+            7 => Some(ArrayType::Double),
+            8 => Some(ArrayType::Byte),
+            9 => Some(ArrayType::Short),
+            10 => Some(ArrayType::Int),
+            11 => Some(ArrayType::Long),
+            _ => None
+        }
+    }
+
+    pub fn value(&self) -> u8 {
+        match self {
+            ArrayType::Boolean => 4,
+            ArrayType::Char => 5,
+            ArrayType::Float => 6,
+            ArrayType::Double => 7,
+            ArrayType::Byte => 8,
+            ArrayType::Short => 9,
+            ArrayType::Int => 10,
+            ArrayType::Long => 11,
+        }
+    }
+
+    pub fn str(&self) -> &str {
+        match self {
+            ArrayType::Boolean => "boolean",
+            ArrayType::Char => "char",
+            ArrayType::Float => "float",
+            ArrayType::Double => "double",
+            ArrayType::Byte => "byte",
+            ArrayType::Short => "short",
+            ArrayType::Int => "int",
+            ArrayType::Long => "long",
+        }
+    }
+}
+
+/// Decode the `code` array of a Code attribute into a list of instructions
+/// along with their pc. Errors report the offset in the `code` array.
+pub fn decode_instructions(code: &[u8]) -> Result<Vec<(u32, Instruction)>, ClassFormatError> {
+    let mut instructions: Vec<(u32, Instruction)> = Vec::new();
+    let mut pc: usize = 0;
+    while pc < code.len() {
+        let (next_pc, instruction) = decode_instruction(pc, code).map_err(|e| e.within(format!("code[{}]", pc)))?;
+        instructions.push((pc as u32, instruction));
+        pc = next_pc;
+    }
+    Ok(instructions)
+}
+
+/// Decode the instruction at `pc`, returns the pc of the next instruction.
+pub fn decode_instruction(pc: usize, code: &[u8]) -> ParseResult<Instruction> {
+    let (idx, value) = get_u1(pc, code)?;
+    let opcode = Opcode::try_from(value).map_err(|_| ClassFormatError::new(pc, ClassFormatErrorKind::InvalidOpcode(value)))?;
+    let (idx, operand) = match opcode.operand_format() {
+        OperandFormat::None => (idx, Operand::None),
+        OperandFormat::Byte => {
+            let (idx, byte) = get_u1(idx, code)?;
+            (idx, Operand::Byte(byte as i8))
+        },
+        OperandFormat::Short => {
+            let (idx, short) = get_u2(idx, code)?;
+            (idx, Operand::Short(short as i16))
+        },
+        OperandFormat::LocalIndex => {
+            let (idx, index) = get_u1(idx, code)?;
+            (idx, Operand::LocalIndex(index as u16))
+        },
+        OperandFormat::ConstantPoolIndexU1 => {
+            let (idx, index) = get_u1(idx, code)?;
+            (idx, Operand::ConstantPoolIndex(index as u16))
+        },
+        OperandFormat::ConstantPoolIndex => {
+            let (idx, index) = get_u2(idx, code)?;
+            (idx, Operand::ConstantPoolIndex(index))
+        },
+        OperandFormat::InvokeDynamic => {
+            // The index is followed by two bytes that must be zero.
+            let (idx, index) = get_u2(idx, code)?;
+            let (idx, _) = get_u2(idx, code)?;
+            (idx, Operand::ConstantPoolIndex(index))
+        },
+        OperandFormat::InvokeInterface => {
+            // The count is followed by a byte that must be zero.
+            let (idx, index) = get_u2(idx, code)?;
+            let (idx, count) = get_u1(idx, code)?;
+            let (idx, _) = get_u1(idx, code)?;
+            (idx, Operand::InvokeInterface { index, count })
+        },
+        OperandFormat::Branch => {
+            let (idx, offset) = get_u2(idx, code)?;
+            (idx, Operand::BranchTarget(branch_target(pc, offset as i16 as i32)?))
+        },
+        OperandFormat::BranchWide => {
+            let (idx, offset) = get_u4(idx, code)?;
+            (idx, Operand::BranchTarget(branch_target(pc, offset as i32)?))
+        },
+        OperandFormat::Iinc => {
+            let (idx, index) = get_u1(idx, code)?;
+            let (idx, value) = get_u1(idx, code)?;
+            (idx, Operand::Iinc { index: index as u16, value: value as i8 as i16 })
+        },
+        OperandFormat::NewArray => {
+            let (idx, atype) = get_u1(idx, code)?;
+            let array_type = ArrayType::from_value(atype)
+                .ok_or_else(|| ClassFormatError::new(idx - 1, ClassFormatErrorKind::InvalidArrayType(atype)))?;
+            (idx, Operand::NewArray(array_type))
+        },
+        OperandFormat::MultiANewArray => {
+            let (idx, index) = get_u2(idx, code)?;
+            let (idx, dimensions) = get_u1(idx, code)?;
+            (idx, Operand::MultiANewArray { index, dimensions })
+        },
+        OperandFormat::TableSwitch => decode_tableswitch(pc, code)?,
+        OperandFormat::LookupSwitch => decode_lookupswitch(pc, code)?,
+        OperandFormat::Wide => return decode_wide_instruction(idx, code),
+    };
+    Ok((idx, Instruction { opcode, wide: false, operand }))
+}
+
+/// Decode the instruction following a `wide` prefix, `idx` points right after
+/// the prefix.
+fn decode_wide_instruction(idx: usize, code: &[u8]) -> ParseResult<Instruction> {
+    let (new_idx, value) = get_u1(idx, code)?;
+    let opcode = Opcode::try_from(value).map_err(|_| ClassFormatError::new(idx, ClassFormatErrorKind::InvalidOpcode(value)))?;
+    let (new_idx, operand) = match opcode.operand_format() {
+        OperandFormat::LocalIndex => {
+            let (new_idx, index) = get_u2(new_idx, code)?;
+            (new_idx, Operand::LocalIndex(index))
+        },
+        OperandFormat::Iinc => {
+            let (new_idx, index) = get_u2(new_idx, code)?;
+            let (new_idx, value) = get_u2(new_idx, code)?;
+            (new_idx, Operand::Iinc { index, value: value as i16 })
+        },
+        _ => return Err(ClassFormatError::new(idx, ClassFormatErrorKind::InvalidWideOpcode(value)))
+    };
+    Ok((new_idx, Instruction { opcode, wide: true, operand }))
+}
+
+/// tableswitch: 0-3 bytes of padding so that `default` starts at a multiple of
+/// 4 from the start of the `code` array, then default, low, high and
+/// high - low + 1 jump offsets.
+fn decode_tableswitch(pc: usize, code: &[u8]) -> ParseResult<Operand> {
+    let idx = switch_operands_start(pc);
+    let (idx, default) = get_u4(idx, code)?;
+    let (idx, low) = get_u4(idx, code)?;
+    let (idx, high) = get_u4(idx, code)?;
+    let (low, high) = (low as i32, high as i32);
+    if low > high {
+        return Err(ClassFormatError::new(idx - 8, ClassFormatErrorKind::InvalidSwitch(format!("low {} is greater than high {}", low, high))));
+    }
+    let count = (high as i64 - low as i64 + 1) as usize;
+    let mut idx = idx;
+    let mut targets: Vec<u32> = Vec::with_capacity(count.min(code.len() / 4));
+    for _ in 0..count {
+        let (i, offset) = get_u4(idx, code)?;
+        idx = i;
+        targets.push(branch_target(pc, offset as i32)?);
+    }
+    let default = branch_target(pc, default as i32)?;
+    Ok((idx, Operand::TableSwitch { default, low, high, targets }))
+}
+
+/// lookupswitch: 0-3 bytes of padding, then default, npairs and npairs
+/// match-offset pairs.
+fn decode_lookupswitch(pc: usize, code: &[u8]) -> ParseResult<Operand> {
+    let idx = switch_operands_start(pc);
+    let (idx, default) = get_u4(idx, code)?;
+    let (idx, npairs) = get_u4(idx, code)?;
+    let npairs = npairs as i32;
+    if npairs < 0 {
+        return Err(ClassFormatError::new(idx - 4, ClassFormatErrorKind::InvalidSwitch(format!("negative npairs {}", npairs))));
+    }
+    let mut idx = idx;
+    let mut pairs: Vec<(i32, u32)> = Vec::with_capacity((npairs as usize).min(code.len() / 8));
+    for _ in 0..npairs {
+        let (i, key) = get_u4(idx, code)?;
+        let (i, offset) = get_u4(i, code)?;
+        idx = i;
+        pairs.push((key as i32, branch_target(pc, offset as i32)?));
+    }
+    let default = branch_target(pc, default as i32)?;
+    Ok((idx, Operand::LookupSwitch { default, pairs }))
+}
+
+fn switch_operands_start(pc: usize) -> usize {
+    (pc + 4) & !3
+}
+
+fn branch_target(pc: usize, offset: i32) -> Result<u32, ClassFormatError> {
+    let target = pc as i64 + offset as i64;
+    if target < 0 || target > u32::MAX as i64 {
+        Err(ClassFormatError::new(pc, ClassFormatErrorKind::InvalidBranchTarget(target)))
+    } else {
+        Ok(target as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_wide_local_index() {
+        let (next_pc, instruction) = decode_instruction(0, &[0xc4, 0x15, 0x01, 0x02]).unwrap();
+        assert_eq!(next_pc, 4);
+        assert_eq!(instruction.opcode, Opcode::Iload);
+        assert!(instruction.wide);
+        assert!(matches!(instruction.operand, Operand::LocalIndex(0x0102)));
+    }
+
+    #[test]
+    fn decode_wide_iinc() {
+        let (next_pc, instruction) = decode_instruction(0, &[0xc4, 0x84, 0x00, 0x05, 0xff, 0xfe]).unwrap();
+        assert_eq!(next_pc, 6);
+        assert_eq!(instruction.opcode, Opcode::Iinc);
+        assert!(matches!(instruction.operand, Operand::Iinc { index: 5, value: -2 }));
+    }
+
+    #[test]
+    fn decode_wide_invalid_opcode() {
+        // iadd cannot be prefixed by wide
+        let err = decode_instruction(0, &[0xc4, 0x60]).unwrap_err();
+        assert_eq!(err.offset, 1);
+        assert_eq!(err.kind, ClassFormatErrorKind::InvalidWideOpcode(0x60));
+    }
+
+    #[test]
+    fn switch_padding() {
+        assert_eq!(switch_operands_start(0), 4);
+        assert_eq!(switch_operands_start(1), 4);
+        assert_eq!(switch_operands_start(3), 4);
+        assert_eq!(switch_operands_start(4), 8);
+    }
+
+    #[test]
+    fn decode_tableswitch_after_padding() {
+        let code = [
+            0x00, // nop
+            0xaa, 0x00, 0x00, // tableswitch and 2 bytes of padding
+            0x00, 0x00, 0x00, 0x10, // default
+            0x00, 0x00, 0x00, 0x01, // low
+            0x00, 0x00, 0x00, 0x02, // high
+            0x00, 0x00, 0x00, 0x14,
+            0x00, 0x00, 0x00, 0x18,
+        ];
+        let instructions = decode_instructions(&code).unwrap();
+        assert_eq!(instructions.len(), 2);
+        let (pc, instruction) = &instructions[1];
+        assert_eq!(*pc, 1);
+        match &instruction.operand {
+            Operand::TableSwitch { default, low, high, targets } => {
+                assert_eq!((*default, *low, *high), (17, 1, 2));
+                assert_eq!(targets, &vec![21, 25]);
+            },
+            operand => panic!("Expected a tableswitch, found {:?}", operand)
+        }
+    }
+
+    #[test]
+    fn decode_lookupswitch_after_padding() {
+        let code = [
+            0xab, 0x00, 0x00, 0x00, // lookupswitch and 3 bytes of padding
+            0x00, 0x00, 0x00, 0x08, // default
+            0x00, 0x00, 0x00, 0x01, // npairs
+            0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x0c,
+        ];
+        let (next_pc, instruction) = decode_instruction(0, &code).unwrap();
+        assert_eq!(next_pc, code.len());
+        match &instruction.operand {
+            Operand::LookupSwitch { default, pairs } => {
+                assert_eq!(*default, 8);
+                assert_eq!(pairs, &vec![(-1, 12)]);
+            },
+            operand => panic!("Expected a lookupswitch, found {:?}", operand)
+        }
+    }
+
+    #[test]
+    fn decode_tableswitch_low_greater_than_high() {
+        let code = [0xaa, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 1];
+        let err = decode_instruction(0, &code).unwrap_err();
+        assert!(matches!(err.kind, ClassFormatErrorKind::InvalidSwitch(_)));
+    }
+
+    #[test]
+    fn decode_truncated_operand() {
+        // sipush with a single byte
+        let err = decode_instruction(0, &[0x11, 0x01]).unwrap_err();
+        assert_eq!(err.offset, 1);
+        assert_eq!(err.kind, ClassFormatErrorKind::UnexpectedEof { needed: 2, available: 1 });
+    }
+
+    #[test]
+    fn decode_truncated_tableswitch() {
+        // low 0 and high 5 without the jump offsets
+        let code = [0xaa, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5];
+        let err = decode_instruction(0, &code).unwrap_err();
+        assert!(matches!(err.kind, ClassFormatErrorKind::UnexpectedEof { .. }));
+    }
+
+    #[test]
+    fn decode_truncated_wide() {
+        let err = decode_instruction(0, &[0xc4]).unwrap_err();
+        assert_eq!(err.kind, ClassFormatErrorKind::UnexpectedEof { needed: 1, available: 0 });
+    }
+
+    #[test]
+    fn decode_negative_branch_target() {
+        // goto -1
+        let err = decode_instruction(0, &[0xa7, 0xff, 0xff]).unwrap_err();
+        assert_eq!(err.kind, ClassFormatErrorKind::InvalidBranchTarget(-1));
+    }
+
+    #[test]
+    fn decode_instructions_error_path() {
+        let err = decode_instructions(&[0x00, 0xcb]).unwrap_err();
+        assert_eq!(err.offset, 1);
+        assert_eq!(err.path, vec![String::from("code[1]")]);
+        assert_eq!(err.kind, ClassFormatErrorKind::InvalidOpcode(0xcb));
+    }
+
+    #[test]
+    fn opcode_value_round_trip() {
+        for value in 0..=255u8 {
+            match Opcode::try_from(value) {
+                Ok(opcode) => assert_eq!(opcode.value(), value),
+                Err(err) => {
+                    assert!((203..=253).contains(&value), "opcode {} is assigned", value);
+                    assert_eq!(err, UnassignedOpcode(value));
+                }
+            }
+        }
+        assert!(Opcode::try_from(202).is_ok());
+        assert!(Opcode::try_from(254).is_ok());
+    }
+
+    #[test]
+    fn opcode_metadata() {
+        assert_eq!(Opcode::Dup2X2.stack_effect(), StackEffect { pop: 4, push: 6, depends_on_operand: false });
+        assert_eq!(Opcode::Lreturn.stack_effect(), StackEffect { pop: 2, push: 0, depends_on_operand: false });
+        assert!(Opcode::Invokevirtual.stack_effect().depends_on_operand);
+        assert!(Opcode::Getfield.stack_effect().depends_on_operand);
+        assert!(Opcode::Ldc.stack_effect().depends_on_operand);
+        assert!(!Opcode::Iadd.stack_effect().depends_on_operand);
+        assert!(Opcode::Jsr.is_deprecated());
+        assert!(Opcode::Ret.is_deprecated());
+        assert!(!Opcode::Goto.is_deprecated());
+    }
+}
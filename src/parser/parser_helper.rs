@@ -1,126 +1,311 @@
 use crate::parser::access_flags::MethodAccessFlag;
-use crate::parser::ast::{ClassFile, CpInfo, FieldType, FieldTypeTerm, MethodInfo};
+use crate::parser::ast::{AttributeInfo, BootstrapCallSite, ClassFile, CpInfo, InnerClass, MethodDescriptor, MethodInfo};
+use crate::parser::descriptor::parse_method_descriptor;
+use crate::parser::error::{ClassFormatError, ClassFormatErrorKind};
 
-pub fn get_constant_class_name(class_index: u16, cp_info: &Vec<CpInfo>) -> String {
+pub fn get_constant_class_name(class_index: u16, cp_info: &[CpInfo]) -> String {
     let constant_class = &cp_info[class_index as usize];
     if let CpInfo::ConstantClass { tag: _tag, name_index } = constant_class {
-        get_constant_utf8(name_index.clone(), cp_info)
+        get_constant_utf8(*name_index, cp_info)
     } else {
         panic!("Expected ConstantClass for idx {}", class_index)
     }
 }
 
-pub fn get_constant_utf8(name_index: u16, cp_info: &Vec<CpInfo>) -> String {
+/// Entry of the InnerClasses attribute of the class describing the class with
+/// the internal name `class_name`, None if it is not a nested class.
+pub fn get_inner_class<'a>(class_name: &str, class_file: &'a ClassFile) -> Option<&'a InnerClass> {
+    class_file.attributes.iter()
+        .find_map(|attribute| match attribute {
+            AttributeInfo::InnerClasses { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, number_of_classes: _number_of_classes, classes } => {
+                Some(classes)
+            },
+            _ => None
+        })?
+        .iter()
+        .find(|inner_class| get_constant_class_name(inner_class.inner_class_info_index, &class_file.cp_info) == class_name)
+}
+
+/// Java name of the class with the internal name `class_name`, nested classes
+/// are resolved with the InnerClasses and EnclosingMethod attributes of the
+/// class. Ex.:
+/// java/util/Map$Entry -> java.util.Map.Entry
+/// p/Outer$1 -> anonymous class in p.Outer.run(int, java.lang.String)
+/// p/Outer$1Local -> local class Local in p.Outer.run(int, java.lang.String)
+pub fn get_java_class_name(class_name: &str, class_file: &ClassFile) -> String {
+    java_class_name(class_name, class_file, 0)
+}
+
+/// Classes nested deeper than this are printed with their binary name, it
+/// also stops the recursion on malformed InnerClasses attributes.
+const MAX_NESTED_CLASS_DEPTH: usize = 32;
+
+fn java_class_name(class_name: &str, class_file: &ClassFile, depth: usize) -> String {
+    let inner_class = match get_inner_class(class_name, class_file) {
+        Some(inner_class) if depth < MAX_NESTED_CLASS_DEPTH => inner_class,
+        _ => return class_name.replace('/', ".")
+    };
+    let cp_info = &class_file.cp_info;
+    match (inner_class.outer_class_info_index, inner_class.inner_name_index) {
+        (0, 0) => format!("anonymous class in {}", enclosing_member(class_name, class_file, depth)),
+        (0, inner_name_index) => {
+            let inner_name = get_constant_utf8(inner_name_index, cp_info);
+            format!("local class {} in {}", inner_name, enclosing_member(class_name, class_file, depth))
+        },
+        // A member class without a name is not valid, keep the binary name
+        (_, 0) => class_name.replace('/', "."),
+        (outer_class_info_index, inner_name_index) => {
+            let outer_class_name = get_constant_class_name(outer_class_info_index, cp_info);
+            format!("{}.{}", java_class_name(&outer_class_name, class_file, depth + 1), get_constant_utf8(inner_name_index, cp_info))
+        }
+    }
+}
+
+/// Class or method that encloses a local or anonymous class. Only the class
+/// itself has an EnclosingMethod attribute, for the other classes the
+/// enclosing class is taken from the binary name.
+fn enclosing_member(class_name: &str, class_file: &ClassFile, depth: usize) -> String {
+    let cp_info = &class_file.cp_info;
+    let enclosing_method = class_file.attributes.iter()
+        .find_map(|attribute| match attribute {
+            AttributeInfo::EnclosingMethod { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, class_index, method_index } => {
+                Some((*class_index, *method_index))
+            },
+            _ => None
+        })
+        .filter(|_| get_constant_class_name(class_file.this_class, cp_info) == class_name);
+    match enclosing_method {
+        Some((class_index, method_index)) => {
+            let enclosing_class = java_class_name(&get_constant_class_name(class_index, cp_info), class_file, depth + 1);
+            if method_index == 0 {
+                return enclosing_class;
+            }
+            let method_name = get_name(method_index, cp_info);
+            match parse_method_descriptor(&get_type(method_index, cp_info)) {
+                Ok(descriptor) => {
+                    let params: Vec<String> = descriptor.params.iter().map(|p| p.str_java()).collect();
+                    format!("{}.{}({})", enclosing_class, method_name, params.join(", "))
+                },
+                Err(_) => format!("{}.{}", enclosing_class, method_name)
+            }
+        },
+        None => match class_name.rfind('$') {
+            Some(n) => java_class_name(&class_name[..n], class_file, depth + 1),
+            None => class_name.replace('/', ".")
+        }
+    }
+}
+
+/// Name of a module, ex.: java.base
+pub fn get_constant_module_name(module_index: u16, cp_info: &[CpInfo]) -> String {
+    let constant_module = &cp_info[module_index as usize];
+    if let CpInfo::ConstantModule { tag: _tag, name_index } = constant_module {
+        get_constant_utf8(*name_index, cp_info)
+    } else {
+        panic!("Expected ConstantModule for idx {}", module_index)
+    }
+}
+
+/// Internal name of a package, ex.: java/lang
+pub fn get_constant_package_name(package_index: u16, cp_info: &[CpInfo]) -> String {
+    let constant_package = &cp_info[package_index as usize];
+    if let CpInfo::ConstantPackage { tag: _tag, name_index } = constant_package {
+        get_constant_utf8(*name_index, cp_info)
+    } else {
+        panic!("Expected ConstantPackage for idx {}", package_index)
+    }
+}
+
+/// Name of the class caught by an exception handler, `any` when the handler
+/// catches every exception.
+pub fn get_catch_type_name(catch_type: u16, cp_info: &[CpInfo]) -> String {
+    if catch_type == 0 {
+        String::from("any")
+    } else {
+        get_constant_class_name(catch_type, cp_info)
+    }
+}
+
+pub fn get_constant_utf8(name_index: u16, cp_info: &[CpInfo]) -> String {
     let constant_utf8 = &cp_info[name_index as usize];
     if let CpInfo::ConstantUtf8 { tag: _tag, length: _length, bytes: _bytes, bytes_str } = constant_utf8 {
-        bytes_str.clone()
+        bytes_str.to_string()
     } else {
         panic!("Expected ConstantUtf8 for idx {}", name_index)
     }
 }
 
-pub fn get_name(name_and_type_index: u16, cp_info: &Vec<CpInfo>) -> String {
+pub fn get_name(name_and_type_index: u16, cp_info: &[CpInfo]) -> String {
     let constant_name_and_type = &cp_info[name_and_type_index as usize];
     if let CpInfo::ConstantNameAndType { tag: _tag, name_index, descriptor_index: _descriptor_index } = constant_name_and_type {
-        get_constant_utf8(name_index.clone(), cp_info)
+        get_constant_utf8(*name_index, cp_info)
     } else {
         panic!("Expected ConstantNameAndType at idx {}", name_and_type_index)
     }
 }
 
+/// Quote a name that is not a sequence of Java identifiers separated by `/`,
+/// the way javap does. Ex.: "<init>", "[I", java/lang/Object
 pub fn get_name_quoted(str: String) -> String {
-    if str == "<init>" { format!("\"{}\"", str) } else { str }
+    let mut previous = '/';
+    for c in str.chars() {
+        let is_identifier_start = c.is_alphabetic() || c == '_' || c == '$';
+        let is_identifier_part = is_identifier_start || c.is_numeric();
+        if (previous == '/' && !is_identifier_start) || (c != '/' && !is_identifier_part) {
+            return format!("\"{}\"", str);
+        }
+        previous = c;
+    }
+    if str.is_empty() { String::from("\"\"") } else { str }
 }
 
-pub fn get_type(name_and_type_index: u16, cp_info: &Vec<CpInfo>) -> String {
+pub fn get_type(name_and_type_index: u16, cp_info: &[CpInfo]) -> String {
     let constant_name_and_type = &cp_info[name_and_type_index as usize];
     if let CpInfo::ConstantNameAndType { tag: _tag, name_index: _name_index, descriptor_index } = constant_name_and_type {
-        get_constant_utf8(descriptor_index.clone(), cp_info)
+        get_constant_utf8(*descriptor_index, cp_info)
     } else {
         panic!("Expected ConstantNameAndType at idx {}", name_and_type_index)
     }
 }
 
-pub fn parse_method_arguments(method_info: &MethodInfo, cp_pool: &Vec<CpInfo>) -> Vec<String> {
-    let mut args: Vec<String> = Vec::new();
-    let descriptor = get_constant_utf8(method_info.descriptor_index, cp_pool);
-    let mut open_found = false;
-    let mut close_found = false;
-    let mut beg = 0;
-    for (i, c) in descriptor.chars().enumerate() {
-        if c == '(' { open_found = true; beg = i + 1; continue; };
-        if c == ')' { close_found = true; break; };
-        if c == ';' {
-            let arg: String = descriptor[beg..=i].to_string();
-            args.push(arg);
-            beg = i + 1;
-        }
-    }
-
-    if !open_found || !close_found {
-        panic!("Failed to parse method arguments {}", descriptor);
-    }
-
-    args
+/// Number of arguments of a method, as printed in javap's `args_size`,
+/// including `this` for instance methods.
+pub fn method_arguments_count(method_info: &MethodInfo, descriptor: &MethodDescriptor) -> usize {
+    let args_len = descriptor.params.len();
+    let flags = MethodAccessFlag::parse_flags(method_info.access_flags);
+    let static_flag = flags.contains(&MethodAccessFlag::AccStatic);
+    if static_flag { args_len } else { args_len + 1 }
 }
 
-pub fn parse_field_types(field_types: &Vec<String>) -> Vec<FieldType> {
-    field_types
-        .iter()
-        .map(|f| parse_field_type(f.to_string()))
-        .collect()
+/// Link the CONSTANT_InvokeDynamic or CONSTANT_Dynamic at `cp_index` to its
+/// entry in the BootstrapMethods attribute of the class. The error offset is
+/// 0 because the class is already parsed.
+pub fn resolve_bootstrap_method<'a>(cp_index: u16, class_file: &'a ClassFile) -> Result<BootstrapCallSite<'a>, ClassFormatError> {
+    let cp_info = &class_file.cp_info;
+    let (bootstrap_method_attr_index, name_and_type_index) = match cp_info.get(cp_index as usize) {
+        Some(CpInfo::ConstantInvokeDynamic { tag: _tag, bootstrap_method_attr_index, name_and_type_index }) |
+        Some(CpInfo::ConstantDynamic { tag: _tag, bootstrap_method_attr_index, name_and_type_index }) => (*bootstrap_method_attr_index, *name_and_type_index),
+        Some(_) => {
+            let kind = ClassFormatErrorKind::UnexpectedConstantPoolEntry { index: cp_index, expected: "CONSTANT_InvokeDynamic or CONSTANT_Dynamic" };
+            return Err(ClassFormatError::new(0, kind));
+        },
+        None => return Err(ClassFormatError::new(0, ClassFormatErrorKind::InvalidConstantPoolIndex(cp_index)))
+    };
+    let bootstrap_method = class_file.attributes.iter()
+        .find_map(|attribute| match attribute {
+            AttributeInfo::BootstrapMethods { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, num_bootstrap_methods: _num_bootstrap_methods, bootstrap_methods } => {
+                bootstrap_methods.get(bootstrap_method_attr_index as usize)
+            },
+            _ => None
+        })
+        .ok_or_else(|| ClassFormatError::new(0, ClassFormatErrorKind::InvalidBootstrapMethodIndex(bootstrap_method_attr_index)))?;
+    Ok(BootstrapCallSite {
+        bootstrap_method_attr_index,
+        name: get_name(name_and_type_index, cp_info),
+        descriptor: get_type(name_and_type_index, cp_info),
+        bootstrap_method
+    })
 }
 
-pub fn parse_field_type(field_type: String) -> FieldType {
-    let first_char = field_type.chars().next().unwrap();
-    match first_char {
-        'B' => FieldType::BaseType { term: FieldTypeTerm::B },
-        'C' => FieldType::BaseType { term: FieldTypeTerm::C },
-        'D' => FieldType::BaseType { term: FieldTypeTerm::D },
-        'F' => FieldType::BaseType { term: FieldTypeTerm::F },
-        'I' => FieldType::BaseType { term: FieldTypeTerm::I },
-        'J' => FieldType::BaseType { term: FieldTypeTerm::J },
-        'S' => FieldType::BaseType { term: FieldTypeTerm::S },
-        'Z' => FieldType::BaseType { term: FieldTypeTerm::Z },
-        'L' => {
-            let class_name: String = field_type[1..].to_string();
-            FieldType::ObjectType { class_name }
-        },
-        '[' => {
-            let field_type_minus_reference = field_type[1..].to_string();
-            let remaining_field_type = parse_field_type(field_type_minus_reference);
-            FieldType::ArrayType { field_type: Box::new(remaining_field_type) }
-        },
-        _ => panic!("Failed to parse field type {}", field_type)
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use super::*;
+
+    fn utf8(str: &str) -> CpInfo<'_> {
+        CpInfo::ConstantUtf8 { tag: 1, length: str.len() as u16, bytes: Cow::Borrowed(str.as_bytes()), bytes_str: Cow::Borrowed(str) }
     }
-}
 
-pub fn method_arguments_count(method_info: &MethodInfo, cp_pool: &Vec<CpInfo>) -> usize {
-    let args_len = parse_method_arguments(method_info, cp_pool).len();
-    let flags = MethodAccessFlag::parse_flags(method_info.access_flags);
-    let static_flag = flags.contains(&MethodAccessFlag::AccStatic);
-    if static_flag { args_len } else { args_len + 1 }
-}
+    fn class(name_index: u16) -> CpInfo<'static> {
+        CpInfo::ConstantClass { tag: 7, name_index }
+    }
+
+    fn inner_class(inner_class_info_index: u16, outer_class_info_index: u16, inner_name_index: u16) -> InnerClass {
+        InnerClass { inner_class_info_index, outer_class_info_index, inner_name_index, inner_class_access_flags: 0 }
+    }
 
-pub fn method_info_return_type(descriptor_index: u16, class_file: &ClassFile) -> String {
-    // ex.: ([Ljava/lang/String;)V
-    // ex.: ()V
-    let descriptor = get_constant_utf8(descriptor_index, &class_file.cp_info);
-    let pos = descriptor.find(')');
-    match pos {
-        None => panic!("Failed to extract return type in descriptor {}", descriptor),
-        Some(idx) => {
-            descriptor.chars().skip(idx + 1).collect()
+    /// The class `this_class` nested in p/Outer, p/A and p/B are nested in
+    /// each other. The EnclosingMethod is p.Outer.run(int, java.lang.String).
+    fn class_file(this_class: u16) -> ClassFile<'static> {
+        let cp_info = vec![
+            CpInfo::Unusable,
+            class(2),
+            utf8("p/Outer"),
+            class(4),
+            utf8("p/Outer$Inner"),
+            utf8("Inner"),
+            class(7),
+            utf8("p/Outer$1"),
+            class(9),
+            utf8("p/Outer$1Local"),
+            utf8("Local"),
+            CpInfo::ConstantNameAndType { tag: 12, name_index: 12, descriptor_index: 13 },
+            utf8("run"),
+            utf8("(ILjava/lang/String;)V"),
+            class(15),
+            utf8("p/Outer$Inner$Deep"),
+            utf8("Deep"),
+            class(18),
+            utf8("p/A"),
+            class(20),
+            utf8("p/B"),
+            utf8("A"),
+            utf8("B"),
+        ];
+        let classes = vec![
+            inner_class(3, 1, 5),
+            inner_class(6, 0, 0),
+            inner_class(8, 0, 10),
+            inner_class(14, 3, 16),
+            inner_class(17, 19, 21),
+            inner_class(19, 17, 22),
+        ];
+        let attributes = vec![
+            AttributeInfo::InnerClasses { attribute_name_index: 0, attribute_length: 0, number_of_classes: classes.len() as u16, classes },
+            AttributeInfo::EnclosingMethod { attribute_name_index: 0, attribute_length: 4, class_index: 1, method_index: 11 },
+        ];
+        ClassFile {
+            magic: 0xCAFEBABE,
+            minor_version: 0,
+            major_version: 61,
+            constant_pool_count: cp_info.len() as u16,
+            cp_info,
+            access_flags: 0x20,
+            this_class,
+            super_class: 0,
+            interfaces_count: 0,
+            interfaces: vec![],
+            fields_count: 0,
+            fields: vec![],
+            methods_count: 0,
+            methods: vec![],
+            attributes_count: attributes.len() as u16,
+            attributes
         }
     }
-}
 
-/// Reference:
-/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.3.3
-pub fn return_descriptor_to_java_code(return_type: String) -> String {
-    if return_type == "V" {
-        String::from("void")
-    } else {
-        todo!("TODO: return_type {} to java code", return_type)
+    #[test]
+    fn java_class_name_of_member_classes() {
+        let class_file = class_file(1);
+        assert_eq!(get_java_class_name("p/Outer", &class_file), "p.Outer");
+        assert_eq!(get_java_class_name("p/Outer$Inner", &class_file), "p.Outer.Inner");
+        assert_eq!(get_java_class_name("p/Outer$Inner$Deep", &class_file), "p.Outer.Inner.Deep");
+    }
+
+    #[test]
+    fn java_class_name_of_local_and_anonymous_classes() {
+        assert_eq!(get_java_class_name("p/Outer$1", &class_file(6)), "anonymous class in p.Outer.run(int, java.lang.String)");
+        assert_eq!(get_java_class_name("p/Outer$1Local", &class_file(8)), "local class Local in p.Outer.run(int, java.lang.String)");
+        // The EnclosingMethod only describes this_class, the other classes
+        // are enclosed by the class of their binary name
+        let class_file = class_file(1);
+        assert_eq!(get_java_class_name("p/Outer$1", &class_file), "anonymous class in p.Outer");
+        assert_eq!(get_java_class_name("p/Outer$1Local", &class_file), "local class Local in p.Outer");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn java_class_name_of_nesting_cycle() {
+        let class_file = class_file(1);
+        assert_eq!(get_java_class_name("p/A", &class_file), format!("p.A{}", ".B.A".repeat(MAX_NESTED_CLASS_DEPTH / 2)));
+    }
+}
@@ -0,0 +1,353 @@
+use crate::parser::ast::{ClassSignature, ClassTypeSignature, FieldTypeTerm, JavaTypeSignature, MethodSignature, ReferenceTypeSignature, ResultSignature, SimpleClassTypeSignature, ThrowsSignature, TypeArgument, TypeParameter};
+use crate::parser::error::{ClassFormatError, ClassFormatErrorKind};
+use crate::parser::ParseResult;
+
+/// Maximum number of array dimensions, as in descriptors.
+const MAX_ARRAY_DIMENSIONS: usize = 255;
+/// Maximum nesting of type arguments and type parameters, ex.: 2 in
+/// Ljava/util/List<Ljava/util/List<TT;>;>;. Each level takes a few
+/// recursive calls, the limit keeps a debug build within a 2 MiB stack.
+const MAX_TYPE_ARGUMENT_DEPTH: usize = 128;
+
+/// Parse the Signature attribute of a class.
+/// Ex.: <T:Ljava/lang/Object;>Ljava/lang/Object;Ljava/lang/Runnable;
+pub fn parse_class_signature(signature: &str) -> Result<ClassSignature, ClassFormatError> {
+    check_type_argument_depth(signature)?;
+    let (idx, type_parameters) = parse_type_parameters(0, signature)?;
+    let (mut idx, superclass) = parse_class_type_signature(idx, signature)?;
+    let mut interfaces: Vec<ClassTypeSignature> = Vec::new();
+    while idx < signature.len() {
+        let (i, interface) = parse_class_type_signature(idx, signature)?;
+        idx = i;
+        interfaces.push(interface);
+    }
+    Ok(ClassSignature { type_parameters, superclass, interfaces })
+}
+
+/// Parse the Signature attribute of a method.
+/// Ex.: <X::Ljava/lang/Comparable<-TX;>;>(Ljava/util/List<TX;>;)V
+pub fn parse_method_signature(signature: &str) -> Result<MethodSignature, ClassFormatError> {
+    check_type_argument_depth(signature)?;
+    let (idx, type_parameters) = parse_type_parameters(0, signature)?;
+    let mut idx = expect(idx, b'(', signature)?;
+    let mut params: Vec<JavaTypeSignature> = Vec::new();
+    while peek(idx, signature) != Some(b')') {
+        let (i, param) = parse_java_type_signature(idx, signature)?;
+        idx = i;
+        params.push(param);
+    }
+    let (mut idx, result) = parse_result_signature(idx + 1, signature)?;
+    let mut throws: Vec<ThrowsSignature> = Vec::new();
+    while idx < signature.len() {
+        let (i, throws_signature) = parse_throws_signature(idx, signature)?;
+        idx = i;
+        throws.push(throws_signature);
+    }
+    Ok(MethodSignature { type_parameters, params, result, throws })
+}
+
+/// Parse the Signature attribute of a field, record component or local
+/// variable. Ex.: Ljava/util/List<Ljava/lang/String;>;
+pub fn parse_field_signature(signature: &str) -> Result<ReferenceTypeSignature, ClassFormatError> {
+    check_type_argument_depth(signature)?;
+    let (idx, reference_type) = parse_reference_type_signature(0, signature)?;
+    if idx != signature.len() {
+        return Err(invalid_signature(idx, signature));
+    }
+    Ok(reference_type)
+}
+
+/// Optional type parameters enclosed in `<` and `>`.
+fn parse_type_parameters(idx: usize, signature: &str) -> ParseResult<Vec<TypeParameter>> {
+    let mut type_parameters: Vec<TypeParameter> = Vec::new();
+    if peek(idx, signature) != Some(b'<') {
+        return Ok((idx, type_parameters));
+    }
+    let mut idx = idx + 1;
+    loop {
+        let (i, type_parameter) = parse_type_parameter(idx, signature)?;
+        idx = i;
+        type_parameters.push(type_parameter);
+        if peek(idx, signature) == Some(b'>') {
+            return Ok((idx + 1, type_parameters));
+        }
+    }
+}
+
+fn parse_type_parameter(idx: usize, signature: &str) -> ParseResult<TypeParameter> {
+    let (idx, identifier) = parse_identifier(idx, signature)?;
+    let idx = expect(idx, b':', signature)?;
+    let (mut idx, class_bound) = match peek(idx, signature) {
+        Some(b'L') | Some(b'T') | Some(b'[') => {
+            let (idx, class_bound) = parse_reference_type_signature(idx, signature)?;
+            (idx, Some(class_bound))
+        },
+        _ => (idx, None)
+    };
+    let mut interface_bounds: Vec<ReferenceTypeSignature> = Vec::new();
+    while peek(idx, signature) == Some(b':') {
+        let (i, interface_bound) = parse_reference_type_signature(idx + 1, signature)?;
+        idx = i;
+        interface_bounds.push(interface_bound);
+    }
+    Ok((idx, TypeParameter { identifier, class_bound, interface_bounds }))
+}
+
+fn parse_java_type_signature(idx: usize, signature: &str) -> ParseResult<JavaTypeSignature> {
+    let term = match peek(idx, signature) {
+        Some(b'B') => FieldTypeTerm::B,
+        Some(b'C') => FieldTypeTerm::C,
+        Some(b'D') => FieldTypeTerm::D,
+        Some(b'F') => FieldTypeTerm::F,
+        Some(b'I') => FieldTypeTerm::I,
+        Some(b'J') => FieldTypeTerm::J,
+        Some(b'S') => FieldTypeTerm::S,
+        Some(b'Z') => FieldTypeTerm::Z,
+        _ => {
+            let (idx, reference_type) = parse_reference_type_signature(idx, signature)?;
+            return Ok((idx, JavaTypeSignature::ReferenceType { reference_type }));
+        }
+    };
+    Ok((idx + 1, JavaTypeSignature::BaseType { term }))
+}
+
+fn parse_reference_type_signature(idx: usize, signature: &str) -> ParseResult<ReferenceTypeSignature> {
+    match peek(idx, signature) {
+        Some(b'L') => {
+            let (idx, class_type) = parse_class_type_signature(idx, signature)?;
+            Ok((idx, ReferenceTypeSignature::ClassType { class_type }))
+        },
+        Some(b'T') => {
+            let (idx, identifier) = parse_type_variable_signature(idx, signature)?;
+            Ok((idx, ReferenceTypeSignature::TypeVariable { identifier }))
+        },
+        Some(b'[') => parse_array_type_signature(idx, signature),
+        _ => Err(invalid_signature(idx, signature))
+    }
+}
+
+/// The dimensions are counted rather than parsed recursively, like in
+/// descriptors.
+fn parse_array_type_signature(idx: usize, signature: &str) -> ParseResult<ReferenceTypeSignature> {
+    let mut dimensions = 0;
+    while peek(idx + dimensions, signature) == Some(b'[') {
+        dimensions += 1;
+    }
+    if dimensions > MAX_ARRAY_DIMENSIONS {
+        return Err(invalid_signature(idx, signature));
+    }
+    let (idx, mut java_type) = parse_java_type_signature(idx + dimensions, signature)?;
+    for _ in 1..dimensions {
+        let reference_type = ReferenceTypeSignature::ArrayType { java_type: Box::new(java_type) };
+        java_type = JavaTypeSignature::ReferenceType { reference_type };
+    }
+    Ok((idx, ReferenceTypeSignature::ArrayType { java_type: Box::new(java_type) }))
+}
+
+/// Ex.: Ljava/util/List<TT;>;
+fn parse_class_type_signature(idx: usize, signature: &str) -> ParseResult<ClassTypeSignature> {
+    let idx = expect(idx, b'L', signature)?;
+    let mut package_specifier = String::new();
+    let (mut idx, mut identifier) = parse_identifier(idx, signature)?;
+    while peek(idx, signature) == Some(b'/') {
+        package_specifier.push_str(&identifier);
+        package_specifier.push('/');
+        let (i, next_identifier) = parse_identifier(idx + 1, signature)?;
+        idx = i;
+        identifier = next_identifier;
+    }
+    let (mut idx, simple_class_type) = parse_simple_class_type_signature(idx, identifier, signature)?;
+    let mut suffixes: Vec<SimpleClassTypeSignature> = Vec::new();
+    while peek(idx, signature) == Some(b'.') {
+        let (i, identifier) = parse_identifier(idx + 1, signature)?;
+        let (i, suffix) = parse_simple_class_type_signature(i, identifier, signature)?;
+        idx = i;
+        suffixes.push(suffix);
+    }
+    let idx = expect(idx, b';', signature)?;
+    Ok((idx, ClassTypeSignature { package_specifier, simple_class_type, suffixes }))
+}
+
+/// Optional type arguments of a class whose identifier was already parsed.
+fn parse_simple_class_type_signature(idx: usize, identifier: String, signature: &str) -> ParseResult<SimpleClassTypeSignature> {
+    let mut type_arguments: Vec<TypeArgument> = Vec::new();
+    if peek(idx, signature) != Some(b'<') {
+        return Ok((idx, SimpleClassTypeSignature { identifier, type_arguments }));
+    }
+    let mut idx = idx + 1;
+    loop {
+        let (i, type_argument) = parse_type_argument(idx, signature)?;
+        idx = i;
+        type_arguments.push(type_argument);
+        if peek(idx, signature) == Some(b'>') {
+            return Ok((idx + 1, SimpleClassTypeSignature { identifier, type_arguments }));
+        }
+    }
+}
+
+fn parse_type_argument(idx: usize, signature: &str) -> ParseResult<TypeArgument> {
+    match peek(idx, signature) {
+        Some(b'*') => Ok((idx + 1, TypeArgument::Wildcard)),
+        Some(b'+') => {
+            let (idx, reference_type) = parse_reference_type_signature(idx + 1, signature)?;
+            Ok((idx, TypeArgument::Extends { reference_type }))
+        },
+        Some(b'-') => {
+            let (idx, reference_type) = parse_reference_type_signature(idx + 1, signature)?;
+            Ok((idx, TypeArgument::Super { reference_type }))
+        },
+        _ => {
+            let (idx, reference_type) = parse_reference_type_signature(idx, signature)?;
+            Ok((idx, TypeArgument::Exact { reference_type }))
+        }
+    }
+}
+
+/// Ex.: TT;
+fn parse_type_variable_signature(idx: usize, signature: &str) -> ParseResult<String> {
+    let idx = expect(idx, b'T', signature)?;
+    let (idx, identifier) = parse_identifier(idx, signature)?;
+    let idx = expect(idx, b';', signature)?;
+    Ok((idx, identifier))
+}
+
+fn parse_result_signature(idx: usize, signature: &str) -> ParseResult<ResultSignature> {
+    if peek(idx, signature) == Some(b'V') {
+        Ok((idx + 1, ResultSignature::Void))
+    } else {
+        let (idx, java_type) = parse_java_type_signature(idx, signature)?;
+        Ok((idx, ResultSignature::JavaType { java_type }))
+    }
+}
+
+fn parse_throws_signature(idx: usize, signature: &str) -> ParseResult<ThrowsSignature> {
+    let idx = expect(idx, b'^', signature)?;
+    match peek(idx, signature) {
+        Some(b'T') => {
+            let (idx, identifier) = parse_type_variable_signature(idx, signature)?;
+            Ok((idx, ThrowsSignature::TypeVariable { identifier }))
+        },
+        _ => {
+            let (idx, class_type) = parse_class_type_signature(idx, signature)?;
+            Ok((idx, ThrowsSignature::ClassType { class_type }))
+        }
+    }
+}
+
+/// An identifier runs until one of the characters reserved by the grammar.
+fn parse_identifier(idx: usize, signature: &str) -> ParseResult<String> {
+    let len = signature[idx..]
+        .find(['.', ';', '[', '/', '<', '>', ':'])
+        .unwrap_or(signature.len() - idx);
+    if len == 0 {
+        return Err(invalid_signature(idx, signature));
+    }
+    Ok((idx + len, String::from(&signature[idx..idx + len])))
+}
+
+/// Type arguments are parsed recursively, the nesting of `<` is checked
+/// up front so that a malformed signature cannot overflow the stack.
+fn check_type_argument_depth(signature: &str) -> Result<(), ClassFormatError> {
+    let mut depth: usize = 0;
+    for (idx, b) in signature.bytes().enumerate() {
+        match b {
+            b'<' => {
+                depth += 1;
+                if depth > MAX_TYPE_ARGUMENT_DEPTH {
+                    return Err(ClassFormatError::new(idx, ClassFormatErrorKind::NestingTooDeep(MAX_TYPE_ARGUMENT_DEPTH)));
+                }
+            },
+            b'>' => depth = depth.saturating_sub(1),
+            _ => ()
+        }
+    }
+    Ok(())
+}
+
+fn peek(idx: usize, signature: &str) -> Option<u8> {
+    signature.as_bytes().get(idx).copied()
+}
+
+fn expect(idx: usize, expected: u8, signature: &str) -> Result<usize, ClassFormatError> {
+    if peek(idx, signature) == Some(expected) {
+        Ok(idx + 1)
+    } else {
+        Err(invalid_signature(idx, signature))
+    }
+}
+
+fn invalid_signature(idx: usize, signature: &str) -> ClassFormatError {
+    ClassFormatError::new(idx, ClassFormatErrorKind::InvalidSignature(String::from(signature)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Field signature of `depth` nested lists. Ex.: 2 -> Ljava/util/List<Ljava/util/List<TT;>;>;
+    fn nested_lists(depth: usize) -> String {
+        format!("{}TT;{}", "Ljava/util/List<".repeat(depth), ">;".repeat(depth))
+    }
+
+    #[test]
+    fn field_signature() {
+        let signature = parse_field_signature("Ljava/util/Map<TK;+Ljava/util/List<*>;>.Entry<-[I>;").unwrap();
+        assert_eq!(signature.str_java(), "java.util.Map<K, ? extends java.util.List<?>>.Entry<? super int[]>");
+    }
+
+    #[test]
+    fn class_signature() {
+        let signature = parse_class_signature("<T:Ljava/lang/Object;:Ljava/lang/Comparable<TT;>;>Ljava/lang/Object;Ljava/lang/Runnable;").unwrap();
+        assert_eq!(signature.type_parameters.len(), 1);
+        assert_eq!(signature.type_parameters[0].interface_bounds.len(), 1);
+        assert_eq!(signature.superclass.str_java(), "java.lang.Object");
+        assert_eq!(signature.interfaces.len(), 1);
+    }
+
+    #[test]
+    fn method_signature() {
+        let signature = parse_method_signature("<X:Ljava/lang/Exception;>(Ljava/util/List<TX;>;I)V^TX;^Ljava/io/IOException;").unwrap();
+        assert_eq!(signature.params.len(), 2);
+        assert!(matches!(signature.result, ResultSignature::Void));
+        assert_eq!(signature.throws.len(), 2);
+    }
+
+    #[test]
+    fn invalid_signatures() {
+        for signature in ["", "Ljava/util/List", "Ljava/util/List<>;", "TT", "I", "Ljava/lang/Object;;"] {
+            assert!(parse_field_signature(signature).is_err(), "{} should be invalid", signature);
+        }
+        assert!(parse_method_signature("(I").is_err());
+        assert!(parse_method_signature("()V^I").is_err());
+    }
+
+    #[test]
+    fn array_dimensions_limit() {
+        let signature = format!("{}Ljava/lang/String;", "[".repeat(MAX_ARRAY_DIMENSIONS));
+        let array_type = parse_field_signature(&signature).unwrap();
+        assert_eq!(array_type.str_java(), format!("java.lang.String{}", "[]".repeat(MAX_ARRAY_DIMENSIONS)));
+        let signature = format!("{}Ljava/lang/String;", "[".repeat(MAX_ARRAY_DIMENSIONS + 1));
+        let err = parse_field_signature(&signature).unwrap_err();
+        assert_eq!(err.kind, ClassFormatErrorKind::InvalidSignature(signature));
+    }
+
+    #[test]
+    fn type_argument_depth_limit() {
+        let signature = parse_field_signature(&nested_lists(MAX_TYPE_ARGUMENT_DEPTH)).unwrap();
+        assert!(signature.str_java().ends_with(&format!("<T{}", ">".repeat(MAX_TYPE_ARGUMENT_DEPTH))));
+        let err = parse_field_signature(&nested_lists(MAX_TYPE_ARGUMENT_DEPTH + 1)).unwrap_err();
+        // The `<` that exceeds the limit
+        assert_eq!(err.offset, "Ljava/util/List<".len() * (MAX_TYPE_ARGUMENT_DEPTH + 1) - 1);
+        assert_eq!(err.kind, ClassFormatErrorKind::NestingTooDeep(MAX_TYPE_ARGUMENT_DEPTH));
+    }
+
+    #[test]
+    fn deep_nesting_does_not_overflow() {
+        // Unbalanced `<` in a signature far larger than the limit
+        let signature = "Ljava/util/List<".repeat(100_000);
+        let err = parse_class_signature(&signature).unwrap_err();
+        assert_eq!(err.kind, ClassFormatErrorKind::NestingTooDeep(MAX_TYPE_ARGUMENT_DEPTH));
+        let signature = format!("{}I", "[".repeat(100_000));
+        assert!(parse_field_signature(&signature).is_err());
+    }
+}
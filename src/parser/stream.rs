@@ -0,0 +1,672 @@
+use std::io;
+use std::io::{BufReader, Read};
+use crate::parser::ast::{AttributeInfo, ClassFile, CpInfo, ExceptionTable, FieldInfo, MethodInfo};
+use crate::parser::attribute_decoder::AttributeDecoders;
+use crate::parser::error::{ClassFormatError, ClassFormatErrorKind};
+use crate::parser::opcodes::{decode_instruction, Instruction};
+use crate::parser::{get_u1, get_u2, get_u4, parse_attribute_info, parse_cp_info, parse_exception_table, parse_interfaces, two_slots_in_last_entry, ParseOptions, ParseResult};
+
+/// Capacity of the buffer between the reader and the parser.
+const READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Parse a class file from `reader`, ex.: a zip entry or a socket, without
+/// reading the whole file in memory first.
+pub fn parse_class_file_from_reader<R: Read>(reader: R) -> Result<ClassFile<'static>, ClassFormatError> {
+    parse_class_file_from_reader_with_options(reader, &AttributeDecoders::new(), &ParseOptions::default())
+}
+
+/// Parse a class file from `reader` one structure at a time: the input is
+/// read through a buffer of `READ_BUFFER_SIZE` bytes and the bytes of a
+/// structure are dropped once it is parsed, the largest one being usually
+/// the Code of a method.
+pub fn parse_class_file_from_reader_with_options<R: Read>(reader: R, decoders: &AttributeDecoders, options: &ParseOptions) -> Result<ClassFile<'static>, ClassFormatError> {
+    let mut input = StructureReader::new(reader);
+    let magic = input.read_u4().map_err(|e| e.within("magic"))?;
+    let minor_version = input.read_u2().map_err(|e| e.within("minor_version"))?;
+    let major_version = input.read_u2().map_err(|e| e.within("major_version"))?;
+    let constant_pool_count = input.read_u2().map_err(|e| e.within("constant_pool_count"))?;
+    let mut cp_info: Vec<CpInfo> = Vec::with_capacity(constant_pool_count as usize);
+    cp_info.push(CpInfo::Unusable);
+    while cp_info.len() < constant_pool_count as usize {
+        let index = cp_info.len();
+        let cp_item = read_cp_info(&mut input).map_err(|e| e.within(format!("constant_pool[{}]", index)))?;
+        push_cp_info(&mut cp_info, cp_item, constant_pool_count, input.offset)?;
+    }
+    let access_flags = input.read_u2().map_err(|e| e.within("access_flags"))?;
+    let this_class = input.read_u2().map_err(|e| e.within("this_class"))?;
+    let super_class = input.read_u2().map_err(|e| e.within("super_class"))?;
+    let interfaces_count = input.read_u2().map_err(|e| e.within("interfaces_count"))?;
+    let interfaces = read_interfaces(&mut input, interfaces_count)?;
+    let fields_count = input.read_u2().map_err(|e| e.within("fields_count"))?;
+    let mut fields: Vec<FieldInfo> = Vec::with_capacity(fields_count as usize);
+    for n in 0..fields_count {
+        let (access_flags, name_index, descriptor_index, attributes_count) = read_member(&mut input)
+            .map_err(|e| e.within(format!("fields[{}]", n)))?;
+        let attributes = read_attributes(&mut input, attributes_count, &cp_info, decoders, options)
+            .map_err(|e| e.within(format!("fields[{}]", n)))?;
+        fields.push(FieldInfo { access_flags, name_index, descriptor_index, attributes_count, attributes });
+    }
+    let methods_count = input.read_u2().map_err(|e| e.within("methods_count"))?;
+    let mut methods: Vec<MethodInfo> = Vec::with_capacity(methods_count as usize);
+    for n in 0..methods_count {
+        let (access_flags, name_index, descriptor_index, attributes_count) = read_member(&mut input)
+            .map_err(|e| e.within(format!("methods[{}]", n)))?;
+        let attributes = read_attributes(&mut input, attributes_count, &cp_info, decoders, options)
+            .map_err(|e| e.within(format!("methods[{}]", n)))?;
+        methods.push(MethodInfo { access_flags, name_index, descriptor_index, attributes_count, attributes });
+    }
+    let attributes_count = input.read_u2().map_err(|e| e.within("attributes_count"))?;
+    let attributes = read_attributes(&mut input, attributes_count, &cp_info, decoders, options)?;
+
+    Ok(ClassFile {
+        magic,
+        minor_version,
+        major_version,
+        constant_pool_count,
+        cp_info,
+        access_flags,
+        this_class,
+        super_class,
+        interfaces_count,
+        interfaces,
+        fields_count,
+        fields,
+        methods_count,
+        methods,
+        attributes_count,
+        attributes
+    })
+}
+
+/// Event of a `ClassEventReader`, in the order of the class file.
+#[derive(Debug)]
+pub enum ClassEvent {
+    Header { magic: u32, minor_version: u16, major_version: u16, constant_pool_count: u16 },
+    /// Entry at `index` of the constant pool. The unusable slots following
+    /// CONSTANT_Long and CONSTANT_Double have no event.
+    ConstantPoolEntry { index: u16, cp_info: CpInfo<'static> },
+    /// Class declaration, following the constant pool.
+    Class { access_flags: u16, this_class: u16, super_class: u16, interfaces: Vec<u16> },
+    /// Start of a field, followed by its attributes and FieldEnd.
+    FieldStart { access_flags: u16, name_index: u16, descriptor_index: u16, attributes_count: u16 },
+    FieldEnd,
+    /// Start of a method, followed by its attributes and MethodEnd.
+    MethodStart { access_flags: u16, name_index: u16, descriptor_index: u16, attributes_count: u16 },
+    MethodEnd,
+    /// Start of the Code of the current method, followed by its
+    /// instructions, its exception table, its attributes and CodeEnd.
+    CodeStart { attribute_name_index: u16, attribute_length: u32, max_stack: u16, max_locals: u16, code_length: u32 },
+    Instruction { pc: u32, instruction: Instruction },
+    ExceptionTable { exception_table: Vec<ExceptionTable> },
+    CodeEnd,
+    /// Attribute of the class, of the current field or method or of the
+    /// current Code.
+    Attribute { attribute_info: AttributeInfo<'static> },
+    /// Non-standard attribute without a registered decoder.
+    AttributeRaw { attribute_name_index: u16, info: Vec<u8> },
+    End,
+}
+
+/// Pull parser of a class file read from `reader`. Unlike
+/// `parse_class_file_from_reader` it does not build the class file: only
+/// the constant pool is kept, to resolve the attribute names, and the Code
+/// of a method is decoded one instruction at a time.
+/// Ex.:
+/// for event in ClassEventReader::new(reader, &decoders, &options) {
+///     if let ClassEvent::Instruction { pc, instruction } = event? { ... }
+/// }
+pub struct ClassEventReader<'d, R: Read> {
+    input: StructureReader<R>,
+    decoders: &'d AttributeDecoders,
+    options: ParseOptions,
+    cp_info: Vec<CpInfo<'static>>,
+    state: State,
+}
+
+/// Next structure of the class file to read.
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Header,
+    ConstantPool { count: u16 },
+    Class,
+    Members { owner: Owner },
+    Attributes { owner: Owner, index: u16, count: u16 },
+    /// Instructions of a Code, the code array is in the input buffer.
+    Instructions { owner: Owner, pc: usize },
+    Done,
+}
+
+/// Structure that owns the attributes being read. A Code records the
+/// position of its attribute in the attributes of its method and the offset
+/// where it ends.
+#[derive(Debug, Clone, Copy)]
+enum Owner {
+    Class,
+    Field { index: u16, count: u16 },
+    Method { index: u16, count: u16 },
+    Code { method_index: u16, methods_count: u16, index: u16, count: u16, end: usize },
+}
+
+impl Owner {
+    fn within(&self, e: ClassFormatError) -> ClassFormatError {
+        match self {
+            Owner::Class => e,
+            Owner::Field { index, count: _count } => e.within(format!("fields[{}]", index)),
+            Owner::Method { index, count: _count } => e.within(format!("methods[{}]", index)),
+            Owner::Code { method_index, methods_count: _methods_count, index, count: _count, end: _end } => {
+                e.within("Code").within(format!("attributes[{}]", index)).within(format!("methods[{}]", method_index))
+            }
+        }
+    }
+}
+
+impl<'d, R: Read> ClassEventReader<'d, R> {
+    pub fn new(reader: R, decoders: &'d AttributeDecoders, options: &ParseOptions) -> ClassEventReader<'d, R> {
+        ClassEventReader {
+            input: StructureReader::new(reader),
+            decoders,
+            options: options.clone(),
+            cp_info: Vec::new(),
+            state: State::Header,
+        }
+    }
+
+    /// Constant pool read so far.
+    pub fn cp_info(&self) -> &[CpInfo<'static>] {
+        &self.cp_info
+    }
+
+    /// Read the next event, None after End.
+    pub fn next_event(&mut self) -> Result<Option<ClassEvent>, ClassFormatError> {
+        loop {
+            let event = match self.state {
+                State::Header => self.read_header()?,
+                State::ConstantPool { count } => self.read_constant_pool_entry(count)?,
+                State::Class => self.read_class()?,
+                State::Members { owner } => self.read_member(owner)?,
+                State::Attributes { owner, index, count } => self.read_attribute(owner, index, count)?,
+                State::Instructions { owner, pc } => self.read_instruction(owner, pc)?,
+                State::Done => return Ok(None)
+            };
+            if event.is_some() {
+                return Ok(event);
+            }
+        }
+    }
+
+    fn read_header(&mut self) -> Result<Option<ClassEvent>, ClassFormatError> {
+        let magic = self.input.read_u4().map_err(|e| e.within("magic"))?;
+        let minor_version = self.input.read_u2().map_err(|e| e.within("minor_version"))?;
+        let major_version = self.input.read_u2().map_err(|e| e.within("major_version"))?;
+        let constant_pool_count = self.input.read_u2().map_err(|e| e.within("constant_pool_count"))?;
+        self.cp_info = Vec::with_capacity(constant_pool_count as usize);
+        self.cp_info.push(CpInfo::Unusable);
+        self.state = State::ConstantPool { count: constant_pool_count };
+        Ok(Some(ClassEvent::Header { magic, minor_version, major_version, constant_pool_count }))
+    }
+
+    fn read_constant_pool_entry(&mut self, count: u16) -> Result<Option<ClassEvent>, ClassFormatError> {
+        let index = self.cp_info.len();
+        if index >= count as usize {
+            self.state = State::Class;
+            return Ok(None);
+        }
+        let cp_item = read_cp_info(&mut self.input).map_err(|e| e.within(format!("constant_pool[{}]", index)))?;
+        let event = ClassEvent::ConstantPoolEntry { index: index as u16, cp_info: cp_item.clone() };
+        push_cp_info(&mut self.cp_info, cp_item, count, self.input.offset)?;
+        Ok(Some(event))
+    }
+
+    fn read_class(&mut self) -> Result<Option<ClassEvent>, ClassFormatError> {
+        let access_flags = self.input.read_u2().map_err(|e| e.within("access_flags"))?;
+        let this_class = self.input.read_u2().map_err(|e| e.within("this_class"))?;
+        let super_class = self.input.read_u2().map_err(|e| e.within("super_class"))?;
+        let interfaces_count = self.input.read_u2().map_err(|e| e.within("interfaces_count"))?;
+        let interfaces = read_interfaces(&mut self.input, interfaces_count)?;
+        let fields_count = self.input.read_u2().map_err(|e| e.within("fields_count"))?;
+        self.state = State::Members { owner: Owner::Field { index: 0, count: fields_count } };
+        Ok(Some(ClassEvent::Class { access_flags, this_class, super_class, interfaces }))
+    }
+
+    /// Start the member at the index of `owner`, or the next table when all
+    /// the members were read.
+    fn read_member(&mut self, owner: Owner) -> Result<Option<ClassEvent>, ClassFormatError> {
+        match owner {
+            Owner::Field { index, count } if index >= count => {
+                let methods_count = self.input.read_u2().map_err(|e| e.within("methods_count"))?;
+                self.state = State::Members { owner: Owner::Method { index: 0, count: methods_count } };
+                return Ok(None);
+            },
+            Owner::Method { index, count } if index >= count => {
+                let attributes_count = self.input.read_u2().map_err(|e| e.within("attributes_count"))?;
+                self.state = State::Attributes { owner: Owner::Class, index: 0, count: attributes_count };
+                return Ok(None);
+            },
+            _ => {}
+        }
+        let (access_flags, name_index, descriptor_index, attributes_count) = read_member(&mut self.input).map_err(|e| owner.within(e))?;
+        self.state = State::Attributes { owner, index: 0, count: attributes_count };
+        let event = match owner {
+            Owner::Field { .. } => ClassEvent::FieldStart { access_flags, name_index, descriptor_index, attributes_count },
+            _ => ClassEvent::MethodStart { access_flags, name_index, descriptor_index, attributes_count }
+        };
+        Ok(Some(event))
+    }
+
+    fn read_attribute(&mut self, owner: Owner, index: u16, count: u16) -> Result<Option<ClassEvent>, ClassFormatError> {
+        if index >= count {
+            return self.end_of_attributes(owner).map(Some);
+        }
+        self.state = State::Attributes { owner, index: index + 1, count };
+        let within = |e: ClassFormatError| owner.within(e.within(format!("attributes[{}]", index)));
+        let (attribute_name_index, attribute_length) = self.input.read_attribute_header().map_err(within)?;
+        let attribute_name = attribute_name(attribute_name_index, &self.cp_info);
+        if let (Some("Code"), Owner::Method { index: method_index, count: methods_count }) = (attribute_name, owner) {
+            if !self.options.skip_code {
+                let end = self.input.position() + attribute_length as usize;
+                let code_owner = Owner::Code { method_index, methods_count, index, count, end };
+                return self.read_code_start(code_owner, attribute_name_index, attribute_length).map(Some);
+            }
+        }
+        let attribute_info = read_attribute_body(&mut self.input, attribute_name_index, attribute_length, &self.cp_info, self.decoders, &self.options)
+            .map_err(within)?;
+        let event = match attribute_info {
+            None => None,
+            Some(AttributeInfo::Unknown { attribute_name_index, attribute_length: _attribute_length, info }) => {
+                Some(ClassEvent::AttributeRaw { attribute_name_index, info: info.into_owned() })
+            },
+            Some(attribute_info) => Some(ClassEvent::Attribute { attribute_info })
+        };
+        Ok(event)
+    }
+
+    fn end_of_attributes(&mut self, owner: Owner) -> Result<ClassEvent, ClassFormatError> {
+        let event = match owner {
+            Owner::Class => {
+                self.state = State::Done;
+                ClassEvent::End
+            },
+            Owner::Field { index, count } => {
+                self.state = State::Members { owner: Owner::Field { index: index + 1, count } };
+                ClassEvent::FieldEnd
+            },
+            Owner::Method { index, count } => {
+                self.state = State::Members { owner: Owner::Method { index: index + 1, count } };
+                ClassEvent::MethodEnd
+            },
+            Owner::Code { method_index, methods_count, index, count, end } => {
+                // Continue after the attribute_length of the Code, like the
+                // attributes read by read_attribute_body
+                let position = self.input.position();
+                if position > end {
+                    let kind = ClassFormatErrorKind::UnexpectedEof { needed: position - end, available: 0 };
+                    return Err(owner.within(ClassFormatError::new(end, kind)));
+                }
+                self.input.skip(end - position).map_err(|e| owner.within(e))?;
+                let method = Owner::Method { index: method_index, count: methods_count };
+                self.state = State::Attributes { owner: method, index: index + 1, count };
+                ClassEvent::CodeEnd
+            }
+        };
+        Ok(event)
+    }
+
+    /// Read max_stack, max_locals and the code array of a Code.
+    fn read_code_start(&mut self, owner: Owner, attribute_name_index: u16, attribute_length: u32) -> Result<ClassEvent, ClassFormatError> {
+        let max_stack = self.input.read_u2().map_err(|e| owner.within(e))?;
+        let max_locals = self.input.read_u2().map_err(|e| owner.within(e))?;
+        let code_length = self.input.read_u4().map_err(|e| owner.within(e))?;
+        self.input.begin();
+        self.input.take(code_length as usize).map_err(|e| owner.within(e))?;
+        self.input.parse(|code| Ok((code.len(), ()))).map_err(|e| owner.within(e))?;
+        self.state = State::Instructions { owner, pc: 0 };
+        Ok(ClassEvent::CodeStart { attribute_name_index, attribute_length, max_stack, max_locals, code_length })
+    }
+
+    /// Decode the instruction at `pc`, or read the exception table and the
+    /// attributes count after the last one.
+    fn read_instruction(&mut self, owner: Owner, pc: usize) -> Result<Option<ClassEvent>, ClassFormatError> {
+        if pc >= self.input.buffer.len() {
+            let exception_table_length = self.input.read_u2().map_err(|e| owner.within(e))?;
+            let exception_table = read_exception_table(&mut self.input, exception_table_length).map_err(|e| owner.within(e))?;
+            let attributes_count = self.input.read_u2().map_err(|e| owner.within(e))?;
+            self.state = State::Attributes { owner, index: 0, count: attributes_count };
+            return Ok(Some(ClassEvent::ExceptionTable { exception_table }));
+        }
+        let (next_pc, instruction) = self.input.parse(|code| {
+            let (next_pc, instruction) = decode_instruction(pc, code)?;
+            Ok((next_pc, (next_pc, instruction)))
+        }).map_err(|e| owner.within(e))?;
+        self.state = State::Instructions { owner, pc: next_pc };
+        Ok(Some(ClassEvent::Instruction { pc: pc as u32, instruction }))
+    }
+}
+
+impl<R: Read> Iterator for ClassEventReader<'_, R> {
+    type Item = Result<ClassEvent, ClassFormatError>;
+
+    /// The iteration ends after End or after the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.next_event();
+        if event.is_err() {
+            self.state = State::Done;
+        }
+        event.transpose()
+    }
+}
+
+/// Reads the class file one structure at a time, the bytes of the structure
+/// being parsed are the only ones kept.
+struct StructureReader<R: Read> {
+    reader: BufReader<R>,
+    /// Bytes of the current structure
+    buffer: Vec<u8>,
+    /// Offset of the current structure in the class file
+    offset: usize,
+    /// Bytes requested for the current structure, more than the length of
+    /// `buffer` when the input ended
+    requested: usize,
+}
+
+impl<R: Read> StructureReader<R> {
+    fn new(reader: R) -> StructureReader<R> {
+        StructureReader {
+            reader: BufReader::with_capacity(READ_BUFFER_SIZE, reader),
+            buffer: Vec::new(),
+            offset: 0,
+            requested: 0,
+        }
+    }
+
+    /// Offset in the class file of the byte after the current structure.
+    fn position(&self) -> usize {
+        self.offset + self.buffer.len()
+    }
+
+    /// Start a structure after the current one.
+    fn begin(&mut self) {
+        self.offset += self.buffer.len();
+        self.buffer.clear();
+        self.requested = 0;
+    }
+
+    /// Append the next `len` bytes of the input to the current structure,
+    /// less when the input ends.
+    fn take(&mut self, len: usize) -> Result<(), ClassFormatError> {
+        if self.requested > self.buffer.len() {
+            return Ok(());
+        }
+        self.requested += len;
+        (&mut self.reader).take(len as u64).read_to_end(&mut self.buffer)
+            .map_err(|e| ClassFormatError::new(self.offset + self.buffer.len(), ClassFormatErrorKind::Io(e.to_string())))?;
+        Ok(())
+    }
+
+    /// Discard the next `len` bytes of the input.
+    fn skip(&mut self, len: usize) -> Result<(), ClassFormatError> {
+        self.begin();
+        let skipped = io::copy(&mut (&mut self.reader).take(len as u64), &mut io::sink())
+            .map_err(|e| ClassFormatError::new(self.offset, ClassFormatErrorKind::Io(e.to_string())))? as usize;
+        if skipped < len {
+            return Err(ClassFormatError::new(self.offset, ClassFormatErrorKind::UnexpectedEof { needed: len, available: skipped }));
+        }
+        self.offset += skipped;
+        Ok(())
+    }
+
+    /// Parse the current structure, the offsets of the errors are rebased on
+    /// the class file. When the input ended, the parser is expected to fail
+    /// where the bytes are missing.
+    fn parse<'b, T, F>(&'b self, parser: F) -> Result<T, ClassFormatError>
+        where F: FnOnce(&'b [u8]) -> ParseResult<T> {
+        match parser(&self.buffer) {
+            Ok((_, value)) if self.requested <= self.buffer.len() => Ok(value),
+            Ok(_) => {
+                let kind = ClassFormatErrorKind::UnexpectedEof { needed: self.requested, available: self.buffer.len() };
+                Err(ClassFormatError::new(self.offset, kind))
+            },
+            Err(mut e) => {
+                e.offset += self.offset;
+                Err(e)
+            }
+        }
+    }
+
+    fn read_u2(&mut self) -> Result<u16, ClassFormatError> {
+        self.begin();
+        self.take(2)?;
+        self.parse(|bytes| get_u2(0, bytes))
+    }
+
+    fn read_u4(&mut self) -> Result<u32, ClassFormatError> {
+        self.begin();
+        self.take(4)?;
+        self.parse(|bytes| get_u4(0, bytes))
+    }
+
+    /// attribute_name_index and attribute_length, the attribute is the
+    /// current structure.
+    fn read_attribute_header(&mut self) -> Result<(u16, u32), ClassFormatError> {
+        self.begin();
+        self.take(6)?;
+        self.parse(|bytes| {
+            let (idx, attribute_name_index) = get_u2(0, bytes)?;
+            let (idx, attribute_length) = get_u4(idx, bytes)?;
+            Ok((idx, (attribute_name_index, attribute_length)))
+        })
+    }
+}
+
+fn read_cp_info<R: Read>(input: &mut StructureReader<R>) -> Result<CpInfo<'static>, ClassFormatError> {
+    input.begin();
+    input.take(1)?;
+    let tag = input.parse(|bytes| get_u1(0, bytes))?;
+    match tag {
+        // CONSTANT_Utf8: length and bytes
+        1 => {
+            input.take(2)?;
+            let length = input.parse(|bytes| get_u2(1, bytes))?;
+            input.take(length as usize)?;
+        },
+        7 | 8 | 16 | 19 | 20 => input.take(2)?,
+        15 => input.take(3)?,
+        3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => input.take(4)?,
+        5 | 6 => input.take(8)?,
+        // parse_cp_info reports the invalid tag
+        _ => {}
+    }
+    input.parse(|bytes| parse_cp_info(0, bytes)).map(CpInfo::into_owned)
+}
+
+/// Add an entry to the constant pool, with the unusable slot following a
+/// CONSTANT_Long or a CONSTANT_Double. `offset` is the offset of the entry.
+fn push_cp_info(cp_info: &mut Vec<CpInfo<'static>>, cp_item: CpInfo<'static>, constant_pool_count: u16, offset: usize) -> Result<(), ClassFormatError> {
+    let index = cp_info.len() as u16;
+    let two_slots = matches!(cp_item, CpInfo::ConstantLong { .. } | CpInfo::ConstantDouble { .. });
+    if two_slots && index + 1 == constant_pool_count {
+        return Err(two_slots_in_last_entry(offset, index));
+    }
+    cp_info.push(cp_item);
+    if two_slots {
+        cp_info.push(CpInfo::Unusable);
+    }
+    Ok(())
+}
+
+fn read_interfaces<R: Read>(input: &mut StructureReader<R>, interfaces_count: u16) -> Result<Vec<u16>, ClassFormatError> {
+    input.begin();
+    input.take(2 * interfaces_count as usize)?;
+    input.parse(|bytes| parse_interfaces(0, interfaces_count, bytes))
+}
+
+/// access_flags, name_index, descriptor_index and attributes_count of a
+/// field or method.
+fn read_member<R: Read>(input: &mut StructureReader<R>) -> Result<(u16, u16, u16, u16), ClassFormatError> {
+    input.begin();
+    input.take(8)?;
+    input.parse(|bytes| {
+        let (idx, access_flags) = get_u2(0, bytes)?;
+        let (idx, name_index) = get_u2(idx, bytes)?;
+        let (idx, descriptor_index) = get_u2(idx, bytes)?;
+        let (idx, attributes_count) = get_u2(idx, bytes)?;
+        Ok((idx, (access_flags, name_index, descriptor_index, attributes_count)))
+    })
+}
+
+fn read_exception_table<R: Read>(input: &mut StructureReader<R>, exception_table_length: u16) -> Result<Vec<ExceptionTable>, ClassFormatError> {
+    input.begin();
+    input.take(8 * exception_table_length as usize)?;
+    input.parse(|bytes| {
+        let mut exception_table: Vec<ExceptionTable> = Vec::with_capacity(exception_table_length as usize);
+        let mut idx = 0;
+        for n in 0..exception_table_length as usize {
+            let (i, exception) = parse_exception_table(idx, bytes).map_err(|e| e.within(format!("exception_table[{}]", n)))?;
+            idx = i;
+            exception_table.push(exception);
+        }
+        Ok((idx, exception_table))
+    })
+}
+
+fn read_attributes<R: Read>(input: &mut StructureReader<R>, attributes_count: u16, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions) -> Result<Vec<AttributeInfo<'static>>, ClassFormatError> {
+    let mut attributes: Vec<AttributeInfo> = Vec::with_capacity(attributes_count as usize);
+    for n in 0..attributes_count {
+        let attribute_info = input.read_attribute_header()
+            .and_then(|(attribute_name_index, attribute_length)| {
+                read_attribute_body(input, attribute_name_index, attribute_length, cp_info, decoders, options)
+            })
+            .map_err(|e| e.within(format!("attributes[{}]", n)))?;
+        if let Some(attribute_info) = attribute_info {
+            attributes.push(attribute_info);
+        }
+    }
+    Ok(attributes)
+}
+
+/// Read the info of the attribute whose header is the current structure,
+/// None when it is skipped by `options`.
+fn read_attribute_body<R: Read>(input: &mut StructureReader<R>, attribute_name_index: u16, attribute_length: u32, cp_info: &[CpInfo], decoders: &AttributeDecoders, options: &ParseOptions) -> Result<Option<AttributeInfo<'static>>, ClassFormatError> {
+    match attribute_name(attribute_name_index, cp_info) {
+        Some(attribute_name) if options.skips(attribute_name) => {
+            input.skip(attribute_length as usize).map_err(|e| e.within(attribute_name))?;
+            Ok(None)
+        },
+        _ => {
+            input.take(attribute_length as usize)?;
+            input.parse(|bytes| parse_attribute_info(0, cp_info, decoders, options, bytes))
+                .map(|attribute_info| attribute_info.map(AttributeInfo::into_owned))
+        }
+    }
+}
+
+fn attribute_name<'c>(attribute_name_index: u16, cp_info: &'c [CpInfo]) -> Option<&'c str> {
+    match cp_info.get(attribute_name_index as usize) {
+        Some(CpInfo::ConstantUtf8 { tag: _tag, length: _length, bytes: _bytes, bytes_str }) => Some(bytes_str),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_class_file;
+
+    /// public class A { public static void m() { return; } }, without the
+    /// constructor.
+    fn class_file_bytes() -> Vec<u8> {
+        let mut bytes: Vec<u8> = vec![0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x34, 0x00, 0x08];
+        bytes.extend([7, 0, 2]);
+        bytes.extend([1, 0, 1, b'A']);
+        bytes.extend([7, 0, 4]);
+        bytes.extend([1, 0, 16]);
+        bytes.extend(b"java/lang/Object");
+        bytes.extend([1, 0, 4]);
+        bytes.extend(b"Code");
+        bytes.extend([1, 0, 1, b'm']);
+        bytes.extend([1, 0, 3]);
+        bytes.extend(b"()V");
+        // access_flags, this_class, super_class, interfaces_count and
+        // fields_count
+        bytes.extend([0x00, 0x21, 0x00, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00]);
+        // methods_count and the method with its Code
+        bytes.extend([0x00, 0x01, 0x00, 0x09, 0x00, 0x06, 0x00, 0x07, 0x00, 0x01]);
+        bytes.extend([0x00, 0x05, 0x00, 0x00, 0x00, 0x0D, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0xB1, 0x00, 0x00, 0x00, 0x00]);
+        // attributes_count
+        bytes.extend([0x00, 0x00]);
+        bytes
+    }
+
+    fn events(bytes: &[u8]) -> Vec<Result<ClassEvent, ClassFormatError>> {
+        ClassEventReader::new(bytes, &AttributeDecoders::new(), &ParseOptions::default()).collect()
+    }
+
+    #[test]
+    fn reader_matches_slice_parser() {
+        let bytes = class_file_bytes();
+        let class_file = parse_class_file_from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(format!("{:?}", class_file), format!("{:?}", parse_class_file(&bytes).unwrap()));
+    }
+
+    #[test]
+    fn reader_truncated_input() {
+        let bytes = class_file_bytes();
+        for len in 0..bytes.len() {
+            let err = parse_class_file_from_reader(&bytes[..len]).unwrap_err();
+            assert!(matches!(err.kind, ClassFormatErrorKind::UnexpectedEof { .. }), "{} bytes: {}", len, err);
+            assert_eq!(err, parse_class_file(&bytes[..len]).unwrap_err(), "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn long_in_last_entry() {
+        // A constant pool with a CONSTANT_Long in its last entry
+        let mut bytes: Vec<u8> = vec![0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x34, 0x00, 0x03];
+        bytes.extend([1, 0, 1, b'A']);
+        bytes.extend([5, 0, 0, 0, 0, 0, 0, 0, 1]);
+        let err = parse_class_file(&bytes).unwrap_err();
+        assert_eq!(err, ClassFormatError { offset: 14, path: vec![String::from("constant_pool[2]")], kind: ClassFormatErrorKind::InvalidConstantPoolIndex(3) });
+        assert_eq!(parse_class_file_from_reader(&bytes[..]).unwrap_err(), err);
+        assert_eq!(events(&bytes).pop().unwrap().unwrap_err(), err);
+    }
+
+    #[test]
+    fn read_events() {
+        let events: Vec<ClassEvent> = events(&class_file_bytes()).into_iter().collect::<Result<_, _>>().unwrap();
+        assert_eq!(events.len(), 16);
+        assert!(matches!(events[0], ClassEvent::Header { magic: 0xCAFEBABE, major_version: 52, constant_pool_count: 8, .. }));
+        assert!(events[1..8].iter().all(|event| matches!(event, ClassEvent::ConstantPoolEntry { .. })));
+        assert!(matches!(events[8], ClassEvent::Class { access_flags: 0x21, this_class: 1, super_class: 3, .. }));
+        assert!(matches!(events[9], ClassEvent::MethodStart { name_index: 6, descriptor_index: 7, .. }));
+        assert!(matches!(events[10], ClassEvent::CodeStart { code_length: 1, .. }));
+        assert!(matches!(&events[11], ClassEvent::Instruction { pc: 0, instruction } if instruction.opcode == crate::parser::opcodes::Opcode::Return));
+        assert!(matches!(events[12], ClassEvent::ExceptionTable { .. }));
+        assert!(matches!(events[13], ClassEvent::CodeEnd));
+        assert!(matches!(events[14], ClassEvent::MethodEnd));
+        assert!(matches!(events[15], ClassEvent::End));
+    }
+
+    #[test]
+    fn read_events_truncated_input() {
+        let bytes = class_file_bytes();
+        for len in 0..bytes.len() {
+            let events = events(&bytes[..len]);
+            // The events end with the error
+            let (last, others) = events.split_last().unwrap();
+            assert!(others.iter().all(|event| event.is_ok()), "{} bytes", len);
+            assert!(matches!(last, Err(ClassFormatError { kind: ClassFormatErrorKind::UnexpectedEof { .. }, .. })), "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn read_events_skip_past_code() {
+        // A Code whose attribute_length covers 2 more bytes than its structure
+        let mut bytes = class_file_bytes();
+        let attribute_length_idx = bytes.len() - 16;
+        bytes[attribute_length_idx] = 0x0F;
+        bytes.splice(bytes.len() - 2..bytes.len() - 2, [0xCA, 0xFE]);
+        let events = events(&bytes);
+        assert!(events.iter().all(|event| event.is_ok()));
+        assert!(matches!(events.last(), Some(Ok(ClassEvent::End))));
+        assert!(parse_class_file(&bytes).is_ok());
+    }
+}
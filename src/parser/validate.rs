@@ -0,0 +1,1473 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::iter;
+use crate::parser::ast::{Annotation, AttributeInfo, ClassFile, CpInfo, ElementValue, ExceptionTable, FieldInfo, FieldType, FieldTypeTerm, MethodDescriptor, MethodInfo, ReturnType, StackMapFrame, TargetInfo, TypeAnnotation, VerificationTypeInfo};
+use crate::parser::descriptor::{parse_field_descriptor, parse_method_descriptor};
+use crate::parser::opcodes::{decode_instructions, Instruction, Opcode, Operand};
+
+/// Newest class file version, Java 19.
+const MAX_MAJOR_VERSION: u16 = 63;
+const MAX_CODE_LENGTH: usize = 65535;
+
+const ACC_PUBLIC: u16 = 0x0001;
+const ACC_PRIVATE: u16 = 0x0002;
+const ACC_PROTECTED: u16 = 0x0004;
+const ACC_STATIC: u16 = 0x0008;
+const ACC_FINAL: u16 = 0x0010;
+const ACC_SUPER: u16 = 0x0020;
+const ACC_SYNCHRONIZED: u16 = 0x0020;
+const ACC_VOLATILE: u16 = 0x0040;
+const ACC_BRIDGE: u16 = 0x0040;
+const ACC_NATIVE: u16 = 0x0100;
+const ACC_INTERFACE: u16 = 0x0200;
+const ACC_ABSTRACT: u16 = 0x0400;
+const ACC_STRICT: u16 = 0x0800;
+const ACC_SYNTHETIC: u16 = 0x1000;
+const ACC_ANNOTATION: u16 = 0x2000;
+const ACC_ENUM: u16 = 0x4000;
+const ACC_MODULE: u16 = 0x8000;
+
+/// Attributes that may appear at most once in an attributes table.
+const UNIQUE_ATTRIBUTES: [&str; 25] = [
+    "ConstantValue", "Code", "StackMapTable", "Exceptions", "InnerClasses", "EnclosingMethod", "Signature",
+    "SourceFile", "SourceDebugExtension", "RuntimeVisibleAnnotations", "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleParameterAnnotations", "RuntimeInvisibleParameterAnnotations", "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations", "AnnotationDefault", "BootstrapMethods", "MethodParameters", "Module",
+    "ModulePackages", "ModuleMainClass", "NestHost", "NestMembers", "Record", "PermittedSubclasses",
+];
+
+/// Problem found by `validate` in a class file that parsed, with the path of
+/// the structure where it was found (ex.: `methods[3].attributes[0].Code`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: Vec<String>,
+    pub kind: DiagnosticKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    InvalidMagic(u32),
+    UnsupportedVersion { major_version: u16, minor_version: u16 },
+    /// Index 0, out of the constant pool or of the unusable entry following
+    /// a CONSTANT_Long or CONSTANT_Double.
+    InvalidConstantPoolIndex(u16),
+    UnexpectedConstantPoolEntry { index: u16, expected: &'static str },
+    /// Constant not allowed by the class file version or outside of a
+    /// module, or method handle not matching its reference_kind.
+    InvalidConstant(String),
+    /// bootstrap_method_attr_index without an entry in the BootstrapMethods
+    /// attribute.
+    InvalidBootstrapMethodIndex(u16),
+    /// Class, package, field or method name not allowed by JVMS 4.2.
+    InvalidName(String),
+    InvalidDescriptor(String),
+    /// Super class of an interface other than java/lang/Object.
+    InvalidSuperClass(String),
+    IllegalAccessFlags { flags: u16, reason: &'static str },
+    DuplicateField { name: String, descriptor: String },
+    DuplicateMethod { name: String, descriptor: String },
+    DuplicateAttribute(String),
+    /// The attribute_length differs from the size of the parsed attribute.
+    AttributeLengthMismatch { attribute_length: u32, actual: usize },
+    /// Method without a Code attribute that is neither abstract nor native.
+    MissingCode,
+    /// Code attribute of an abstract or native method.
+    UnexpectedCode,
+    InvalidCode(String),
+    /// Branch target that is not the start of an instruction.
+    InvalidBranchTarget(u32),
+    /// pc of an exception handler, line number or local variable that is not
+    /// the start of an instruction.
+    InvalidPc(u32),
+    /// Empty range or range past the end of the code.
+    InvalidCodeRange { start_pc: u32, end_pc: u32 },
+}
+
+impl DiagnosticKind {
+    /// Reference to a constant pool entry or bootstrap method that does not
+    /// exist or has the wrong kind, the class file cannot be described.
+    pub fn is_unresolved_reference(&self) -> bool {
+        matches!(self, DiagnosticKind::InvalidConstantPoolIndex(_)
+            | DiagnosticKind::UnexpectedConstantPoolEntry { .. }
+            | DiagnosticKind::InvalidBootstrapMethodIndex(_))
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticKind::InvalidMagic(magic) =>
+                write!(f, "invalid magic 0x{:08X}", magic),
+            DiagnosticKind::UnsupportedVersion { major_version, minor_version } =>
+                write!(f, "unsupported class file version {}.{}", major_version, minor_version),
+            DiagnosticKind::InvalidConstantPoolIndex(index) =>
+                write!(f, "invalid constant pool index {}", index),
+            DiagnosticKind::UnexpectedConstantPoolEntry { index, expected } =>
+                write!(f, "expected {} at constant pool index {}", expected, index),
+            DiagnosticKind::InvalidConstant(reason) =>
+                write!(f, "invalid constant: {}", reason),
+            DiagnosticKind::InvalidBootstrapMethodIndex(index) =>
+                write!(f, "invalid bootstrap method index {}", index),
+            DiagnosticKind::InvalidName(name) =>
+                write!(f, "invalid name {}", name),
+            DiagnosticKind::InvalidDescriptor(descriptor) =>
+                write!(f, "invalid descriptor {}", descriptor),
+            DiagnosticKind::InvalidSuperClass(name) =>
+                write!(f, "invalid super class {} of an interface", name),
+            DiagnosticKind::IllegalAccessFlags { flags, reason } =>
+                write!(f, "illegal access flags {:#06x}: {}", flags, reason),
+            DiagnosticKind::DuplicateField { name, descriptor } =>
+                write!(f, "duplicate field {} {}", name, descriptor),
+            DiagnosticKind::DuplicateMethod { name, descriptor } =>
+                write!(f, "duplicate method {} {}", name, descriptor),
+            DiagnosticKind::DuplicateAttribute(name) =>
+                write!(f, "duplicate attribute {}", name),
+            DiagnosticKind::AttributeLengthMismatch { attribute_length, actual } =>
+                write!(f, "attribute_length {} does not match the {} byte(s) of the attribute", attribute_length, actual),
+            DiagnosticKind::MissingCode =>
+                write!(f, "missing Code attribute"),
+            DiagnosticKind::UnexpectedCode =>
+                write!(f, "Code attribute in an abstract or native method"),
+            DiagnosticKind::InvalidCode(reason) =>
+                write!(f, "invalid code: {}", reason),
+            DiagnosticKind::InvalidBranchTarget(target) =>
+                write!(f, "invalid branch target {}", target),
+            DiagnosticKind::InvalidPc(pc) =>
+                write!(f, "invalid pc {}", pc),
+            DiagnosticKind::InvalidCodeRange { start_pc, end_pc } =>
+                write!(f, "invalid code range [{}, {})", start_pc, end_pc),
+        }
+    }
+}
+
+impl Diagnostic {
+    pub fn structure(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{} in {}", self.kind, self.structure())
+        }
+    }
+}
+
+/// Check the format of a parsed class file per JVMS 4.8: magic and version,
+/// constant pool references, names and descriptors, attribute lengths,
+/// access flags, duplicate members and the Code of methods. The parser only
+/// fails on what it cannot read, a class file that parsed may still be
+/// rejected by the JVM. The class file must be parsed without skipping
+/// attributes, otherwise their absence is reported.
+pub fn validate(class_file: &ClassFile) -> Vec<Diagnostic> {
+    let mut validator = Validator { class_file, path: Vec::new(), diagnostics: Vec::new() };
+    validator.validate_header();
+    validator.validate_constant_pool();
+    validator.validate_class();
+    validator.validate_fields();
+    validator.validate_methods();
+    validator.validate_attributes(&class_file.attributes, None);
+    validator.diagnostics
+}
+
+struct Validator<'c, 'a> {
+    class_file: &'c ClassFile<'a>,
+    /// Structure being validated, like `ClassFormatError::path`
+    path: Vec<String>,
+    diagnostics: Vec<Diagnostic>
+}
+
+impl<'c, 'a> Validator<'c, 'a> {
+    fn report(&mut self, kind: DiagnosticKind) {
+        self.diagnostics.push(Diagnostic { path: self.path.clone(), kind });
+    }
+
+    fn within<S: Into<String>, F: FnOnce(&mut Self)>(&mut self, structure: S, f: F) {
+        self.path.push(structure.into());
+        f(self);
+        self.path.pop();
+    }
+
+    fn validate_header(&mut self) {
+        let class_file = self.class_file;
+        if class_file.magic != 0xCAFEBABE {
+            self.within("magic", |v| v.report(DiagnosticKind::InvalidMagic(class_file.magic)));
+        }
+        let (major_version, minor_version) = (class_file.major_version, class_file.minor_version);
+        // Since Java 12 the minor version is 0, or 65535 when the class uses
+        // preview features.
+        if !(45..=MAX_MAJOR_VERSION).contains(&major_version) || (major_version >= 56 && minor_version != 0 && minor_version != 65535) {
+            self.within("major_version", |v| v.report(DiagnosticKind::UnsupportedVersion { major_version, minor_version }));
+        }
+    }
+
+    fn validate_constant_pool(&mut self) {
+        let class_file = self.class_file;
+        for (index, cp_info) in class_file.cp_info.iter().enumerate().skip(1) {
+            self.within(format!("constant_pool[{}]", index), |v| v.validate_constant(cp_info));
+        }
+    }
+
+    fn validate_constant(&mut self, cp_info: &CpInfo) {
+        match cp_info {
+            CpInfo::ConstantClass { tag: _tag, name_index } => {
+                if let Some(name) = self.expect_utf8(*name_index) {
+                    if !is_class_name(name) {
+                        self.report(DiagnosticKind::InvalidName(String::from(name)));
+                    }
+                }
+            },
+            CpInfo::ConstantFieldref { tag: _tag, class_index, name_and_type_index } => {
+                self.expect_class(*class_index);
+                if let Some((name, descriptor)) = self.expect_name_and_type(*name_and_type_index) {
+                    self.check_name(name, is_unqualified_name(name));
+                    self.check_field_descriptor(descriptor);
+                }
+            },
+            CpInfo::ConstantMethodref { tag: _tag, class_index, name_and_type_index } |
+            CpInfo::ConstantInterfaceMethodref { tag: _tag, class_index, name_and_type_index } => {
+                self.expect_class(*class_index);
+                if let Some((name, descriptor)) = self.expect_name_and_type(*name_and_type_index) {
+                    // Instance initialization methods are only invoked on
+                    // classes, class initialization methods never.
+                    let is_init = name == "<init>" && matches!(cp_info, CpInfo::ConstantMethodref { .. });
+                    self.check_name(name, is_init || is_method_name(name));
+                    if let Some(method_descriptor) = self.check_method_descriptor(descriptor) {
+                        if is_init && method_descriptor.ret != ReturnType::Void {
+                            self.report(DiagnosticKind::InvalidDescriptor(String::from(descriptor)));
+                        }
+                    }
+                }
+            },
+            CpInfo::ConstantString { tag: _tag, string_index } => {
+                self.expect_utf8(*string_index);
+            },
+            CpInfo::ConstantNameAndType { tag: _tag, name_index, descriptor_index } => {
+                self.expect_utf8(*name_index);
+                self.expect_utf8(*descriptor_index);
+            },
+            CpInfo::ConstantMethodHandle { tag: _tag, reference_kind, reference_index } => {
+                self.require_version(51, "CONSTANT_MethodHandle");
+                self.validate_method_handle(*reference_kind, *reference_index);
+            },
+            CpInfo::ConstantMethodType { tag: _tag, descriptor_index } => {
+                self.require_version(51, "CONSTANT_MethodType");
+                if let Some(descriptor) = self.expect_utf8(*descriptor_index) {
+                    self.check_method_descriptor(descriptor);
+                }
+            },
+            CpInfo::ConstantDynamic { tag: _tag, bootstrap_method_attr_index, name_and_type_index } => {
+                self.require_version(55, "CONSTANT_Dynamic");
+                self.check_bootstrap_method_index(*bootstrap_method_attr_index);
+                if let Some((name, descriptor)) = self.expect_name_and_type(*name_and_type_index) {
+                    self.check_name(name, is_unqualified_name(name));
+                    self.check_field_descriptor(descriptor);
+                }
+            },
+            CpInfo::ConstantInvokeDynamic { tag: _tag, bootstrap_method_attr_index, name_and_type_index } => {
+                self.require_version(51, "CONSTANT_InvokeDynamic");
+                self.check_bootstrap_method_index(*bootstrap_method_attr_index);
+                if let Some((name, descriptor)) = self.expect_name_and_type(*name_and_type_index) {
+                    self.check_name(name, is_method_name(name));
+                    self.check_method_descriptor(descriptor);
+                }
+            },
+            CpInfo::ConstantModule { tag: _tag, name_index } => {
+                self.require_module("CONSTANT_Module");
+                self.expect_utf8(*name_index);
+            },
+            CpInfo::ConstantPackage { tag: _tag, name_index } => {
+                self.require_module("CONSTANT_Package");
+                if let Some(name) = self.expect_utf8(*name_index) {
+                    self.check_name(name, is_binary_name(name));
+                }
+            },
+            CpInfo::ConstantInteger { .. } | CpInfo::ConstantFloat { .. } | CpInfo::ConstantLong { .. } |
+            CpInfo::ConstantDouble { .. } | CpInfo::ConstantUtf8 { .. } | CpInfo::Unusable => ()
+        }
+    }
+
+    /// The reference_index of a method handle must point at a field for the
+    /// field kinds and at a method for the invoke kinds, REF_newInvokeSpecial
+    /// at a constructor and the others at any method but the initialization
+    /// methods.
+    fn validate_method_handle(&mut self, reference_kind: u8, reference_index: u16) {
+        let major_version = self.class_file.major_version;
+        let expected = match reference_kind {
+            1..=4 => "CONSTANT_Fieldref",
+            5 | 8 => "CONSTANT_Methodref",
+            6 | 7 if major_version >= 52 => "CONSTANT_Methodref or CONSTANT_InterfaceMethodref",
+            6 | 7 => "CONSTANT_Methodref",
+            9 => "CONSTANT_InterfaceMethodref",
+            _ => {
+                self.report(DiagnosticKind::InvalidConstant(format!("invalid reference_kind {}", reference_kind)));
+                return;
+            }
+        };
+        let reference = match self.constant(reference_index) {
+            Some(reference) => reference,
+            None => return
+        };
+        let matches = match (reference_kind, reference) {
+            (1..=4, CpInfo::ConstantFieldref { .. }) => true,
+            (5..=8, CpInfo::ConstantMethodref { .. }) => true,
+            (6 | 7, CpInfo::ConstantInterfaceMethodref { .. }) => major_version >= 52,
+            (9, CpInfo::ConstantInterfaceMethodref { .. }) => true,
+            _ => false
+        };
+        if !matches {
+            self.report(DiagnosticKind::UnexpectedConstantPoolEntry { index: reference_index, expected });
+            return;
+        }
+        let name = match reference {
+            CpInfo::ConstantFieldref { tag: _tag, class_index: _class_index, name_and_type_index } |
+            CpInfo::ConstantMethodref { tag: _tag, class_index: _class_index, name_and_type_index } |
+            CpInfo::ConstantInterfaceMethodref { tag: _tag, class_index: _class_index, name_and_type_index } =>
+                name_and_type_at(*name_and_type_index, &self.class_file.cp_info).map(|(name, _)| name),
+            _ => None
+        };
+        match (reference_kind, name) {
+            (8, Some(name)) if name != "<init>" =>
+                self.report(DiagnosticKind::InvalidConstant(format!("REF_newInvokeSpecial of method {}", name))),
+            (5..=7 | 9, Some(name @ ("<init>" | "<clinit>"))) =>
+                self.report(DiagnosticKind::InvalidConstant(format!("reference_kind {} of method {}", reference_kind, name))),
+            _ => ()
+        }
+    }
+
+    fn require_version(&mut self, major_version: u16, constant: &str) {
+        if self.class_file.major_version < major_version {
+            self.report(DiagnosticKind::InvalidConstant(format!("{} requires class file version {}", constant, major_version)));
+        }
+    }
+
+    fn require_module(&mut self, constant: &str) {
+        if self.class_file.access_flags & ACC_MODULE == 0 {
+            self.report(DiagnosticKind::InvalidConstant(format!("{} outside of a module", constant)));
+        }
+    }
+
+    fn check_bootstrap_method_index(&mut self, bootstrap_method_attr_index: u16) {
+        let bootstrap_methods_count = self.class_file.attributes.iter()
+            .find_map(|attribute| match attribute {
+                AttributeInfo::BootstrapMethods { bootstrap_methods, .. } => Some(bootstrap_methods.len()),
+                _ => None
+            })
+            .unwrap_or(0);
+        if bootstrap_method_attr_index as usize >= bootstrap_methods_count {
+            self.report(DiagnosticKind::InvalidBootstrapMethodIndex(bootstrap_method_attr_index));
+        }
+    }
+
+    fn validate_class(&mut self) {
+        let class_file = self.class_file;
+        let access_flags = class_file.access_flags;
+        self.within("access_flags", |v| {
+            if let Some(reason) = class_access_flags_error(access_flags) {
+                v.report(DiagnosticKind::IllegalAccessFlags { flags: access_flags, reason });
+            }
+        });
+        self.within("this_class", |v| {
+            v.expect_class(class_file.this_class);
+        });
+        self.within("super_class", |v| {
+            if class_file.super_class == 0 {
+                // Only java.lang.Object and modules have no super class.
+                let this_class_name = class_name_at(class_file.this_class, &class_file.cp_info);
+                if access_flags & ACC_MODULE == 0 && this_class_name != Some("java/lang/Object") {
+                    v.report(DiagnosticKind::InvalidConstantPoolIndex(0));
+                }
+            } else if v.expect_class(class_file.super_class) && access_flags & ACC_INTERFACE != 0 {
+                match class_name_at(class_file.super_class, &class_file.cp_info) {
+                    Some("java/lang/Object") | None => (),
+                    Some(name) => v.report(DiagnosticKind::InvalidSuperClass(String::from(name)))
+                }
+            }
+        });
+        for (n, interface) in class_file.interfaces.iter().enumerate() {
+            self.within(format!("interfaces[{}]", n), |v| {
+                v.expect_class(*interface);
+            });
+        }
+    }
+
+    fn validate_fields(&mut self) {
+        let class_file = self.class_file;
+        let mut fields: HashSet<(&str, &str)> = HashSet::new();
+        for (n, field_info) in class_file.fields.iter().enumerate() {
+            self.within(format!("fields[{}]", n), |v| {
+                v.validate_field(field_info);
+                if let Some((name, descriptor)) = member_at(field_info.name_index, field_info.descriptor_index, &class_file.cp_info) {
+                    if !fields.insert((name, descriptor)) {
+                        v.report(DiagnosticKind::DuplicateField { name: String::from(name), descriptor: String::from(descriptor) });
+                    }
+                }
+            });
+        }
+    }
+
+    fn validate_field(&mut self, field_info: &'c FieldInfo<'a>) {
+        let access_flags = field_info.access_flags;
+        let is_interface = self.class_file.access_flags & ACC_INTERFACE != 0;
+        self.within("access_flags", |v| {
+            if let Some(reason) = field_access_flags_error(access_flags, is_interface) {
+                v.report(DiagnosticKind::IllegalAccessFlags { flags: access_flags, reason });
+            }
+        });
+        self.within("name_index", |v| {
+            if let Some(name) = v.expect_utf8(field_info.name_index) {
+                v.check_name(name, is_unqualified_name(name));
+            }
+        });
+        let mut field_type: Option<FieldType> = None;
+        self.within("descriptor_index", |v| {
+            if let Some(descriptor) = v.expect_utf8(field_info.descriptor_index) {
+                field_type = v.check_field_descriptor(descriptor);
+            }
+        });
+        self.validate_attributes(&field_info.attributes, None);
+        // The constant must have the type of the field
+        for (n, attribute) in field_info.attributes.iter().enumerate() {
+            if let (AttributeInfo::ConstantValue { constantvalue_index, .. }, Some(field_type)) = (attribute, &field_type) {
+                self.within(format!("attributes[{}]", n), |v| v.within("ConstantValue", |v| {
+                    v.check_constant_value(*constantvalue_index, field_type);
+                }));
+            }
+        }
+    }
+
+    fn check_constant_value(&mut self, constantvalue_index: u16, field_type: &FieldType) {
+        match field_type {
+            FieldType::BaseType { term: FieldTypeTerm::J } =>
+                self.expect(constantvalue_index, "CONSTANT_Long", |c| matches!(c, CpInfo::ConstantLong { .. })),
+            FieldType::BaseType { term: FieldTypeTerm::F } =>
+                self.expect(constantvalue_index, "CONSTANT_Float", |c| matches!(c, CpInfo::ConstantFloat { .. })),
+            FieldType::BaseType { term: FieldTypeTerm::D } =>
+                self.expect(constantvalue_index, "CONSTANT_Double", |c| matches!(c, CpInfo::ConstantDouble { .. })),
+            FieldType::BaseType { .. } =>
+                self.expect(constantvalue_index, "CONSTANT_Integer", |c| matches!(c, CpInfo::ConstantInteger { .. })),
+            FieldType::ObjectType { class_name } if class_name == "java/lang/String" =>
+                self.expect(constantvalue_index, "CONSTANT_String", |c| matches!(c, CpInfo::ConstantString { .. })),
+            _ => self.constant(constantvalue_index).is_some()
+        };
+    }
+
+    fn validate_methods(&mut self) {
+        let class_file = self.class_file;
+        let mut methods: HashSet<(&str, &str)> = HashSet::new();
+        for (n, method_info) in class_file.methods.iter().enumerate() {
+            self.within(format!("methods[{}]", n), |v| {
+                v.validate_method(method_info);
+                if let Some((name, descriptor)) = member_at(method_info.name_index, method_info.descriptor_index, &class_file.cp_info) {
+                    if !methods.insert((name, descriptor)) {
+                        v.report(DiagnosticKind::DuplicateMethod { name: String::from(name), descriptor: String::from(descriptor) });
+                    }
+                }
+            });
+        }
+    }
+
+    fn validate_method(&mut self, method_info: &'c MethodInfo<'a>) {
+        let access_flags = method_info.access_flags;
+        let is_interface = self.class_file.access_flags & ACC_INTERFACE != 0;
+        let major_version = self.class_file.major_version;
+        let mut method_name: Option<&str> = None;
+        self.within("name_index", |v| {
+            if let Some(name) = v.expect_utf8(method_info.name_index) {
+                let is_special = name == "<clinit>" || (name == "<init>" && !is_interface);
+                v.check_name(name, is_special || is_method_name(name));
+                method_name = Some(name);
+            }
+        });
+        let mut method_descriptor: Option<MethodDescriptor> = None;
+        self.within("descriptor_index", |v| {
+            if let Some(descriptor) = v.expect_utf8(method_info.descriptor_index) {
+                method_descriptor = v.check_method_descriptor(descriptor);
+                let is_initializer = matches!(method_name, Some("<init>" | "<clinit>"));
+                match &method_descriptor {
+                    Some(MethodDescriptor { params, ret }) if is_initializer => {
+                        let has_params = method_name == Some("<clinit>") && major_version >= 51 && !params.is_empty();
+                        if *ret != ReturnType::Void || has_params {
+                            v.report(DiagnosticKind::InvalidDescriptor(String::from(descriptor)));
+                        }
+                    },
+                    _ => ()
+                }
+            }
+        });
+        self.within("access_flags", |v| {
+            if let Some(reason) = method_access_flags_error(access_flags, method_name.unwrap_or(""), is_interface, major_version) {
+                v.report(DiagnosticKind::IllegalAccessFlags { flags: access_flags, reason });
+            }
+        });
+        let has_code = method_info.attributes.iter().any(|attribute| matches!(attribute, AttributeInfo::Code { .. }));
+        if access_flags & (ACC_ABSTRACT | ACC_NATIVE) != 0 {
+            if has_code {
+                self.report(DiagnosticKind::UnexpectedCode);
+            }
+        } else if !has_code {
+            self.report(DiagnosticKind::MissingCode);
+        }
+        self.validate_attributes(&method_info.attributes, None);
+        // The parameters are the first local variables
+        if let Some(method_descriptor) = method_descriptor {
+            let this_size = if access_flags & ACC_STATIC == 0 { 1 } else { 0 };
+            let parameters_size: usize = this_size + method_descriptor.params.iter().map(field_type_size).sum::<usize>();
+            for (n, attribute) in method_info.attributes.iter().enumerate() {
+                if let AttributeInfo::Code { max_locals, .. } = attribute {
+                    if (*max_locals as usize) < parameters_size {
+                        let reason = format!("max_locals {} is less than the size of the parameters {}", max_locals, parameters_size);
+                        self.within(format!("attributes[{}]", n), |v| v.within("Code", |v| v.report(DiagnosticKind::InvalidCode(reason))));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check the attributes of the class, a member, a record component or a
+    /// Code attribute. `instructions` are the pcs where the instructions of
+    /// the enclosing Code start, when it could be decoded.
+    fn validate_attributes(&mut self, attributes: &'c [AttributeInfo<'a>], instructions: Option<&[bool]>) {
+        let cp_info = &self.class_file.cp_info;
+        let mut attribute_names: HashSet<&str> = HashSet::new();
+        for (n, attribute) in attributes.iter().enumerate() {
+            let (attribute_name_index, attribute_length) = attribute.header();
+            let attribute_name = utf8_at(attribute_name_index, cp_info).unwrap_or_default();
+            self.within(format!("attributes[{}]", n), |v| v.within(attribute_name, |v| {
+                if UNIQUE_ATTRIBUTES.contains(&attribute_name) && !attribute_names.insert(attribute_name) {
+                    v.report(DiagnosticKind::DuplicateAttribute(String::from(attribute_name)));
+                }
+                let actual = attribute_info_length(attribute);
+                if attribute_length as usize != actual {
+                    v.report(DiagnosticKind::AttributeLengthMismatch { attribute_length, actual });
+                }
+                v.validate_attribute(attribute, instructions);
+            }));
+        }
+    }
+
+    fn validate_attribute(&mut self, attribute: &'c AttributeInfo<'a>, instructions: Option<&[bool]>) {
+        match attribute {
+            AttributeInfo::Code { code, exception_table, attributes, .. } =>
+                self.validate_code(code, exception_table, attributes),
+            AttributeInfo::StackMapTable { entries, .. } => {
+                for (n, frame) in entries.iter().enumerate() {
+                    self.within(format!("entries[{}]", n), |v| v.validate_stack_map_frame(frame, instructions));
+                }
+            },
+            AttributeInfo::Exceptions { exception_index_table, .. } => {
+                for (n, exception_index) in exception_index_table.iter().enumerate() {
+                    self.within(format!("exception_index_table[{}]", n), |v| {
+                        v.expect_class(*exception_index);
+                    });
+                }
+            },
+            AttributeInfo::InnerClasses { classes, .. } => {
+                for (n, inner_class) in classes.iter().enumerate() {
+                    self.within(format!("classes[{}]", n), |v| {
+                        v.expect_class(inner_class.inner_class_info_index);
+                        if inner_class.outer_class_info_index != 0 {
+                            v.expect_class(inner_class.outer_class_info_index);
+                        }
+                        if inner_class.inner_name_index != 0 {
+                            v.expect_utf8(inner_class.inner_name_index);
+                        }
+                    });
+                }
+            },
+            AttributeInfo::EnclosingMethod { class_index, method_index, .. } => {
+                self.expect_class(*class_index);
+                if *method_index != 0 {
+                    self.expect_name_and_type(*method_index);
+                }
+            },
+            AttributeInfo::Signature { signature_index: index, .. } |
+            AttributeInfo::SourceFile { sourcefile_index: index, .. } => {
+                self.expect_utf8(*index);
+            },
+            AttributeInfo::LineNumberTable { line_number_tables, .. } => {
+                if let Some(instructions) = instructions {
+                    for (n, line_number) in line_number_tables.iter().enumerate() {
+                        if line_number.start_pc as usize >= instructions.len() {
+                            self.within(format!("line_number_table[{}]", n), |v| v.report(DiagnosticKind::InvalidPc(line_number.start_pc as u32)));
+                        }
+                    }
+                }
+            },
+            AttributeInfo::LocalVariableTable { local_variable_table, .. } => {
+                for (n, local_variable) in local_variable_table.iter().enumerate() {
+                    self.within(format!("local_variable_table[{}]", n), |v| {
+                        v.check_local_variable_range(local_variable.start_pc, local_variable.length, instructions);
+                        if let Some(name) = v.expect_utf8(local_variable.name_index) {
+                            v.check_name(name, is_unqualified_name(name));
+                        }
+                        if let Some(descriptor) = v.expect_utf8(local_variable.descriptor_index) {
+                            v.check_field_descriptor(descriptor);
+                        }
+                    });
+                }
+            },
+            AttributeInfo::LocalVariableTypeTable { local_variable_type_table, .. } => {
+                for (n, local_variable) in local_variable_type_table.iter().enumerate() {
+                    self.within(format!("local_variable_type_table[{}]", n), |v| {
+                        v.check_local_variable_range(local_variable.start_pc, local_variable.length, instructions);
+                        v.expect_utf8(local_variable.name_index);
+                        v.expect_utf8(local_variable.signature_index);
+                    });
+                }
+            },
+            AttributeInfo::RuntimeVisibleAnnotations { annotations, .. } |
+            AttributeInfo::RuntimeInvisibleAnnotations { annotations, .. } => {
+                for (n, annotation) in annotations.iter().enumerate() {
+                    self.within(format!("annotations[{}]", n), |v| v.validate_annotation(annotation));
+                }
+            },
+            AttributeInfo::RuntimeVisibleParameterAnnotations { parameter_annotations, .. } |
+            AttributeInfo::RuntimeInvisibleParameterAnnotations { parameter_annotations, .. } => {
+                for (n, annotations) in parameter_annotations.iter().enumerate() {
+                    for (m, annotation) in annotations.iter().enumerate() {
+                        self.within(format!("parameter_annotations[{}][{}]", n, m), |v| v.validate_annotation(annotation));
+                    }
+                }
+            },
+            AttributeInfo::RuntimeVisibleTypeAnnotations { annotations, .. } |
+            AttributeInfo::RuntimeInvisibleTypeAnnotations { annotations, .. } => {
+                for (n, type_annotation) in annotations.iter().enumerate() {
+                    self.within(format!("annotations[{}]", n), |v| v.validate_annotation(&type_annotation.annotation));
+                }
+            },
+            AttributeInfo::AnnotationDefault { default_value, .. } =>
+                self.within("default_value", |v| v.validate_element_value(default_value)),
+            AttributeInfo::BootstrapMethods { bootstrap_methods, .. } => {
+                for (n, bootstrap_method) in bootstrap_methods.iter().enumerate() {
+                    self.within(format!("bootstrap_methods[{}]", n), |v| {
+                        v.expect(bootstrap_method.bootstrap_method_ref, "CONSTANT_MethodHandle", |c| matches!(c, CpInfo::ConstantMethodHandle { .. }));
+                        for argument in bootstrap_method.bootstrap_arguments.iter() {
+                            v.expect(*argument, "loadable constant", is_loadable);
+                        }
+                    });
+                }
+            },
+            AttributeInfo::MethodParameters { parameters, .. } => {
+                for (n, parameter) in parameters.iter().enumerate() {
+                    if parameter.name_index != 0 {
+                        self.within(format!("parameters[{}]", n), |v| {
+                            if let Some(name) = v.expect_utf8(parameter.name_index) {
+                                v.check_name(name, is_unqualified_name(name));
+                            }
+                        });
+                    }
+                }
+            },
+            AttributeInfo::Module { module_name_index, module_version_index, requires, exports, opens, uses_index, provides, .. } => {
+                self.expect_module(*module_name_index);
+                if *module_version_index != 0 {
+                    self.expect_utf8(*module_version_index);
+                }
+                for (n, module_requires) in requires.iter().enumerate() {
+                    self.within(format!("requires[{}]", n), |v| {
+                        v.expect_module(module_requires.requires_index);
+                        if module_requires.requires_version_index != 0 {
+                            v.expect_utf8(module_requires.requires_version_index);
+                        }
+                    });
+                }
+                for (n, module_exports) in exports.iter().enumerate() {
+                    self.within(format!("exports[{}]", n), |v| {
+                        v.expect_package(module_exports.exports_index);
+                        module_exports.exports_to_index.iter().for_each(|index| { v.expect_module(*index); });
+                    });
+                }
+                for (n, module_opens) in opens.iter().enumerate() {
+                    self.within(format!("opens[{}]", n), |v| {
+                        v.expect_package(module_opens.opens_index);
+                        module_opens.opens_to_index.iter().for_each(|index| { v.expect_module(*index); });
+                    });
+                }
+                for (n, uses) in uses_index.iter().enumerate() {
+                    self.within(format!("uses_index[{}]", n), |v| {
+                        v.expect_class(*uses);
+                    });
+                }
+                for (n, module_provides) in provides.iter().enumerate() {
+                    self.within(format!("provides[{}]", n), |v| {
+                        v.expect_class(module_provides.provides_index);
+                        module_provides.provides_with_index.iter().for_each(|index| { v.expect_class(*index); });
+                    });
+                }
+            },
+            AttributeInfo::ModulePackages { package_index, .. } => {
+                for (n, package) in package_index.iter().enumerate() {
+                    self.within(format!("package_index[{}]", n), |v| {
+                        v.expect_package(*package);
+                    });
+                }
+            },
+            AttributeInfo::ModuleMainClass { main_class_index: index, .. } |
+            AttributeInfo::NestHost { host_class_index: index, .. } => {
+                self.expect_class(*index);
+            },
+            AttributeInfo::NestMembers { classes, .. } |
+            AttributeInfo::PermittedSubclasses { classes, .. } => {
+                for (n, class) in classes.iter().enumerate() {
+                    self.within(format!("classes[{}]", n), |v| {
+                        v.expect_class(*class);
+                    });
+                }
+            },
+            AttributeInfo::Record { components, .. } => {
+                for (n, component) in components.iter().enumerate() {
+                    self.within(format!("components[{}]", n), |v| {
+                        if let Some(name) = v.expect_utf8(component.name_index) {
+                            v.check_name(name, is_unqualified_name(name));
+                        }
+                        if let Some(descriptor) = v.expect_utf8(component.descriptor_index) {
+                            v.check_field_descriptor(descriptor);
+                        }
+                        v.validate_attributes(&component.attributes, None);
+                    });
+                }
+            },
+            // The constant of a ConstantValue is checked with the type of
+            // its field.
+            AttributeInfo::ConstantValue { .. } | AttributeInfo::Synthetic { .. } | AttributeInfo::Deprecated { .. } |
+            AttributeInfo::SourceDebugExtension { .. } | AttributeInfo::Unknown { .. } | AttributeInfo::Custom { .. } => ()
+        }
+    }
+
+    fn validate_code(&mut self, code: &[u8], exception_table: &[ExceptionTable], attributes: &'c [AttributeInfo<'a>]) {
+        if code.is_empty() {
+            self.report(DiagnosticKind::InvalidCode(String::from("code_length is 0")));
+        } else if code.len() > MAX_CODE_LENGTH {
+            self.report(DiagnosticKind::InvalidCode(format!("code_length {} exceeds {}", code.len(), MAX_CODE_LENGTH)));
+        }
+        let decoded = match decode_instructions(code) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                self.report(DiagnosticKind::InvalidCode(e.to_string()));
+                self.validate_attributes(attributes, None);
+                return;
+            }
+        };
+        let mut instructions = vec![false; code.len()];
+        for (pc, _) in decoded.iter() {
+            instructions[*pc as usize] = true;
+        }
+        for (pc, instruction) in decoded.iter() {
+            self.within(format!("code[{}]", pc), |v| {
+                for target in branch_targets(&instruction.operand) {
+                    if !is_instruction(target, &instructions) {
+                        v.report(DiagnosticKind::InvalidBranchTarget(target));
+                    }
+                }
+                v.validate_instruction_constant(instruction);
+            });
+        }
+        for (n, exception) in exception_table.iter().enumerate() {
+            self.within(format!("exception_table[{}]", n), |v| {
+                let (start_pc, end_pc) = (exception.start_pc as u32, exception.end_pc as u32);
+                if start_pc >= end_pc {
+                    v.report(DiagnosticKind::InvalidCodeRange { start_pc, end_pc });
+                } else {
+                    v.check_code_range(start_pc, end_pc, &instructions);
+                }
+                if !is_instruction(exception.handler_pc as u32, &instructions) {
+                    v.report(DiagnosticKind::InvalidPc(exception.handler_pc as u32));
+                }
+                if exception.catch_type != 0 {
+                    v.expect_class(exception.catch_type);
+                }
+            });
+        }
+        self.validate_attributes(attributes, Some(&instructions));
+    }
+
+    /// Constant pool entries referenced by instructions must have the kind
+    /// the instruction operates on.
+    fn validate_instruction_constant(&mut self, instruction: &Instruction) {
+        let major_version = self.class_file.major_version;
+        match (&instruction.opcode, &instruction.operand) {
+            (Opcode::Ldc | Opcode::LdcW, Operand::ConstantPoolIndex(index)) => {
+                self.expect(*index, "loadable constant", |c| is_loadable(c) && !matches!(c, CpInfo::ConstantLong { .. } | CpInfo::ConstantDouble { .. }));
+            },
+            (Opcode::Ldc2W, Operand::ConstantPoolIndex(index)) => {
+                self.expect(*index, "CONSTANT_Long, CONSTANT_Double or CONSTANT_Dynamic",
+                            |c| matches!(c, CpInfo::ConstantLong { .. } | CpInfo::ConstantDouble { .. } | CpInfo::ConstantDynamic { .. }));
+            },
+            (Opcode::Getstatic | Opcode::Putstatic | Opcode::Getfield | Opcode::Putfield, Operand::ConstantPoolIndex(index)) => {
+                self.expect(*index, "CONSTANT_Fieldref", |c| matches!(c, CpInfo::ConstantFieldref { .. }));
+            },
+            (Opcode::Invokespecial | Opcode::Invokestatic, Operand::ConstantPoolIndex(index)) if major_version >= 52 => {
+                self.expect(*index, "CONSTANT_Methodref or CONSTANT_InterfaceMethodref",
+                            |c| matches!(c, CpInfo::ConstantMethodref { .. } | CpInfo::ConstantInterfaceMethodref { .. }));
+            },
+            (Opcode::Invokevirtual | Opcode::Invokespecial | Opcode::Invokestatic, Operand::ConstantPoolIndex(index)) => {
+                self.expect(*index, "CONSTANT_Methodref", |c| matches!(c, CpInfo::ConstantMethodref { .. }));
+            },
+            (Opcode::Invokeinterface, Operand::InvokeInterface { index, .. }) => {
+                self.expect(*index, "CONSTANT_InterfaceMethodref", |c| matches!(c, CpInfo::ConstantInterfaceMethodref { .. }));
+            },
+            (Opcode::Invokedynamic, Operand::ConstantPoolIndex(index)) => {
+                self.expect(*index, "CONSTANT_InvokeDynamic", |c| matches!(c, CpInfo::ConstantInvokeDynamic { .. }));
+            },
+            (Opcode::New | Opcode::Anewarray | Opcode::Checkcast | Opcode::Instanceof, Operand::ConstantPoolIndex(index)) |
+            (Opcode::Multianewarray, Operand::MultiANewArray { index, .. }) => {
+                self.expect_class(*index);
+            },
+            _ => ()
+        }
+    }
+
+    fn validate_stack_map_frame(&mut self, frame: &StackMapFrame, instructions: Option<&[bool]>) {
+        let verification_types: Vec<&VerificationTypeInfo> = match frame {
+            StackMapFrame::SameLocals1StackItemFrame { stack, .. } |
+            StackMapFrame::SameLocals1StackItemFrameExtended { stack, .. } => vec![stack],
+            StackMapFrame::AppendFrame { locals, .. } => locals.iter().collect(),
+            StackMapFrame::FullFrame { locals, stack, .. } => locals.iter().chain(stack.iter()).collect(),
+            _ => Vec::new()
+        };
+        for verification_type in verification_types {
+            match verification_type {
+                VerificationTypeInfo::Object { cpool_index } => {
+                    self.expect_class(*cpool_index);
+                },
+                VerificationTypeInfo::Uninitialized { offset } if instructions.is_some_and(|instructions| !is_instruction(*offset as u32, instructions)) =>
+                    self.report(DiagnosticKind::InvalidPc(*offset as u32)),
+                _ => ()
+            }
+        }
+    }
+
+    fn validate_annotation(&mut self, annotation: &Annotation) {
+        if let Some(descriptor) = self.expect_utf8(annotation.type_index) {
+            self.check_field_descriptor(descriptor);
+        }
+        for element_value_pair in annotation.element_value_pairs.iter() {
+            self.expect_utf8(element_value_pair.element_name_index);
+            self.validate_element_value(&element_value_pair.value);
+        }
+    }
+
+    fn validate_element_value(&mut self, element_value: &ElementValue) {
+        match element_value {
+            ElementValue::ConstValue { tag, const_value_index } => {
+                let index = *const_value_index;
+                match tag {
+                    b'D' => self.expect(index, "CONSTANT_Double", |c| matches!(c, CpInfo::ConstantDouble { .. })),
+                    b'F' => self.expect(index, "CONSTANT_Float", |c| matches!(c, CpInfo::ConstantFloat { .. })),
+                    b'J' => self.expect(index, "CONSTANT_Long", |c| matches!(c, CpInfo::ConstantLong { .. })),
+                    b's' => self.expect_utf8(index).is_some(),
+                    _ => self.expect(index, "CONSTANT_Integer", |c| matches!(c, CpInfo::ConstantInteger { .. }))
+                };
+            },
+            ElementValue::EnumConstValue { type_name_index, const_name_index } => {
+                if let Some(descriptor) = self.expect_utf8(*type_name_index) {
+                    self.check_field_descriptor(descriptor);
+                }
+                self.expect_utf8(*const_name_index);
+            },
+            ElementValue::ClassInfo { class_info_index } => {
+                match self.expect_utf8(*class_info_index) {
+                    Some("V") | None => (),
+                    Some(descriptor) => {
+                        self.check_field_descriptor(descriptor);
+                    }
+                }
+            },
+            ElementValue::AnnotationValue { annotation_value } => self.validate_annotation(annotation_value),
+            ElementValue::ArrayValue { num_values: _num_values, values } => {
+                for value in values.iter() {
+                    self.validate_element_value(value);
+                }
+            }
+        }
+    }
+
+    /// The local variable lives from an instruction to another instruction
+    /// or the end of the code.
+    fn check_local_variable_range(&mut self, start_pc: u16, length: u16, instructions: Option<&[bool]>) {
+        if let Some(instructions) = instructions {
+            let start_pc = start_pc as u32;
+            self.check_code_range(start_pc, start_pc + length as u32, instructions);
+        }
+    }
+
+    fn check_code_range(&mut self, start_pc: u32, end_pc: u32, instructions: &[bool]) {
+        if start_pc > end_pc || end_pc as usize > instructions.len() {
+            self.report(DiagnosticKind::InvalidCodeRange { start_pc, end_pc });
+            return;
+        }
+        if !is_instruction(start_pc, instructions) {
+            self.report(DiagnosticKind::InvalidPc(start_pc));
+        }
+        if (end_pc as usize) < instructions.len() && !is_instruction(end_pc, instructions) {
+            self.report(DiagnosticKind::InvalidPc(end_pc));
+        }
+    }
+
+    fn check_name(&mut self, name: &str, is_valid: bool) {
+        if !is_valid {
+            self.report(DiagnosticKind::InvalidName(String::from(name)));
+        }
+    }
+
+    fn check_field_descriptor(&mut self, descriptor: &str) -> Option<FieldType> {
+        match parse_field_descriptor(descriptor) {
+            Ok(field_type) => Some(field_type),
+            Err(_) => {
+                self.report(DiagnosticKind::InvalidDescriptor(String::from(descriptor)));
+                None
+            }
+        }
+    }
+
+    fn check_method_descriptor(&mut self, descriptor: &str) -> Option<MethodDescriptor> {
+        match parse_method_descriptor(descriptor) {
+            Ok(method_descriptor) => Some(method_descriptor),
+            Err(_) => {
+                self.report(DiagnosticKind::InvalidDescriptor(String::from(descriptor)));
+                None
+            }
+        }
+    }
+
+    /// The constant at `index`, None when the index is not usable.
+    fn constant(&mut self, index: u16) -> Option<&'c CpInfo<'a>> {
+        let class_file = self.class_file;
+        match class_file.cp_info.get(index as usize) {
+            Some(CpInfo::Unusable) | None => {
+                self.report(DiagnosticKind::InvalidConstantPoolIndex(index));
+                None
+            },
+            Some(cp_info) => Some(cp_info)
+        }
+    }
+
+    /// Whether the constant at `index` is of the `expected` kind.
+    fn expect(&mut self, index: u16, expected: &'static str, matches: fn(&CpInfo) -> bool) -> bool {
+        match self.constant(index) {
+            Some(cp_info) if matches(cp_info) => true,
+            Some(_) => {
+                self.report(DiagnosticKind::UnexpectedConstantPoolEntry { index, expected });
+                false
+            },
+            None => false
+        }
+    }
+
+    fn expect_utf8(&mut self, index: u16) -> Option<&'c str> {
+        match self.constant(index)? {
+            CpInfo::ConstantUtf8 { tag: _tag, length: _length, bytes: _bytes, bytes_str } => Some(bytes_str),
+            _ => {
+                self.report(DiagnosticKind::UnexpectedConstantPoolEntry { index, expected: "CONSTANT_Utf8" });
+                None
+            }
+        }
+    }
+
+    fn expect_class(&mut self, index: u16) -> bool {
+        self.expect(index, "CONSTANT_Class", |c| matches!(c, CpInfo::ConstantClass { .. }))
+    }
+
+    fn expect_module(&mut self, index: u16) -> bool {
+        self.expect(index, "CONSTANT_Module", |c| matches!(c, CpInfo::ConstantModule { .. }))
+    }
+
+    fn expect_package(&mut self, index: u16) -> bool {
+        self.expect(index, "CONSTANT_Package", |c| matches!(c, CpInfo::ConstantPackage { .. }))
+    }
+
+    /// The name and descriptor of the CONSTANT_NameAndType at `index`, the
+    /// entry itself is checked with the constant pool.
+    fn expect_name_and_type(&mut self, index: u16) -> Option<(&'c str, &'c str)> {
+        if self.expect(index, "CONSTANT_NameAndType", |c| matches!(c, CpInfo::ConstantNameAndType { .. })) {
+            name_and_type_at(index, &self.class_file.cp_info)
+        } else {
+            None
+        }
+    }
+}
+
+fn class_access_flags_error(access_flags: u16) -> Option<&'static str> {
+    if access_flags & ACC_MODULE != 0 {
+        (access_flags != ACC_MODULE).then_some("ACC_MODULE with other flags")
+    } else if access_flags & ACC_INTERFACE != 0 {
+        if access_flags & ACC_ABSTRACT == 0 {
+            Some("ACC_INTERFACE without ACC_ABSTRACT")
+        } else if access_flags & (ACC_FINAL | ACC_SUPER | ACC_ENUM) != 0 {
+            Some("ACC_INTERFACE with ACC_FINAL, ACC_SUPER or ACC_ENUM")
+        } else {
+            None
+        }
+    } else if access_flags & ACC_ANNOTATION != 0 {
+        Some("ACC_ANNOTATION without ACC_INTERFACE")
+    } else if access_flags & ACC_FINAL != 0 && access_flags & ACC_ABSTRACT != 0 {
+        Some("ACC_FINAL with ACC_ABSTRACT")
+    } else {
+        None
+    }
+}
+
+fn field_access_flags_error(access_flags: u16, is_interface: bool) -> Option<&'static str> {
+    if (access_flags & (ACC_PUBLIC | ACC_PRIVATE | ACC_PROTECTED)).count_ones() > 1 {
+        Some("more than one of ACC_PUBLIC, ACC_PRIVATE and ACC_PROTECTED")
+    } else if access_flags & ACC_FINAL != 0 && access_flags & ACC_VOLATILE != 0 {
+        Some("ACC_FINAL with ACC_VOLATILE")
+    } else if is_interface && access_flags & !ACC_SYNTHETIC != ACC_PUBLIC | ACC_STATIC | ACC_FINAL {
+        Some("interface field that is not ACC_PUBLIC, ACC_STATIC and ACC_FINAL")
+    } else {
+        None
+    }
+}
+
+/// Flags of a method per JVMS 4.6, the flags of a class initialization
+/// method are ignored except ACC_STATIC.
+fn method_access_flags_error(access_flags: u16, name: &str, is_interface: bool, major_version: u16) -> Option<&'static str> {
+    if name == "<clinit>" {
+        return (major_version >= 51 && access_flags & ACC_STATIC == 0).then_some("<clinit> without ACC_STATIC");
+    }
+    if (access_flags & (ACC_PUBLIC | ACC_PRIVATE | ACC_PROTECTED)).count_ones() > 1 {
+        return Some("more than one of ACC_PUBLIC, ACC_PRIVATE and ACC_PROTECTED");
+    }
+    if name == "<init>" && access_flags & (ACC_STATIC | ACC_FINAL | ACC_SYNCHRONIZED | ACC_BRIDGE | ACC_NATIVE | ACC_ABSTRACT) != 0 {
+        return Some("<init> with ACC_STATIC, ACC_FINAL, ACC_SYNCHRONIZED, ACC_BRIDGE, ACC_NATIVE or ACC_ABSTRACT");
+    }
+    if is_interface {
+        if major_version < 52 && access_flags & (ACC_PUBLIC | ACC_ABSTRACT) != ACC_PUBLIC | ACC_ABSTRACT {
+            return Some("interface method without ACC_PUBLIC and ACC_ABSTRACT");
+        }
+        if major_version >= 52 && (access_flags & (ACC_PUBLIC | ACC_PRIVATE)).count_ones() != 1 {
+            return Some("interface method without exactly one of ACC_PUBLIC and ACC_PRIVATE");
+        }
+        if access_flags & (ACC_PROTECTED | ACC_FINAL | ACC_SYNCHRONIZED | ACC_NATIVE) != 0 {
+            return Some("interface method with ACC_PROTECTED, ACC_FINAL, ACC_SYNCHRONIZED or ACC_NATIVE");
+        }
+    }
+    if access_flags & ACC_ABSTRACT != 0 {
+        if access_flags & (ACC_PRIVATE | ACC_STATIC | ACC_FINAL | ACC_SYNCHRONIZED | ACC_NATIVE) != 0 {
+            return Some("ACC_ABSTRACT with ACC_PRIVATE, ACC_STATIC, ACC_FINAL, ACC_SYNCHRONIZED or ACC_NATIVE");
+        }
+        // ACC_STRICT is ignored since Java 17
+        if (46..=60).contains(&major_version) && access_flags & ACC_STRICT != 0 {
+            return Some("ACC_ABSTRACT with ACC_STRICT");
+        }
+    }
+    None
+}
+
+/// Unqualified name of a field, method or local variable. Reference:
+/// https://docs.oracle.com/javase/specs/jvms/se19/html/jvms-4.html#jvms-4.2.2
+fn is_unqualified_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['.', ';', '[', '/'])
+}
+
+/// Method name other than the initialization methods.
+fn is_method_name(name: &str) -> bool {
+    is_unqualified_name(name) && !name.contains(['<', '>'])
+}
+
+/// Binary name of a class or package in internal form, ex.: java/lang/Object
+fn is_binary_name(name: &str) -> bool {
+    name.split('/').all(is_unqualified_name)
+}
+
+/// Name of a CONSTANT_Class, array classes are named by their descriptor.
+fn is_class_name(name: &str) -> bool {
+    if name.starts_with('[') {
+        parse_field_descriptor(name).is_ok()
+    } else {
+        is_binary_name(name)
+    }
+}
+
+fn is_loadable(cp_info: &CpInfo) -> bool {
+    matches!(cp_info, CpInfo::ConstantInteger { .. } | CpInfo::ConstantFloat { .. } | CpInfo::ConstantLong { .. } |
+        CpInfo::ConstantDouble { .. } | CpInfo::ConstantClass { .. } | CpInfo::ConstantString { .. } |
+        CpInfo::ConstantMethodHandle { .. } | CpInfo::ConstantMethodType { .. } | CpInfo::ConstantDynamic { .. })
+}
+
+fn is_instruction(pc: u32, instructions: &[bool]) -> bool {
+    instructions.get(pc as usize).copied().unwrap_or(false)
+}
+
+fn branch_targets(operand: &Operand) -> Vec<u32> {
+    match operand {
+        Operand::BranchTarget(target) => vec![*target],
+        Operand::TableSwitch { default, targets, .. } => iter::once(default).chain(targets.iter()).copied().collect(),
+        Operand::LookupSwitch { default, pairs } => iter::once(*default).chain(pairs.iter().map(|(_, target)| *target)).collect(),
+        _ => Vec::new()
+    }
+}
+
+/// Number of local variables taken by a parameter.
+fn field_type_size(field_type: &FieldType) -> usize {
+    match field_type {
+        FieldType::BaseType { term: FieldTypeTerm::J | FieldTypeTerm::D } => 2,
+        _ => 1
+    }
+}
+
+fn utf8_at<'c>(index: u16, cp_info: &'c [CpInfo]) -> Option<&'c str> {
+    match cp_info.get(index as usize) {
+        Some(CpInfo::ConstantUtf8 { tag: _tag, length: _length, bytes: _bytes, bytes_str }) => Some(bytes_str),
+        _ => None
+    }
+}
+
+fn class_name_at<'c>(index: u16, cp_info: &'c [CpInfo]) -> Option<&'c str> {
+    match cp_info.get(index as usize) {
+        Some(CpInfo::ConstantClass { tag: _tag, name_index }) => utf8_at(*name_index, cp_info),
+        _ => None
+    }
+}
+
+fn name_and_type_at<'c>(index: u16, cp_info: &'c [CpInfo]) -> Option<(&'c str, &'c str)> {
+    match cp_info.get(index as usize) {
+        Some(CpInfo::ConstantNameAndType { tag: _tag, name_index, descriptor_index }) =>
+            Some((utf8_at(*name_index, cp_info)?, utf8_at(*descriptor_index, cp_info)?)),
+        _ => None
+    }
+}
+
+/// Name and descriptor of a field or method.
+fn member_at<'c>(name_index: u16, descriptor_index: u16, cp_info: &'c [CpInfo]) -> Option<(&'c str, &'c str)> {
+    Some((utf8_at(name_index, cp_info)?, utf8_at(descriptor_index, cp_info)?))
+}
+
+/// Size of the `info` of a parsed attribute, what its attribute_length must
+/// be. The size of the structures that follow attribute_length.
+fn attribute_info_length(attribute_info: &AttributeInfo) -> usize {
+    match attribute_info {
+        AttributeInfo::ConstantValue { .. } => 2,
+        AttributeInfo::Code { code, exception_table, attributes, .. } =>
+            2 + 2 + 4 + code.len() + 2 + 8 * exception_table.len() + 2 + attributes_length(attributes),
+        AttributeInfo::StackMapTable { entries, .. } => 2 + entries.iter().map(stack_map_frame_length).sum::<usize>(),
+        AttributeInfo::Exceptions { exception_index_table, .. } => 2 + 2 * exception_index_table.len(),
+        AttributeInfo::InnerClasses { classes, .. } => 2 + 8 * classes.len(),
+        AttributeInfo::EnclosingMethod { .. } => 4,
+        AttributeInfo::Synthetic { .. } | AttributeInfo::Deprecated { .. } => 0,
+        AttributeInfo::SourceDebugExtension { debug_extension, .. } => debug_extension.len(),
+        AttributeInfo::Signature { .. } | AttributeInfo::SourceFile { .. } => 2,
+        AttributeInfo::LineNumberTable { line_number_tables, .. } => 2 + 4 * line_number_tables.len(),
+        AttributeInfo::LocalVariableTable { local_variable_table, .. } => 2 + 10 * local_variable_table.len(),
+        AttributeInfo::LocalVariableTypeTable { local_variable_type_table, .. } => 2 + 10 * local_variable_type_table.len(),
+        AttributeInfo::RuntimeVisibleAnnotations { annotations, .. } |
+        AttributeInfo::RuntimeInvisibleAnnotations { annotations, .. } => 2 + annotations.iter().map(annotation_length).sum::<usize>(),
+        AttributeInfo::RuntimeVisibleParameterAnnotations { parameter_annotations, .. } |
+        AttributeInfo::RuntimeInvisibleParameterAnnotations { parameter_annotations, .. } =>
+            1 + parameter_annotations.iter().map(|annotations| 2 + annotations.iter().map(annotation_length).sum::<usize>()).sum::<usize>(),
+        AttributeInfo::RuntimeVisibleTypeAnnotations { annotations, .. } |
+        AttributeInfo::RuntimeInvisibleTypeAnnotations { annotations, .. } => 2 + annotations.iter().map(type_annotation_length).sum::<usize>(),
+        AttributeInfo::AnnotationDefault { default_value, .. } => element_value_length(default_value),
+        AttributeInfo::BootstrapMethods { bootstrap_methods, .. } =>
+            2 + bootstrap_methods.iter().map(|bootstrap_method| 4 + 2 * bootstrap_method.bootstrap_arguments.len()).sum::<usize>(),
+        AttributeInfo::MethodParameters { parameters, .. } => 1 + 4 * parameters.len(),
+        AttributeInfo::Module { requires, exports, opens, uses_index, provides, .. } =>
+            6 + 2 + 6 * requires.len()
+                + 2 + exports.iter().map(|module_exports| 6 + 2 * module_exports.exports_to_index.len()).sum::<usize>()
+                + 2 + opens.iter().map(|module_opens| 6 + 2 * module_opens.opens_to_index.len()).sum::<usize>()
+                + 2 + 2 * uses_index.len()
+                + 2 + provides.iter().map(|module_provides| 4 + 2 * module_provides.provides_with_index.len()).sum::<usize>(),
+        AttributeInfo::ModulePackages { package_index, .. } => 2 + 2 * package_index.len(),
+        AttributeInfo::ModuleMainClass { .. } | AttributeInfo::NestHost { .. } => 2,
+        AttributeInfo::NestMembers { classes, .. } | AttributeInfo::PermittedSubclasses { classes, .. } => 2 + 2 * classes.len(),
+        AttributeInfo::Record { components, .. } =>
+            2 + components.iter().map(|component| 6 + attributes_length(&component.attributes)).sum::<usize>(),
+        AttributeInfo::Unknown { info, .. } | AttributeInfo::Custom { info, .. } => info.len()
+    }
+}
+
+/// Size of attribute_info structures, with their name and length.
+fn attributes_length(attributes: &[AttributeInfo]) -> usize {
+    attributes.iter().map(|attribute_info| 6 + attribute_info_length(attribute_info)).sum()
+}
+
+fn stack_map_frame_length(frame: &StackMapFrame) -> usize {
+    match frame {
+        StackMapFrame::SameFrame { .. } => 1,
+        StackMapFrame::SameLocals1StackItemFrame { stack, .. } => 1 + verification_type_info_length(stack),
+        StackMapFrame::SameLocals1StackItemFrameExtended { stack, .. } => 3 + verification_type_info_length(stack),
+        StackMapFrame::ChopFrame { .. } | StackMapFrame::SameFrameExtended { .. } => 3,
+        StackMapFrame::AppendFrame { locals, .. } => 3 + locals.iter().map(verification_type_info_length).sum::<usize>(),
+        StackMapFrame::FullFrame { locals, stack, .. } =>
+            3 + 2 + locals.iter().map(verification_type_info_length).sum::<usize>() + 2 + stack.iter().map(verification_type_info_length).sum::<usize>()
+    }
+}
+
+fn verification_type_info_length(verification_type_info: &VerificationTypeInfo) -> usize {
+    match verification_type_info {
+        VerificationTypeInfo::Object { .. } | VerificationTypeInfo::Uninitialized { .. } => 3,
+        _ => 1
+    }
+}
+
+fn annotation_length(annotation: &Annotation) -> usize {
+    2 + 2 + annotation.element_value_pairs.iter().map(|pair| 2 + element_value_length(&pair.value)).sum::<usize>()
+}
+
+fn element_value_length(element_value: &ElementValue) -> usize {
+    1 + match element_value {
+        ElementValue::ConstValue { .. } | ElementValue::ClassInfo { .. } => 2,
+        ElementValue::EnumConstValue { .. } => 4,
+        ElementValue::AnnotationValue { annotation_value } => annotation_length(annotation_value),
+        ElementValue::ArrayValue { num_values: _num_values, values } => 2 + values.iter().map(element_value_length).sum::<usize>()
+    }
+}
+
+fn type_annotation_length(type_annotation: &TypeAnnotation) -> usize {
+    let target_info_length = match &type_annotation.target_info {
+        TargetInfo::Empty => 0,
+        TargetInfo::TypeParameter { .. } | TargetInfo::FormalParameter { .. } => 1,
+        TargetInfo::Supertype { .. } | TargetInfo::TypeParameterBound { .. } | TargetInfo::Throws { .. } |
+        TargetInfo::Catch { .. } | TargetInfo::Offset { .. } => 2,
+        TargetInfo::TypeArgument { .. } => 3,
+        TargetInfo::Localvar { table, .. } => 2 + 6 * table.len()
+    };
+    1 + target_info_length + 1 + 2 * type_annotation.type_path.len() + annotation_length(&type_annotation.annotation)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use super::*;
+    use crate::parser::parse_class_file;
+
+    /// public class A {
+    ///     public static final int f = 1;
+    ///     public static void m() { m(); }
+    /// }
+    /// with a CONSTANT_Long and without the constructor.
+    fn class_file_bytes() -> Vec<u8> {
+        let mut bytes: Vec<u8> = vec![0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x34, 0x00, 0x12];
+        let utf8 = |bytes: &mut Vec<u8>, str: &str| {
+            bytes.extend([1, 0, str.len() as u8]);
+            bytes.extend(str.as_bytes());
+        };
+        bytes.extend([7, 0, 2]);
+        utf8(&mut bytes, "A");
+        bytes.extend([7, 0, 4]);
+        utf8(&mut bytes, "java/lang/Object");
+        utf8(&mut bytes, "Code");
+        utf8(&mut bytes, "m");
+        utf8(&mut bytes, "()V");
+        utf8(&mut bytes, "f");
+        utf8(&mut bytes, "I");
+        bytes.extend([3, 0, 0, 0, 1]);
+        utf8(&mut bytes, "ConstantValue");
+        bytes.extend([5, 0, 0, 0, 0, 0, 0, 0, 5]);
+        utf8(&mut bytes, "SourceFile");
+        utf8(&mut bytes, "A.java");
+        bytes.extend([12, 0, 6, 0, 7]);
+        bytes.extend([10, 0, 1, 0, 16]);
+        // access_flags, this_class, super_class and interfaces_count
+        bytes.extend([0x00, 0x21, 0x00, 0x01, 0x00, 0x03, 0x00, 0x00]);
+        // The field and its ConstantValue
+        bytes.extend([0x00, 0x01, 0x00, 0x19, 0x00, 0x08, 0x00, 0x09, 0x00, 0x01]);
+        bytes.extend([0x00, 0x0B, 0x00, 0x00, 0x00, 0x02, 0x00, 0x0A]);
+        // The method and its Code: invokestatic #17, return
+        bytes.extend([0x00, 0x01, 0x00, 0x09, 0x00, 0x06, 0x00, 0x07, 0x00, 0x01]);
+        bytes.extend([0x00, 0x05, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04]);
+        bytes.extend([0xB8, 0x00, 0x11, 0xB1, 0x00, 0x00, 0x00, 0x00]);
+        // The SourceFile
+        bytes.extend([0x00, 0x01, 0x00, 0x0E, 0x00, 0x00, 0x00, 0x02, 0x00, 0x0F]);
+        bytes
+    }
+
+    fn diagnostic(path: &str, kind: DiagnosticKind) -> Diagnostic {
+        let path = if path.is_empty() { Vec::new() } else { path.split('.').map(String::from).collect() };
+        Diagnostic { path, kind }
+    }
+
+    /// Validate the fixture changed by `f`.
+    fn validate_with<F: FnOnce(&mut ClassFile)>(f: F) -> Vec<Diagnostic> {
+        let bytes = class_file_bytes();
+        let mut class_file = parse_class_file(&bytes).unwrap();
+        f(&mut class_file);
+        validate(&class_file)
+    }
+
+    /// Replace the code and exception table of the method, with consistent lengths.
+    fn set_code(class_file: &mut ClassFile, new_code: Vec<u8>, new_exception_table: Vec<ExceptionTable>) {
+        match &mut class_file.methods[0].attributes[0] {
+            AttributeInfo::Code { attribute_length, code_length, code, exception_table_length, exception_table, .. } => {
+                *attribute_length = 12 + new_code.len() as u32 + 8 * new_exception_table.len() as u32;
+                *code_length = new_code.len() as u32;
+                *code = Cow::Owned(new_code);
+                *exception_table_length = new_exception_table.len() as u16;
+                *exception_table = new_exception_table;
+            }
+            attribute => panic!("Expected AttributeInfo::Code, found {:?}", attribute)
+        }
+    }
+
+    fn utf8(str: &'static str) -> CpInfo<'static> {
+        CpInfo::ConstantUtf8 { tag: 1, length: str.len() as u16, bytes: Cow::Borrowed(str.as_bytes()), bytes_str: Cow::Borrowed(str) }
+    }
+
+    #[test]
+    fn valid_class_file() {
+        assert_eq!(validate_with(|_| ()), vec![]);
+    }
+
+    #[test]
+    fn invalid_magic() {
+        let diagnostics = validate_with(|class_file| class_file.magic = 0xCAFEBABA);
+        assert_eq!(diagnostics, vec![diagnostic("magic", DiagnosticKind::InvalidMagic(0xCAFEBABA))]);
+        assert_eq!(diagnostics[0].to_string(), "invalid magic 0xCAFEBABA in magic");
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let kind = DiagnosticKind::UnsupportedVersion { major_version: 64, minor_version: 0 };
+        assert_eq!(validate_with(|class_file| class_file.major_version = 64), vec![diagnostic("major_version", kind)]);
+    }
+
+    #[test]
+    fn invalid_constant_pool_index() {
+        let diagnostics = validate_with(|class_file| {
+            class_file.cp_info[17] = CpInfo::ConstantMethodref { tag: 10, class_index: 13, name_and_type_index: 16 };
+        });
+        // 13 is the unusable entry after the CONSTANT_Long
+        assert_eq!(diagnostics, vec![diagnostic("constant_pool[17]", DiagnosticKind::InvalidConstantPoolIndex(13))]);
+    }
+
+    #[test]
+    fn unexpected_constant_pool_entry() {
+        let kind = DiagnosticKind::UnexpectedConstantPoolEntry { index: 2, expected: "CONSTANT_Class" };
+        assert_eq!(validate_with(|class_file| class_file.this_class = 2), vec![diagnostic("this_class", kind)]);
+    }
+
+    #[test]
+    fn invalid_constant() {
+        let diagnostics = validate_with(|class_file| {
+            class_file.cp_info.push(CpInfo::ConstantMethodHandle { tag: 15, reference_kind: 10, reference_index: 17 });
+        });
+        let kind = DiagnosticKind::InvalidConstant(String::from("invalid reference_kind 10"));
+        assert_eq!(diagnostics, vec![diagnostic("constant_pool[18]", kind)]);
+    }
+
+    #[test]
+    fn invalid_bootstrap_method_index() {
+        let diagnostics = validate_with(|class_file| {
+            class_file.cp_info.push(CpInfo::ConstantInvokeDynamic { tag: 18, bootstrap_method_attr_index: 0, name_and_type_index: 16 });
+        });
+        assert_eq!(diagnostics, vec![diagnostic("constant_pool[18]", DiagnosticKind::InvalidBootstrapMethodIndex(0))]);
+    }
+
+    #[test]
+    fn invalid_name() {
+        let diagnostics = validate_with(|class_file| class_file.cp_info[2] = utf8("p.A"));
+        assert_eq!(diagnostics, vec![diagnostic("constant_pool[1]", DiagnosticKind::InvalidName(String::from("p.A")))]);
+    }
+
+    #[test]
+    fn invalid_descriptor() {
+        let diagnostics = validate_with(|class_file| class_file.cp_info[9] = utf8("X"));
+        assert_eq!(diagnostics, vec![diagnostic("fields[0].descriptor_index", DiagnosticKind::InvalidDescriptor(String::from("X")))]);
+    }
+
+    #[test]
+    fn invalid_super_class() {
+        let diagnostics = validate_with(|class_file| {
+            class_file.access_flags = ACC_PUBLIC | ACC_INTERFACE | ACC_ABSTRACT;
+            class_file.super_class = 1;
+        });
+        assert_eq!(diagnostics, vec![diagnostic("super_class", DiagnosticKind::InvalidSuperClass(String::from("A")))]);
+    }
+
+    #[test]
+    fn illegal_access_flags() {
+        let diagnostics = validate_with(|class_file| class_file.fields[0].access_flags = ACC_PUBLIC | ACC_PRIVATE | ACC_STATIC);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].structure(), "fields[0].access_flags");
+        assert!(matches!(diagnostics[0].kind, DiagnosticKind::IllegalAccessFlags { flags: 0x000B, .. }));
+    }
+
+    #[test]
+    fn duplicate_field() {
+        let diagnostics = validate_with(|class_file| {
+            class_file.fields.push(FieldInfo { access_flags: ACC_STATIC, name_index: 8, descriptor_index: 9, attributes_count: 0, attributes: Vec::new() });
+        });
+        let kind = DiagnosticKind::DuplicateField { name: String::from("f"), descriptor: String::from("I") };
+        assert_eq!(diagnostics, vec![diagnostic("fields[1]", kind)]);
+    }
+
+    #[test]
+    fn duplicate_method() {
+        let diagnostics = validate_with(|class_file| {
+            let access_flags = ACC_PUBLIC | ACC_STATIC | ACC_NATIVE;
+            class_file.methods.push(MethodInfo { access_flags, name_index: 6, descriptor_index: 7, attributes_count: 0, attributes: Vec::new() });
+        });
+        let kind = DiagnosticKind::DuplicateMethod { name: String::from("m"), descriptor: String::from("()V") };
+        assert_eq!(diagnostics, vec![diagnostic("methods[1]", kind)]);
+    }
+
+    #[test]
+    fn duplicate_attribute() {
+        let diagnostics = validate_with(|class_file| {
+            class_file.attributes.push(AttributeInfo::SourceFile { attribute_name_index: 14, attribute_length: 2, sourcefile_index: 15 });
+        });
+        let kind = DiagnosticKind::DuplicateAttribute(String::from("SourceFile"));
+        assert_eq!(diagnostics, vec![diagnostic("attributes[1].SourceFile", kind)]);
+    }
+
+    #[test]
+    fn attribute_length_mismatch() {
+        // A SourceFile with 2 more bytes than its sourcefile_index
+        let mut bytes = class_file_bytes();
+        let attribute_length_idx = bytes.len() - 3;
+        bytes[attribute_length_idx] = 4;
+        bytes.extend([0xCA, 0xFE]);
+        let class_file = parse_class_file(&bytes).unwrap();
+        let kind = DiagnosticKind::AttributeLengthMismatch { attribute_length: 4, actual: 2 };
+        assert_eq!(validate(&class_file), vec![diagnostic("attributes[0].SourceFile", kind)]);
+    }
+
+    #[test]
+    fn missing_code() {
+        let diagnostics = validate_with(|class_file| class_file.methods[0].attributes.clear());
+        assert_eq!(diagnostics, vec![diagnostic("methods[0]", DiagnosticKind::MissingCode)]);
+    }
+
+    #[test]
+    fn unexpected_code() {
+        let diagnostics = validate_with(|class_file| class_file.methods[0].access_flags |= ACC_NATIVE);
+        assert_eq!(diagnostics, vec![diagnostic("methods[0]", DiagnosticKind::UnexpectedCode)]);
+    }
+
+    #[test]
+    fn invalid_code() {
+        let diagnostics = validate_with(|class_file| set_code(class_file, Vec::new(), Vec::new()));
+        let kind = DiagnosticKind::InvalidCode(String::from("code_length is 0"));
+        assert_eq!(diagnostics, vec![diagnostic("methods[0].attributes[0].Code", kind)]);
+    }
+
+    #[test]
+    fn invalid_branch_target() {
+        // goto 2, in the middle of the goto
+        let diagnostics = validate_with(|class_file| set_code(class_file, vec![0xA7, 0x00, 0x02, 0xB1], Vec::new()));
+        assert_eq!(diagnostics, vec![diagnostic("methods[0].attributes[0].Code.code[0]", DiagnosticKind::InvalidBranchTarget(2))]);
+    }
+
+    #[test]
+    fn invalid_pc() {
+        let diagnostics = validate_with(|class_file| {
+            let exception_table = vec![ExceptionTable { start_pc: 0, end_pc: 3, handler_pc: 2, catch_type: 0 }];
+            set_code(class_file, vec![0xB8, 0x00, 0x11, 0xB1], exception_table);
+        });
+        assert_eq!(diagnostics, vec![diagnostic("methods[0].attributes[0].Code.exception_table[0]", DiagnosticKind::InvalidPc(2))]);
+    }
+
+    #[test]
+    fn invalid_code_range() {
+        let diagnostics = validate_with(|class_file| {
+            let exception_table = vec![ExceptionTable { start_pc: 3, end_pc: 3, handler_pc: 0, catch_type: 0 }];
+            set_code(class_file, vec![0xB8, 0x00, 0x11, 0xB1], exception_table);
+        });
+        let kind = DiagnosticKind::InvalidCodeRange { start_pc: 3, end_pc: 3 };
+        assert_eq!(diagnostics, vec![diagnostic("methods[0].attributes[0].Code.exception_table[0]", kind)]);
+    }
+}
@@ -1,43 +1,61 @@
 #![allow(dead_code)]
 
-use crate::parser::{get_u1, get_u2};
-use crate::parser::access_flags::{ClassAccessFlag, MethodAccessFlag};
-use crate::parser::access_flags::ClassAccessFlag::AccSuper;
-use crate::parser::ast::{AttributeInfo, ClassFile, CpInfo, MethodInfo};
-use crate::parser::opcodes::{get_opcode, Opcode};
-use crate::parser::parser_helper::{get_constant_class_name, get_constant_utf8, get_name, get_name_quoted, get_type, method_arguments_count, method_info_return_type, parse_field_types, parse_method_arguments, return_descriptor_to_java_code};
-use crate::pretty_print_helper::{get_constant_method_ref_description, get_ldc_description, get_static_description};
-
-pub fn pretty_print_text(class_file: &ClassFile) {
-    let this_class_name = get_constant_class_name(class_file.this_class, &class_file.cp_info);
+use crate::parser::access_flags::{ClassAccessFlag, FieldAccessFlag, InnerClassAccessFlag, MethodAccessFlag, MethodParameterAccessFlag, ModuleAccessFlag};
+use crate::parser::ast::{type_parameters_str_java, Annotation, AttributeInfo, ClassFile, CpInfo, ElementValue, ExceptionTable, FieldInfo, InnerClass, MethodInfo, MethodParameter, RecordComponentInfo, StackMapFrame, TargetInfo, TypeAnnotation, TypePathKind, VerificationTypeInfo};
+use crate::parser::descriptor::{parse_field_descriptor, parse_method_descriptor};
+use crate::parser::error::ClassFormatError;
+use crate::parser::modified_utf8::decode_modified_utf8;
+use crate::parser::opcodes::{decode_instructions, Instruction, Opcode, OpcodeCategory, Operand};
+use crate::parser::parser_helper::{get_catch_type_name, get_constant_class_name, get_constant_module_name, get_constant_package_name, get_constant_utf8, get_inner_class, get_java_class_name, get_name, get_name_quoted, get_type, method_arguments_count};
+use crate::parser::signature::{parse_class_signature, parse_field_signature, parse_method_signature};
+use crate::pretty_print_helper::{get_bootstrap_argument_description, get_constant_member_ref_description, get_constant_method_handle_description, get_constant_utf8_description, get_constant_value_description, get_element_value_const_description, get_instruction_constant_description, get_invoke_dynamic_annotation, java_double_to_string, java_float_to_string};
+
+/// Optional output on top of javap's.
+#[derive(Debug, Default, Clone)]
+pub struct PrettyPrintOptions {
+    /// Follow invokedynamic instructions with a description of the lambda,
+    /// method reference or string concatenation they create.
+    pub annotate_invokedynamic: bool,
+    /// Comment loads, stores and iinc with the name and type of the local
+    /// variable they access, from the LocalVariableTable.
+    pub annotate_local_variables: bool,
+}
+
+pub fn pretty_print_text(class_file: &ClassFile, options: &PrettyPrintOptions) -> Result<(), ClassFormatError> {
     let this_class = format!("this_class: #{}", class_file.this_class);
+    let this_class = format!("{:<40}// {}", this_class, get_name_quoted(get_constant_class_name(class_file.this_class, &class_file.cp_info)));
+    // module-info has no superclass
     let super_class = format!("super_class: #{}", class_file.super_class);
+    let super_class = if class_file.super_class == 0 {
+        super_class
+    } else {
+        format!("{:<40}// {}", super_class, get_name_quoted(get_constant_class_name(class_file.super_class, &class_file.cp_info)))
+    };
 
     let access_flags: Vec<ClassAccessFlag> = ClassAccessFlag::parse_flags(class_file.access_flags);
-    let this_class_access_flags: Vec<ClassAccessFlag> = access_flags.iter().filter(|&class_access_flag| class_access_flag != &AccSuper).cloned().collect();
-    let this_class_access_flags: String = this_class_access_flags.iter().map(|f| f.to_java_code()).collect();
     let class_access_flags: Vec<&str> = access_flags.iter().map(|f| f.to_str()).collect();
     let class_access_flags: String = class_access_flags.join(", ");
+    let class_declaration = match module_attribute(class_file) {
+        Some(module) => module_declaration(module, class_file),
+        None => class_declaration(class_file)?
+    };
 
     println!("Classfile TODO");
-    println!("{} class {}\n  \
+    println!("{}\n  \
       minor version: {:x}\n  \
       major version: {}\n  \
       flags: ({:#06x}) {}\n  \
-      {:<40}// {}\n  \
-      {:<40}// {}\n  \
+      {}\n  \
+      {}\n  \
       interfaces: {}, fields: {}, methods: {}, attributes: {}\
       ",
-        this_class_access_flags,
-        this_class_name,
+        class_declaration,
         class_file.minor_version,
         class_file.major_version,
         class_file.access_flags,
         class_access_flags,
         this_class,
-        get_constant_class_name(class_file.this_class, &class_file.cp_info),
         super_class,
-        get_constant_class_name(class_file.super_class, &class_file.cp_info),
         class_file.interfaces_count,
         class_file.fields_count,
         class_file.methods_count,
@@ -45,9 +63,8 @@ pub fn pretty_print_text(class_file: &ClassFile) {
     );
 
     println!("Constant pool:");
-    for (idx, _item) in class_file.cp_info.iter().enumerate() {
-        if idx != 0 {
-            let line = cp_info_to_string(idx, &class_file.cp_info);
+    for idx in 0..class_file.cp_info.len() {
+        if let Some(line) = cp_info_to_string(idx, &class_file.cp_info) {
             println!("{}", line);
         }
     }
@@ -61,141 +78,1090 @@ pub fn pretty_print_text(class_file: &ClassFile) {
     println!();
     */
 
+    let mut members: Vec<String> = Vec::with_capacity(class_file.fields.len() + class_file.methods.len());
+    for (n, field_info) in class_file.fields.iter().enumerate() {
+        members.push(field_info_to_string(field_info, class_file).map_err(|e| e.within(format!("fields[{}]", n)))?);
+    }
+    for (n, method_info) in class_file.methods.iter().enumerate() {
+        members.push(method_info_to_string(method_info, class_file, options).map_err(|e| e.within(format!("methods[{}]", n)))?);
+    }
+
     println!("{{");
-    let mut it = class_file.methods.iter().peekable();
-    while let Some(method_info) = it.next() {
-        let method_str = method_info_to_string(method_info, &class_file);
+    // The body of a module is its directives
+    if let Some(module) = module_attribute(class_file) {
+        for directive in module_directives(module, class_file) {
+            println!("  {}", directive);
+        }
+    }
+    let mut it = members.iter().peekable();
+    while let Some(member_str) = it.next() {
         if it.peek().is_none() {
-            println!("  {}", method_str);
+            println!("  {}", member_str);
         } else {
-            println!("  {}\n", method_str);
+            println!("  {}\n", member_str);
         }
     }
     println!("}}");
+    for attribute in class_file.attributes.iter() {
+        println!("{}", class_attribute(attribute, class_file)?);
+    }
+    Ok(())
+}
+
+/// The Module attribute of a module-info class.
+fn module_attribute<'a>(class_file: &'a ClassFile) -> Option<&'a AttributeInfo<'a>> {
+    if !ClassAccessFlag::parse_flags(class_file.access_flags).contains(&ClassAccessFlag::AccModule) {
+        return None;
+    }
+    class_file.attributes.iter().find(|attribute| matches!(attribute, AttributeInfo::Module { .. }))
+}
+
+/// First line of the description of a module-info class.
+/// Ex.: open module foo.bar@1.2
+fn module_declaration(module: &AttributeInfo, class_file: &ClassFile) -> String {
+    if let AttributeInfo::Module { module_name_index, module_flags, module_version_index, .. } = module {
+        let cp_info = &class_file.cp_info;
+        let mut declaration: Vec<&str> = ModuleAccessFlag::parse_module_flags(*module_flags).iter()
+            .filter(|&flag| flag == &ModuleAccessFlag::AccOpen)
+            .map(|_| "open")
+            .collect();
+        declaration.push("module");
+        let mut module_name = get_constant_module_name(*module_name_index, cp_info);
+        if *module_version_index != 0 {
+            module_name.push_str(&format!("@{}", get_constant_utf8(*module_version_index, cp_info)));
+        }
+        declaration.push(&module_name);
+        declaration.join(" ")
+    } else {
+        panic!("Expected AttributeInfo::Module")
+    }
+}
+
+/// Directives of a module in Java syntax, one item per line.
+/// Ex.:
+///   requires transitive java.logging;
+///   exports foo.bar.impl to
+///     java.base,
+///     java.sql;
+///   uses foo.bar.Service;
+fn module_directives(module: &AttributeInfo, class_file: &ClassFile) -> Vec<String> {
+    if let AttributeInfo::Module { requires, exports, opens, uses_index, provides, .. } = module {
+        let cp_info = &class_file.cp_info;
+        let mut lines: Vec<String> = Vec::new();
+        for module_requires in requires.iter() {
+            let flags = ModuleAccessFlag::parse_requires_flags(module_requires.requires_flags);
+            let mut directive: Vec<&str> = vec!["requires"];
+            // javap prints static before transitive
+            if flags.contains(&ModuleAccessFlag::AccStaticPhase) {
+                directive.push(ModuleAccessFlag::AccStaticPhase.to_java_code());
+            }
+            if flags.contains(&ModuleAccessFlag::AccTransitive) {
+                directive.push(ModuleAccessFlag::AccTransitive.to_java_code());
+            }
+            let module_name = get_constant_module_name(module_requires.requires_index, cp_info);
+            directive.push(&module_name);
+            lines.push(format!("{};", directive.join(" ")));
+        }
+        for module_exports in exports.iter() {
+            lines.extend(module_directive_lines("exports", &get_constant_package_name(module_exports.exports_index, cp_info).replace('/', "."),
+                "to", &module_names(&module_exports.exports_to_index, class_file)));
+        }
+        for module_opens in opens.iter() {
+            lines.extend(module_directive_lines("opens", &get_constant_package_name(module_opens.opens_index, cp_info).replace('/', "."),
+                "to", &module_names(&module_opens.opens_to_index, class_file)));
+        }
+        for uses in uses_index.iter() {
+            lines.push(format!("uses {};", get_constant_class_name(*uses, cp_info).replace('/', ".")));
+        }
+        for module_provides in provides.iter() {
+            let implementations: Vec<String> = module_provides.provides_with_index.iter()
+                .map(|i| get_constant_class_name(*i, cp_info).replace('/', "."))
+                .collect();
+            // javap prints two spaces after provides
+            lines.extend(module_directive_lines("provides ", &get_constant_class_name(module_provides.provides_index, cp_info).replace('/', "."),
+                "with", &implementations));
+        }
+        lines
+    } else {
+        panic!("Expected AttributeInfo::Module")
+    }
+}
+
+fn module_names(module_indexes: &[u16], class_file: &ClassFile) -> Vec<String> {
+    module_indexes.iter().map(|i| get_constant_module_name(*i, &class_file.cp_info)).collect()
+}
+
+/// A directive with an optional list of targets, one target per line.
+/// Ex.: exports foo.bar to
+///        java.base,
+///        java.sql;
+fn module_directive_lines(directive: &str, name: &str, separator: &str, targets: &[String]) -> Vec<String> {
+    if targets.is_empty() {
+        return vec![format!("{} {};", directive, name)];
+    }
+    let mut lines: Vec<String> = vec![format!("{} {} {}", directive, name, separator)];
+    let targets: Vec<String> = targets.iter().map(|target| format!("  {}", target)).collect();
+    lines.push(format!("{};", targets.join(",\n  ")));
+    lines
+}
+
+/// First line of the class description. The type parameters and the generic
+/// superclass and interfaces come from the Signature attribute when the class
+/// has one. Records list their components and sealed classes their permitted
+/// subclasses.
+/// Ex.: public class G<T extends java.lang.Object> extends java.lang.Object implements java.lang.Runnable
+///      public record Point(int x, int y)
+///      public abstract sealed class Shape permits Circle, Square
+fn class_declaration(class_file: &ClassFile) -> Result<String, ClassFormatError> {
+    let access_flags: Vec<ClassAccessFlag> = ClassAccessFlag::parse_flags(class_file.access_flags);
+    let is_interface = access_flags.contains(&ClassAccessFlag::AccInterface);
+    let record_components = record_components(class_file);
+    let permitted_subclasses = permitted_subclasses(class_file);
+    // Like javap, only public, final and abstract are printed. Interfaces are
+    // implicitly abstract and records implicitly final.
+    let mut declaration: Vec<&str> = access_flags.iter()
+        .filter(|&flag| match flag {
+            ClassAccessFlag::AccPublic => true,
+            ClassAccessFlag::AccFinal => record_components.is_none(),
+            ClassAccessFlag::AccAbstract => !is_interface,
+            _ => false
+        })
+        .map(|f| f.to_java_code())
+        .collect();
+    if permitted_subclasses.is_some() {
+        declaration.push("sealed");
+    }
+    declaration.push(match (is_interface, record_components) {
+        (true, _) => "interface",
+        (false, Some(_)) => "record",
+        (false, None) => "class"
+    });
+    let interfaces_keyword = if is_interface { "extends" } else { "implements" };
+    let java_class_name = |class_index: u16| get_java_class_name(&get_constant_class_name(class_index, &class_file.cp_info), class_file);
+    let mut class_name = declared_class_name(class_file);
+    match signature_index(&class_file.attributes) {
+        Some(signature_index) => {
+            let signature = get_constant_utf8(signature_index, &class_file.cp_info);
+            let class_signature = parse_class_signature(&signature).map_err(|e| e.within("Signature"))?;
+            class_name.push_str(&type_parameters_str_java(&class_signature.type_parameters));
+            // The superclass of an interface is java.lang.Object, it only
+            // extends its superinterfaces
+            match record_components {
+                Some(components) => class_name.push_str(&record_components_java(components, class_file)?),
+                None if is_interface => (),
+                None => class_name.push_str(&format!(" extends {}", class_signature.superclass.str_java()))
+            }
+            if !class_signature.interfaces.is_empty() {
+                let interfaces: Vec<String> = class_signature.interfaces.iter().map(|i| i.str_java()).collect();
+                class_name.push_str(&format!(" {} {}", interfaces_keyword, interfaces.join(", ")));
+            }
+        },
+        None => {
+            if let Some(components) = record_components {
+                class_name.push_str(&record_components_java(components, class_file)?);
+            } else if class_file.super_class != 0 {
+                let super_class = java_class_name(class_file.super_class);
+                if super_class != "java.lang.Object" {
+                    class_name.push_str(&format!(" extends {}", super_class));
+                }
+            }
+            if !class_file.interfaces.is_empty() {
+                // javap does not separate the interfaces with a space here
+                let interfaces: Vec<String> = class_file.interfaces.iter().map(|i| java_class_name(*i)).collect();
+                class_name.push_str(&format!(" {} {}", interfaces_keyword, interfaces.join(",")));
+            }
+        }
+    }
+    if let Some(classes) = permitted_subclasses {
+        let classes: Vec<String> = classes.iter().map(|c| java_class_name(*c)).collect();
+        class_name.push_str(&format!(" permits {}", classes.join(", ")));
+    }
+    declaration.push(&class_name);
+    Ok(declaration.join(" "))
+}
+
+/// Name of this class in its declaration and its constructors. Ex.:
+/// p/Outer$Inner -> p.Outer.Inner
+fn declared_class_name(class_file: &ClassFile) -> String {
+    let this_class_name = get_constant_class_name(class_file.this_class, &class_file.cp_info);
+    // Local and anonymous classes have no name to declare them with, like
+    // javap keep their binary name
+    match get_inner_class(&this_class_name, class_file) {
+        Some(inner_class) if inner_class.outer_class_info_index == 0 => this_class_name.replace('/', "."),
+        _ => get_java_class_name(&this_class_name, class_file)
+    }
+}
+
+fn record_components<'a>(class_file: &'a ClassFile) -> Option<&'a [RecordComponentInfo<'a>]> {
+    class_file.attributes.iter().find_map(|attribute| match attribute {
+        AttributeInfo::Record { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, components_count: _components_count, components } => {
+            Some(components.as_slice())
+        },
+        _ => None
+    })
+}
+
+fn permitted_subclasses<'a>(class_file: &'a ClassFile) -> Option<&'a [u16]> {
+    class_file.attributes.iter().find_map(|attribute| match attribute {
+        AttributeInfo::PermittedSubclasses { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, number_of_classes: _number_of_classes, classes } => {
+            Some(classes.as_slice())
+        },
+        _ => None
+    })
+}
+
+/// Record header. Ex.: (int x, java.util.List<T> ys)
+fn record_components_java(components: &[RecordComponentInfo], class_file: &ClassFile) -> Result<String, ClassFormatError> {
+    let mut acc: Vec<String> = Vec::with_capacity(components.len());
+    for (n, component) in components.iter().enumerate() {
+        let descriptor = get_constant_utf8(component.descriptor_index, &class_file.cp_info);
+        let component_type = field_type_java(&descriptor, &component.attributes, class_file)
+            .map_err(|e| e.within(format!("Record.components[{}]", n)))?;
+        acc.push(format!("{} {}", component_type, get_constant_utf8(component.name_index, &class_file.cp_info)));
+    }
+    Ok(format!("({})", acc.join(", ")))
+}
+
+fn class_attribute(attribute_info: &AttributeInfo, class_file: &ClassFile) -> Result<String, ClassFormatError> {
+    match attribute_info {
+        AttributeInfo::SourceFile { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, sourcefile_index } => {
+            Ok(format!("SourceFile: \"{}\"", get_constant_utf8(*sourcefile_index, &class_file.cp_info)))
+        },
+        AttributeInfo::Signature { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, signature_index } => {
+            let signature_str = format!("Signature: #{}", signature_index);
+            Ok(format!("{:<40}// {}", signature_str, get_constant_utf8(*signature_index, &class_file.cp_info)))
+        },
+        AttributeInfo::SourceDebugExtension { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, debug_extension } => {
+            let debug_extension = decode_modified_utf8(debug_extension).map_err(|e| e.within("SourceDebugExtension"))?;
+            let mut lines: Vec<String> = vec![String::from("SourceDebugExtension:")];
+            // javap prints each non-empty line of the extension
+            lines.extend(debug_extension.split(['\r', '\n']).filter(|line| !line.is_empty()).map(|line| format!("  {}", line)));
+            Ok(lines.join("\n"))
+        },
+        AttributeInfo::Synthetic { .. } => Ok(String::from("Synthetic: true")),
+        AttributeInfo::Deprecated { .. } => Ok(String::from("Deprecated: true")),
+        bootstrap_methods @ AttributeInfo::BootstrapMethods { .. } => Ok(bootstrap_methods_attribute(bootstrap_methods, class_file)),
+        module @ AttributeInfo::Module { .. } => Ok(module_attribute_to_string(module, class_file)),
+        AttributeInfo::NestHost { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, host_class_index } => {
+            Ok(format!("NestHost: class {}", get_name_quoted(get_constant_class_name(*host_class_index, &class_file.cp_info))))
+        },
+        AttributeInfo::NestMembers { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, number_of_classes: _number_of_classes, classes } => {
+            Ok(classes_attribute("NestMembers", classes, class_file))
+        },
+        AttributeInfo::PermittedSubclasses { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, number_of_classes: _number_of_classes, classes } => {
+            Ok(classes_attribute("PermittedSubclasses", classes, class_file))
+        },
+        record @ AttributeInfo::Record { .. } => record_attribute(record, class_file),
+        AttributeInfo::InnerClasses { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, number_of_classes: _number_of_classes, classes } => {
+            let mut lines: Vec<String> = vec![String::from("InnerClasses:")];
+            lines.extend(classes.iter().map(|inner_class| inner_class_line(inner_class, class_file)));
+            Ok(lines.join("\n"))
+        },
+        AttributeInfo::EnclosingMethod { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, class_index, method_index } => {
+            let enclosing_method = format!("EnclosingMethod: #{}.#{}", class_index, method_index);
+            let mut comment = get_constant_class_name(*class_index, &class_file.cp_info).replace('/', ".");
+            if *method_index != 0 {
+                comment.push_str(&format!(".{}", get_name(*method_index, &class_file.cp_info)));
+            }
+            Ok(format!("{:<40}// {}", enclosing_method, comment))
+        },
+        AttributeInfo::ModulePackages { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, package_count: _package_count, package_index } => {
+            let mut lines: Vec<String> = vec![String::from("ModulePackages:")];
+            for package in package_index.iter() {
+                let package_name = get_constant_package_name(*package, &class_file.cp_info).replace('/', ".");
+                lines.push(format!("  {:<40}// {}", format!("#{}", package), package_name));
+            }
+            Ok(lines.join("\n"))
+        },
+        AttributeInfo::ModuleMainClass { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, main_class_index } => {
+            let main_class = format!("ModuleMainClass: #{}", main_class_index);
+            Ok(format!("{:<40}// {}", main_class, get_constant_class_name(*main_class_index, &class_file.cp_info).replace('/', ".")))
+        },
+        AttributeInfo::RuntimeVisibleAnnotations { .. } |
+        AttributeInfo::RuntimeInvisibleAnnotations { .. } |
+        AttributeInfo::RuntimeVisibleTypeAnnotations { .. } |
+        AttributeInfo::RuntimeInvisibleTypeAnnotations { .. } => annotations_attribute(attribute_info, 0, class_file),
+        AttributeInfo::Unknown { .. } |
+        AttributeInfo::Custom { .. } => Ok(non_standard_attribute(attribute_info, 0, class_file)),
+        AttributeInfo::ConstantValue { .. } |
+        AttributeInfo::Code { .. } |
+        AttributeInfo::StackMapTable { .. } |
+        AttributeInfo::Exceptions { .. } |
+        AttributeInfo::LineNumberTable { .. } |
+        AttributeInfo::LocalVariableTable { .. } |
+        AttributeInfo::LocalVariableTypeTable { .. } |
+        AttributeInfo::RuntimeVisibleParameterAnnotations { .. } |
+        AttributeInfo::RuntimeInvisibleParameterAnnotations { .. } |
+        AttributeInfo::AnnotationDefault { .. } |
+        AttributeInfo::MethodParameters { .. } => Ok(unexpected_attribute(attribute_info, 0, class_file))
+    }
+}
+
+/// Print the BootstrapMethods attribute like javap, each method handle
+/// followed by its static arguments.
+/// Ex.:
+/// BootstrapMethods:
+///   0: #28 REF_invokeStatic java/lang/invoke/LambdaMetafactory.metafactory:(...)Ljava/lang/invoke/CallSite;
+///     Method arguments:
+///       #35 ()V
+fn bootstrap_methods_attribute(bootstrap_methods: &AttributeInfo, class_file: &ClassFile) -> String {
+    if let AttributeInfo::BootstrapMethods {
+        attribute_name_index: _attribute_name_index,
+        attribute_length: _attribute_length,
+        num_bootstrap_methods: _num_bootstrap_methods,
+        bootstrap_methods
+    } = bootstrap_methods {
+        let cp_info = &class_file.cp_info;
+        let mut lines: Vec<String> = vec![String::from("BootstrapMethods:")];
+        for (i, bootstrap_method) in bootstrap_methods.iter().enumerate() {
+            let method_ref = bootstrap_method.bootstrap_method_ref;
+            lines.push(format!("  {}: #{} {}", i, method_ref, get_constant_method_handle_description(method_ref as usize, cp_info)));
+            lines.push(String::from("    Method arguments:"));
+            for argument in bootstrap_method.bootstrap_arguments.iter() {
+                lines.push(format!("      #{} {}", argument, get_bootstrap_argument_description(*argument as usize, cp_info)));
+            }
+        }
+        lines.join("\n")
+    } else {
+        panic!("Expected AttributeInfo::BootstrapMethods")
+    }
+}
+
+/// Print the Module attribute like javap, with the constant pool indexes and
+/// the hexadecimal flags of the module and of each directive.
+/// Ex.:
+/// Module:
+///   #5,20                                   // "foo.bar" ACC_OPEN
+///   #0
+///   1                                       // requires
+///     #14,8000                                // "java.base" ACC_MANDATED
+///     #15                                     // 17.0.15
+fn module_attribute_to_string(module: &AttributeInfo, class_file: &ClassFile) -> String {
+    if let AttributeInfo::Module {
+        attribute_name_index: _attribute_name_index,
+        attribute_length: _attribute_length,
+        module_name_index,
+        module_flags,
+        module_version_index,
+        requires_count,
+        requires,
+        exports_count,
+        exports,
+        opens_count,
+        opens,
+        uses_count,
+        uses_index,
+        provides_count,
+        provides
+    } = module {
+        let cp_info = &class_file.cp_info;
+        let module_name = |index: u16| get_name_quoted(get_constant_module_name(index, cp_info));
+        let class_name = |index: u16| get_name_quoted(get_constant_class_name(index, cp_info));
+        let mut lines: Vec<String> = vec![String::from("Module:")];
+        let flags = module_flags_str(&ModuleAccessFlag::parse_module_flags(*module_flags));
+        lines.push(module_line(2, format!("#{},{:x}", module_name_index, module_flags), Some(format!("{}{}", module_name(*module_name_index), flags))));
+        lines.push(module_version_line(2, *module_version_index, cp_info));
+        lines.push(module_line(2, requires_count.to_string(), Some(String::from("requires"))));
+        for module_requires in requires.iter() {
+            let flags = module_flags_str(&ModuleAccessFlag::parse_requires_flags(module_requires.requires_flags));
+            let indexes = format!("#{},{:x}", module_requires.requires_index, module_requires.requires_flags);
+            lines.push(module_line(4, indexes, Some(format!("{}{}", module_name(module_requires.requires_index), flags))));
+            lines.push(module_version_line(4, module_requires.requires_version_index, cp_info));
+        }
+        lines.push(module_line(2, exports_count.to_string(), Some(String::from("exports"))));
+        for module_exports in exports.iter() {
+            lines.extend(module_package_lines(module_exports.exports_index, module_exports.exports_flags, &module_exports.exports_to_index, class_file));
+        }
+        lines.push(module_line(2, opens_count.to_string(), Some(String::from("opens"))));
+        for module_opens in opens.iter() {
+            lines.extend(module_package_lines(module_opens.opens_index, module_opens.opens_flags, &module_opens.opens_to_index, class_file));
+        }
+        lines.push(module_line(2, uses_count.to_string(), Some(String::from("uses"))));
+        for uses in uses_index.iter() {
+            lines.push(module_line(4, format!("#{}", uses), Some(class_name(*uses))));
+        }
+        lines.push(module_line(2, provides_count.to_string(), Some(String::from("provides"))));
+        for module_provides in provides.iter() {
+            let service = format!("{} with ... {}", class_name(module_provides.provides_index), module_provides.provides_with_count);
+            lines.push(module_line(4, format!("#{}", module_provides.provides_index), Some(service)));
+            for implementation in module_provides.provides_with_index.iter() {
+                lines.push(module_line(6, format!("#{}", implementation), Some(format!("... with {}", class_name(*implementation)))));
+            }
+        }
+        lines.join("\n")
+    } else {
+        panic!("Expected AttributeInfo::Module")
+    }
+}
+
+/// exports or opens entry of the Module attribute.
+fn module_package_lines(package_index: u16, flags: u16, to_index: &[u16], class_file: &ClassFile) -> Vec<String> {
+    let cp_info = &class_file.cp_info;
+    let mut comment = get_name_quoted(get_constant_package_name(package_index, cp_info));
+    comment.push_str(&module_flags_str(&ModuleAccessFlag::parse_exports_flags(flags)));
+    if !to_index.is_empty() {
+        comment.push_str(&format!(" to ... {}", to_index.len()));
+    }
+    let mut lines: Vec<String> = vec![module_line(4, format!("#{},{:x}", package_index, flags), Some(comment))];
+    for to in to_index.iter() {
+        let module_name = get_name_quoted(get_constant_module_name(*to, cp_info));
+        lines.push(module_line(6, format!("#{}", to), Some(format!("... to {}", module_name))));
+    }
+    lines
+}
+
+fn module_version_line(indent: usize, version_index: u16, cp_info: &[CpInfo]) -> String {
+    if version_index == 0 {
+        module_line(indent, String::from("#0"), None)
+    } else {
+        module_line(indent, format!("#{}", version_index), Some(get_constant_utf8(version_index, cp_info)))
+    }
+}
+
+/// Ex.: " ACC_TRANSITIVE ACC_MANDATED"
+fn module_flags_str(flags: &[ModuleAccessFlag]) -> String {
+    flags.iter().map(|flag| format!(" {}", flag.to_str())).collect()
+}
+
+fn module_line(indent: usize, indexes: String, comment: Option<String>) -> String {
+    match comment {
+        Some(comment) => format!("{}{:<40}// {}", " ".repeat(indent), indexes, comment),
+        None => format!("{}{}", " ".repeat(indent), indexes)
+    }
+}
+
+/// Entry of the InnerClasses attribute, the inner name and the outer class are
+/// only printed when present.
+/// Ex.: private static #49= #39 of #13;         // Nested=class p/Outer$Nested of class p/Outer
+fn inner_class_line(inner_class: &InnerClass, class_file: &ClassFile) -> String {
+    let cp_info = &class_file.cp_info;
+    let access_flags = InnerClassAccessFlag::parse_flags(inner_class.inner_class_access_flags);
+    let is_interface = access_flags.contains(&InnerClassAccessFlag::AccInterface);
+    let mut entry: String = access_flags.iter()
+        .filter(|&flag| match flag {
+            InnerClassAccessFlag::AccPublic |
+            InnerClassAccessFlag::AccPrivate |
+            InnerClassAccessFlag::AccProtected |
+            InnerClassAccessFlag::AccStatic |
+            InnerClassAccessFlag::AccFinal => true,
+            InnerClassAccessFlag::AccAbstract => !is_interface,
+            _ => false
+        })
+        .map(|f| format!("{} ", f.to_java_code()))
+        .collect();
+    let mut comment = String::new();
+    if inner_class.inner_name_index != 0 {
+        entry.push_str(&format!("#{}= ", inner_class.inner_name_index));
+        comment.push_str(&format!("{}=", get_constant_utf8(inner_class.inner_name_index, cp_info)));
+    }
+    entry.push_str(&format!("#{}", inner_class.inner_class_info_index));
+    comment.push_str(&format!("class {}", get_name_quoted(get_constant_class_name(inner_class.inner_class_info_index, cp_info))));
+    if inner_class.outer_class_info_index != 0 {
+        entry.push_str(&format!(" of #{}", inner_class.outer_class_info_index));
+        comment.push_str(&format!(" of class {}", get_name_quoted(get_constant_class_name(inner_class.outer_class_info_index, cp_info))));
+    }
+    entry.push(';');
+    format!("  {:<40}// {}", entry, comment)
+}
+
+/// NestMembers or PermittedSubclasses, one class per line.
+fn classes_attribute(attribute_name: &str, classes: &[u16], class_file: &ClassFile) -> String {
+    let mut lines: Vec<String> = vec![format!("{}:", attribute_name)];
+    for class in classes.iter() {
+        lines.push(format!("  {}", get_name_quoted(get_constant_class_name(*class, &class_file.cp_info))));
+    }
+    lines.join("\n")
+}
+
+/// Print the Record attribute like javap, each component is followed by its
+/// attributes and a blank line.
+/// Ex.:
+/// Record:
+///   int x;
+///     descriptor: I
+///
+fn record_attribute(record: &AttributeInfo, class_file: &ClassFile) -> Result<String, ClassFormatError> {
+    if let AttributeInfo::Record { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, components_count: _components_count, components } = record {
+        let mut lines: Vec<String> = vec![String::from("Record:")];
+        for (n, component) in components.iter().enumerate() {
+            let descriptor = get_constant_utf8(component.descriptor_index, &class_file.cp_info);
+            let component_type = field_type_java(&descriptor, &component.attributes, class_file)
+                .map_err(|e| e.within(format!("Record.components[{}]", n)))?;
+            lines.push(format!("  {} {};", component_type, get_constant_utf8(component.name_index, &class_file.cp_info)));
+            lines.push(format!("    descriptor: {}", descriptor));
+            for attribute in component.attributes.iter() {
+                lines.push(field_info_attribute(attribute, class_file)?);
+            }
+            lines.push(String::new());
+        }
+        Ok(lines.join("\n"))
+    } else {
+        panic!("Expected AttributeInfo::Record")
+    }
+}
+
+/// Index of the Signature attribute of a class or member, if any.
+fn signature_index(attributes: &[AttributeInfo]) -> Option<u16> {
+    attributes.iter().find_map(|attribute| match attribute {
+        AttributeInfo::Signature { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, signature_index } => Some(*signature_index),
+        _ => None
+    })
 }
 
-fn cp_info_to_string(idx: usize, cp_info: &Vec<CpInfo>) -> String {
-    match &cp_info[idx] {
+fn cp_info_to_string(idx: usize, cp_info: &[CpInfo]) -> Option<String> {
+    let idx_prefix = cp_info_index_prefix(idx, cp_info.len());
+    let operands = |operands: String| cp_info_operands(operands, &idx_prefix);
+    let line = match &cp_info[idx] {
         CpInfo::ConstantClass { tag: _tag, name_index } => {
-            let idx_prefix = cp_info_index_prefix(idx);
-            format!("{} = {:19}#{:<14}// {}", idx_prefix, "Class", name_index, get_constant_utf8(name_index.clone(), cp_info))
+            format!("{} = {:19}{}// {}", idx_prefix, "Class", operands(format!("#{}", name_index)), get_name_quoted(get_constant_utf8(*name_index, cp_info)))
         },
         CpInfo::ConstantFieldref { tag: _tag, class_index, name_and_type_index } => {
-            let idx_prefix = cp_info_index_prefix(idx);
-            let class_name = get_constant_class_name(class_index.clone(), cp_info);
-            let field_name = get_name(name_and_type_index.clone(), cp_info);
-            let field_type = get_type(name_and_type_index.clone(), cp_info);
             let field_indexes = format!("#{}.#{}", class_index, name_and_type_index);
-            format!("{} = {:19}{:15}// {}.{}:{}", idx_prefix, "Fieldref", field_indexes, class_name, field_name, field_type)
+            let description = get_constant_member_ref_description(idx, cp_info);
+            format!("{} = {:19}{}// {}", idx_prefix, "Fieldref", operands(field_indexes), description)
         },
         CpInfo::ConstantMethodref { tag: _tag, class_index, name_and_type_index } => {
-            let idx_prefix = cp_info_index_prefix(idx);
-            let class_name = get_constant_class_name(class_index.clone(), cp_info);
-            let method_name = get_name_quoted(get_name(name_and_type_index.clone(), cp_info));
-            let method_type = get_type(name_and_type_index.clone(), cp_info);
             let method_indexes = format!("#{}.#{}", class_index, name_and_type_index);
-            format!("{} = {:19}{:15}// {}.{}:{}", idx_prefix, "Methodref", method_indexes, class_name, method_name, method_type)
+            let description = get_constant_member_ref_description(idx, cp_info);
+            format!("{} = {:19}{}// {}", idx_prefix, "Methodref", operands(method_indexes), description)
+        },
+        CpInfo::ConstantInterfaceMethodref { tag: _tag, class_index, name_and_type_index } => {
+            let method_indexes = format!("#{}.#{}", class_index, name_and_type_index);
+            let description = get_constant_member_ref_description(idx, cp_info);
+            format!("{} = {:19}{}// {}", idx_prefix, "InterfaceMethodref", operands(method_indexes), description)
         },
         CpInfo::ConstantString { tag: _tag, string_index } => {
-            let idx_prefix = cp_info_index_prefix(idx);
-            format!("{} = {:19}#{:<14}// {}", idx_prefix, "String", string_index, get_constant_utf8(string_index.clone(), cp_info))
+            format!("{} = {:19}{}// {}", idx_prefix, "String", operands(format!("#{}", string_index)), get_constant_utf8_description(*string_index, cp_info))
+        },
+        CpInfo::ConstantInteger { tag: _tag, bytes } => {
+            format!("{} = {:19}{}", idx_prefix, "Integer", CpInfo::integer_value(*bytes))
+        },
+        CpInfo::ConstantFloat { tag: _tag, bytes } => {
+            format!("{} = {:19}{}f", idx_prefix, "Float", java_float_to_string(CpInfo::float_value(*bytes)))
+        },
+        CpInfo::ConstantLong { tag: _tag, high_bytes, low_bytes } => {
+            format!("{} = {:19}{}l", idx_prefix, "Long", CpInfo::long_value(*high_bytes, *low_bytes))
+        },
+        CpInfo::ConstantDouble { tag: _tag, high_bytes, low_bytes } => {
+            format!("{} = {:19}{}d", idx_prefix, "Double", java_double_to_string(CpInfo::double_value(*high_bytes, *low_bytes)))
         },
         CpInfo::ConstantNameAndType { tag: _tag, name_index, descriptor_index } => {
-            let idx_prefix = cp_info_index_prefix(idx);
-            let name = get_name_quoted(get_constant_utf8(name_index.clone(), cp_info));
-            let typename = get_constant_utf8(descriptor_index.clone(), cp_info);
+            let name = get_name_quoted(get_constant_utf8(*name_index, cp_info));
+            let typename = get_constant_utf8(*descriptor_index, cp_info);
             let name_and_type_indexes = format!("#{}:#{}", name_index, descriptor_index);
-            format!("{} = {:19}{:15}// {}:{}", idx_prefix, "NameAndType", name_and_type_indexes, name, typename)
+            format!("{} = {:19}{}// {}:{}", idx_prefix, "NameAndType", operands(name_and_type_indexes), name, typename)
         },
-        CpInfo::ConstantUtf8 { tag: _tag, length: _length, bytes: _bytes, bytes_str } => {
-            let idx_prefix = cp_info_index_prefix(idx);
-            format!("{} = {:19}{}", idx_prefix, "Utf8", bytes_str)
-        }
+        CpInfo::ConstantUtf8 { .. } => {
+            format!("{} = {:19}{}", idx_prefix, "Utf8", get_constant_utf8_description(idx as u16, cp_info))
+        },
+        CpInfo::ConstantMethodHandle { tag: _tag, reference_kind, reference_index } => {
+            let method_handle_indexes = format!("{}:#{}", reference_kind, reference_index);
+            let description = get_constant_method_handle_description(idx, cp_info);
+            format!("{} = {:19}{}// {}", idx_prefix, "MethodHandle", operands(method_handle_indexes), description)
+        },
+        CpInfo::ConstantMethodType { tag: _tag, descriptor_index } => {
+            format!("{} = {:19}{}//  {}", idx_prefix, "MethodType", operands(format!("#{}", descriptor_index)), get_constant_utf8(*descriptor_index, cp_info))
+        },
+        CpInfo::ConstantDynamic { tag: _tag, bootstrap_method_attr_index, name_and_type_index } => {
+            let dynamic_indexes = format!("#{}:#{}", bootstrap_method_attr_index, name_and_type_index);
+            let name = get_name(*name_and_type_index, cp_info);
+            let typename = get_type(*name_and_type_index, cp_info);
+            format!("{} = {:19}{}// #{}:{}:{}", idx_prefix, "Dynamic", operands(dynamic_indexes), bootstrap_method_attr_index, name, typename)
+        },
+        CpInfo::ConstantInvokeDynamic { tag: _tag, bootstrap_method_attr_index, name_and_type_index } => {
+            let dynamic_indexes = format!("#{}:#{}", bootstrap_method_attr_index, name_and_type_index);
+            let name = get_name(*name_and_type_index, cp_info);
+            let typename = get_type(*name_and_type_index, cp_info);
+            format!("{} = {:19}{}// #{}:{}:{}", idx_prefix, "InvokeDynamic", operands(dynamic_indexes), bootstrap_method_attr_index, name, typename)
+        },
+        CpInfo::ConstantModule { tag: _tag, name_index } => {
+            format!("{} = {:19}{}// {}", idx_prefix, "Module", operands(format!("#{}", name_index)), get_name_quoted(get_constant_utf8(*name_index, cp_info)))
+        },
+        CpInfo::ConstantPackage { tag: _tag, name_index } => {
+            format!("{} = {:19}{}// {}", idx_prefix, "Package", operands(format!("#{}", name_index)), get_name_quoted(get_constant_utf8(*name_index, cp_info)))
+        },
+        CpInfo::Unusable => return None
+    };
+    Some(line)
+}
+
+/// Index of a constant right aligned like javap to the width of the largest
+/// index. Ex.: "   #1" in a constant pool of 10 to 99 constants.
+fn cp_info_index_prefix(idx: usize, cp_info_count: usize) -> String {
+    let index = format!("#{}", idx);
+    let width = cp_info_count.to_string().len() + 1;
+    format!("  {}{}", " ".repeat(width.saturating_sub(index.len())), index)
+}
+
+/// Operands of a constant padded to the column of its comment, that javap
+/// keeps at 42 whatever the width of the index, with at least one space.
+fn cp_info_operands(operands: String, idx_prefix: &str) -> String {
+    let width = 20usize.saturating_sub(idx_prefix.len()).max(operands.len() + 1);
+    format!("{:<1$}", operands, width)
+}
+
+fn field_info_to_string(field_info: &FieldInfo, class_file: &ClassFile) -> Result<String, ClassFormatError> {
+    let access_flags: Vec<FieldAccessFlag> = FieldAccessFlag::parse_flags(field_info.access_flags);
+    let access_flags_jvm: Vec<&str> = access_flags.iter().map(|f| f.to_str()).collect();
+    let access_flags_jvm: String = access_flags_jvm.join(", ");
+    let field_name = get_constant_utf8(field_info.name_index, &class_file.cp_info);
+    let descriptor = get_constant_utf8(field_info.descriptor_index, &class_file.cp_info);
+    let field_type_java = field_type_java(&descriptor, &field_info.attributes, class_file)?;
+
+    let mut declaration: Vec<&str> = access_flags.iter()
+        .filter(|&f| f != &FieldAccessFlag::AccSynthetic && f != &FieldAccessFlag::AccEnum)
+        .map(|f| f.to_java_code())
+        .collect();
+    declaration.push(&field_type_java);
+    declaration.push(&field_name);
+
+    let mut lines: Vec<String> = vec![
+        format!("{};", declaration.join(" ")),
+        format!("    descriptor: {}", descriptor),
+        flags_line(field_info.access_flags, &access_flags_jvm),
+    ];
+    for attribute in field_info.attributes.iter() {
+        lines.push(field_info_attribute(attribute, class_file)?);
     }
+    Ok(lines.join("\n"))
 }
 
-fn cp_info_index_prefix(idx: usize) -> String {
-    let left_pad = 5 - idx.to_string().len() - 1;
-    format!("{0:<1$}#{2}", " ", left_pad, idx)
+/// Type of a field or record component, from its Signature attribute when it
+/// has one.
+fn field_type_java(descriptor: &str, attributes: &[AttributeInfo], class_file: &ClassFile) -> Result<String, ClassFormatError> {
+    match signature_index(attributes) {
+        Some(signature_index) => {
+            let signature = get_constant_utf8(signature_index, &class_file.cp_info);
+            Ok(parse_field_signature(&signature).map_err(|e| e.within("Signature"))?.str_java())
+        },
+        None => Ok(parse_field_descriptor(descriptor).map_err(|e| e.within("descriptor"))?.str_java())
+    }
+}
+
+fn field_info_attribute(attribute_info: &AttributeInfo, class_file: &ClassFile) -> Result<String, ClassFormatError> {
+    match attribute_info {
+        AttributeInfo::ConstantValue { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, constantvalue_index } => {
+            let constant_value = get_constant_value_description(*constantvalue_index as usize, &class_file.cp_info);
+            Ok(format!("    ConstantValue: {}", constant_value))
+        },
+        signature @ AttributeInfo::Signature { .. } => Ok(signature_attribute(signature, class_file)),
+        AttributeInfo::Synthetic { .. } => Ok(String::from("    Synthetic: true")),
+        AttributeInfo::Deprecated { .. } => Ok(String::from("    Deprecated: true")),
+        AttributeInfo::RuntimeVisibleAnnotations { .. } |
+        AttributeInfo::RuntimeInvisibleAnnotations { .. } |
+        AttributeInfo::RuntimeVisibleTypeAnnotations { .. } |
+        AttributeInfo::RuntimeInvisibleTypeAnnotations { .. } => annotations_attribute(attribute_info, 4, class_file),
+        AttributeInfo::Unknown { .. } |
+        AttributeInfo::Custom { .. } => Ok(non_standard_attribute(attribute_info, 4, class_file)),
+        _ => Ok(unexpected_attribute(attribute_info, 4, class_file))
+    }
+}
+
+fn signature_attribute(signature: &AttributeInfo, class_file: &ClassFile) -> String {
+    if let AttributeInfo::Signature { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, signature_index } = signature {
+        let signature_str = format!("Signature: #{}", signature_index);
+        format!("    {:<40}// {}", signature_str, get_constant_utf8(*signature_index, &class_file.cp_info))
+    } else {
+        panic!("Expected AttributeInfo::Signature")
+    }
+}
+
+/// Format the `flags:` line of a member, the list of flags is omitted when
+/// no flag is set.
+/// Ex.: flags: (0x0019) ACC_PUBLIC, ACC_STATIC, ACC_FINAL
+fn flags_line(flags: u16, flags_jvm: &str) -> String {
+    if flags_jvm.is_empty() {
+        format!("    flags: ({:#06x})", flags)
+    } else {
+        format!("    flags: ({:#06x}) {}", flags, flags_jvm)
+    }
 }
 
-fn method_info_to_string(method_info: &MethodInfo, class_file: &ClassFile) -> String {
+fn method_info_to_string(method_info: &MethodInfo, class_file: &ClassFile, options: &PrettyPrintOptions) -> Result<String, ClassFormatError> {
     let access_flags: Vec<MethodAccessFlag> = MethodAccessFlag::parse_flags(method_info.access_flags);
-    let access_flags_java: Vec<&str> = access_flags.iter().map(|f| f.to_java_code()).collect();
-    let access_flags_java: String = access_flags_java.join(" ");
     let access_flags_jvm: Vec<&str> = access_flags.iter().map(|f| f.to_str()).collect();
     let access_flags_jvm: String = access_flags_jvm.join(", ");
     let method_name = get_constant_utf8(method_info.name_index, &class_file.cp_info);
-    let method_return_type: String = if method_name == "<init>" { String::from(" ") } else {
-        let return_type = return_descriptor_to_java_code(method_info_return_type(method_info.descriptor_index, class_file));
-        format!(" {} ", return_type)
-    };
-    let method_name: String = if method_name == "<init>" { get_constant_class_name(class_file.this_class, &class_file.cp_info) } else { method_name };
-    let attributes = method_info_attributes(method_info, class_file);
-    let arguments = parse_method_arguments(method_info, &class_file.cp_info);
-    let arguments = parse_field_types(&arguments);
-    let arguments: Vec<String> = arguments.iter().map(|f| f.str_java()).collect();
     let descriptor = get_constant_utf8(method_info.descriptor_index, &class_file.cp_info);
+    let method_descriptor = parse_method_descriptor(&descriptor).map_err(|e| e.within("descriptor"))?;
+    // Generic methods print their type parameters, return and argument
+    // types from the Signature attribute
+    let (type_parameters, return_type, arguments) = match signature_index(&method_info.attributes) {
+        Some(signature_index) => {
+            let signature = get_constant_utf8(signature_index, &class_file.cp_info);
+            let method_signature = parse_method_signature(&signature).map_err(|e| e.within("Signature"))?;
+            let arguments: Vec<String> = method_signature.params.iter().map(|p| p.str_java()).collect();
+            (type_parameters_str_java(&method_signature.type_parameters), method_signature.result.str_java(), arguments)
+        },
+        None => {
+            let arguments: Vec<String> = method_descriptor.params.iter().map(|p| p.str_java()).collect();
+            (String::new(), method_descriptor.ret.str_java(), arguments)
+        }
+    };
+    let mut arguments = arguments;
+    if access_flags.contains(&MethodAccessFlag::AccVarargs) {
+        if let Some(last) = arguments.last_mut().filter(|last| last.ends_with("[]")) {
+            last.truncate(last.len() - 2);
+            last.push_str("...");
+        }
+    }
+    // Name the arguments when the MethodParameters attribute has a name for
+    // each of them, the attribute also lists the implicit parameters that
+    // the Signature attribute leaves out.
+    if let Some(parameters) = method_parameters(&method_info.attributes) {
+        if parameters.len() == arguments.len() {
+            for (argument, parameter) in arguments.iter_mut().zip(parameters.iter()) {
+                if parameter.name_index != 0 {
+                    argument.push_str(&format!(" {}", get_constant_utf8(parameter.name_index, &class_file.cp_info)));
+                }
+            }
+        }
+    }
+    // Like javap, bridge, varargs and synthetic are left to the flags line
+    let mut declaration: Vec<String> = access_flags.iter()
+        .filter(|&f| !matches!(f, MethodAccessFlag::AccBridge | MethodAccessFlag::AccVarargs | MethodAccessFlag::AccSynthetic))
+        .map(|f| String::from(f.to_java_code()))
+        .collect();
+    let is_interface = ClassAccessFlag::parse_flags(class_file.access_flags).contains(&ClassAccessFlag::AccInterface);
+    let is_default = is_interface && method_name != "<clinit>" && !access_flags.iter()
+        .any(|f| matches!(f, MethodAccessFlag::AccAbstract | MethodAccessFlag::AccStatic | MethodAccessFlag::AccPrivate));
+    if is_default {
+        declaration.push(String::from("default"));
+    }
+    if !type_parameters.is_empty() {
+        declaration.push(type_parameters);
+    }
+    if method_name == "<clinit>" {
+        declaration.push(String::from("{}"));
+    } else if method_name == "<init>" {
+        declaration.push(format!("{}({})", declared_class_name(class_file), arguments.join(", ")));
+    } else {
+        declaration.push(return_type);
+        declaration.push(format!("{}({})", method_name, arguments.join(", ")));
+    }
+    let throws = method_throws(method_info, class_file)?;
+    if !throws.is_empty() {
+        declaration.push(format!("throws {}", throws.join(", ")));
+    }
+    let attributes = method_info_attributes(method_info, class_file, options)?;
+
+    let mut lines: Vec<String> = vec![
+        format!("{};", declaration.join(" ")),
+        format!("    descriptor: {}", descriptor),
+        flags_line(method_info.access_flags, &access_flags_jvm),
+    ];
+    lines.extend(attributes);
+    Ok(lines.join("\n"))
+}
 
-    format!("{}{}{}({});\n    \
-        descriptor: {}\n    \
-        flags: ({:#06x}) {}\n\
-        {}",
-        access_flags_java,
-        method_return_type,
-        method_name,
-        arguments.join(", "),
-        descriptor,
-        method_info.access_flags,
-        access_flags_jvm,
-        attributes.join("\n")
-    )
-}
-
-fn method_info_attributes(method_info: &MethodInfo, class_file: &ClassFile) -> Vec<String> {
+fn method_parameters<'a>(attributes: &'a [AttributeInfo]) -> Option<&'a [MethodParameter]> {
+    attributes.iter().find_map(|attribute| match attribute {
+        AttributeInfo::MethodParameters { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, parameters_count: _parameters_count, parameters } => {
+            Some(parameters.as_slice())
+        },
+        _ => None
+    })
+}
+
+/// Exceptions of the `throws` clause of a method, from the Signature
+/// attribute when it declares them since they can be type variables.
+fn method_throws(method_info: &MethodInfo, class_file: &ClassFile) -> Result<Vec<String>, ClassFormatError> {
+    if let Some(signature_index) = signature_index(&method_info.attributes) {
+        let signature = get_constant_utf8(signature_index, &class_file.cp_info);
+        let method_signature = parse_method_signature(&signature).map_err(|e| e.within("Signature"))?;
+        if !method_signature.throws.is_empty() {
+            return Ok(method_signature.throws.iter().map(|t| t.str_java()).collect());
+        }
+    }
+    let exceptions = method_info.attributes.iter()
+        .find_map(|attribute| match attribute {
+            AttributeInfo::Exceptions { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, number_of_exceptions: _number_of_exceptions, exception_index_table } => {
+                Some(exception_index_table.iter().map(|e| get_constant_class_name(*e, &class_file.cp_info).replace('/', ".")).collect())
+            },
+            _ => None
+        })
+        .unwrap_or_default();
+    Ok(exceptions)
+}
+
+fn method_info_attributes(method_info: &MethodInfo, class_file: &ClassFile, options: &PrettyPrintOptions) -> Result<Vec<String>, ClassFormatError> {
     let mut attributes: Vec<String> = Vec::with_capacity(method_info.attributes_count as usize);
     for attribute in method_info.attributes.iter() {
-        attributes.push(method_info_attribute(attribute, method_info, class_file));
+        attributes.push(method_info_attribute(attribute, method_info, class_file, options)?);
+    }
+    Ok(attributes)
+}
+
+fn method_info_attribute(attribute_info: &AttributeInfo, method_info: &MethodInfo, class_file: &ClassFile, options: &PrettyPrintOptions) -> Result<String, ClassFormatError> {
+    match attribute_info {
+        AttributeInfo::ConstantValue { .. } => field_info_attribute(attribute_info, class_file),
+        code @ AttributeInfo::Code { .. } => method_attribute_info_code(code, method_info, class_file, options),
+        smt @ AttributeInfo::StackMapTable { .. } => Ok(stack_map_table(smt, class_file)),
+        AttributeInfo::Exceptions { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, number_of_exceptions: _number_of_exceptions, exception_index_table } => {
+            let exceptions: Vec<String> = exception_index_table.iter()
+                .map(|e| get_constant_class_name(*e, &class_file.cp_info).replace('/', "."))
+                .collect();
+            Ok(format!("    Exceptions:\n      throws {}", exceptions.join(", ")))
+        },
+        AttributeInfo::Synthetic { .. } => field_info_attribute(attribute_info, class_file),
+        signature @ AttributeInfo::Signature { .. } => Ok(signature_attribute(signature, class_file)),
+        lnt @ AttributeInfo::LineNumberTable { .. } => Ok(line_number_table(lnt)),
+        lvt @ AttributeInfo::LocalVariableTable { .. } => Ok(local_variable_table(lvt, class_file)),
+        lvtt @ AttributeInfo::LocalVariableTypeTable { .. } => Ok(local_variable_table(lvtt, class_file)),
+        AttributeInfo::Deprecated { .. } => field_info_attribute(attribute_info, class_file),
+        AttributeInfo::RuntimeVisibleAnnotations { .. } |
+        AttributeInfo::RuntimeInvisibleAnnotations { .. } |
+        AttributeInfo::RuntimeVisibleParameterAnnotations { .. } |
+        AttributeInfo::RuntimeInvisibleParameterAnnotations { .. } |
+        AttributeInfo::AnnotationDefault { .. } |
+        AttributeInfo::RuntimeVisibleTypeAnnotations { .. } |
+        AttributeInfo::RuntimeInvisibleTypeAnnotations { .. } => annotations_attribute(attribute_info, 4, class_file),
+        AttributeInfo::MethodParameters { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, parameters_count: _parameters_count, parameters } => {
+            Ok(method_parameters_attribute(parameters, class_file))
+        },
+        AttributeInfo::Unknown { .. } |
+        AttributeInfo::Custom { .. } => Ok(non_standard_attribute(attribute_info, 4, class_file)),
+        AttributeInfo::InnerClasses { .. } |
+        AttributeInfo::EnclosingMethod { .. } |
+        AttributeInfo::SourceFile { .. } |
+        AttributeInfo::SourceDebugExtension { .. } |
+        AttributeInfo::BootstrapMethods { .. } |
+        AttributeInfo::Module { .. } |
+        AttributeInfo::ModulePackages { .. } |
+        AttributeInfo::ModuleMainClass { .. } |
+        AttributeInfo::NestHost { .. } |
+        AttributeInfo::NestMembers { .. } |
+        AttributeInfo::Record { .. } |
+        AttributeInfo::PermittedSubclasses { .. } => Ok(unexpected_attribute(attribute_info, 4, class_file))
+    }
+}
+
+/// Print an attribute defined by the JVMS in a structure where it is not
+/// expected, by its name and length.
+/// Ex.:
+///     SourceFile: length = 0x2
+fn unexpected_attribute(attribute_info: &AttributeInfo, indent: usize, class_file: &ClassFile) -> String {
+    let (_attribute_name_index, attribute_length) = attribute_info.header();
+    format!("{}{}: length = 0x{:X}", " ".repeat(indent), attribute_info_name(attribute_info, class_file), attribute_length)
+}
+
+/// Print an attribute not defined by the JVMS, indented past `indent` like
+/// javap. Decoded attributes print the lines of their value, the others a
+/// hex dump of their bytes.
+/// Ex.:
+///   ScalaSig: length = 0x3 (unknown attribute)
+///    05 00 FF
+fn non_standard_attribute(attribute_info: &AttributeInfo, indent: usize, class_file: &ClassFile) -> String {
+    let pad = " ".repeat(indent + 2);
+    let cp_info = &class_file.cp_info;
+    let mut lines: Vec<String> = Vec::new();
+    match attribute_info {
+        AttributeInfo::Unknown { attribute_name_index, attribute_length: _attribute_length, info } => {
+            let attribute_name = get_constant_utf8(*attribute_name_index, cp_info);
+            lines.push(format!("{}{}: length = 0x{:X} (unknown attribute)", pad, attribute_name, info.len()));
+            for chunk in info.chunks(16) {
+                let bytes: Vec<String> = chunk.iter().map(|b| format!("{:02X}", b)).collect();
+                lines.push(format!("{} {}", pad, bytes.join(" ")));
+            }
+        },
+        AttributeInfo::Custom { attribute_name_index, attribute_length: _attribute_length, info: _info, value } => {
+            lines.push(format!("{}{}:", pad, get_constant_utf8(*attribute_name_index, cp_info)));
+            lines.extend(value.lines(cp_info).iter().map(|line| format!("{}  {}", pad, line)));
+        },
+        attribute => panic!("Expected a non-standard attribute, found {:?}", attribute)
+    }
+    lines.join("\n")
+}
+
+/// Print the MethodParameters attribute like javap, parameters without a name
+/// are printed as `<no name>`.
+/// Ex.:
+///     MethodParameters:
+///       Name                           Flags
+///       a                              final
+fn method_parameters_attribute(parameters: &[MethodParameter], class_file: &ClassFile) -> String {
+    let mut lines: Vec<String> = Vec::with_capacity(parameters.len() + 2);
+    lines.push(String::from("    MethodParameters:"));
+    lines.push(format!("      {:<30} {}", "Name", "Flags"));
+    for parameter in parameters.iter() {
+        let name = if parameter.name_index == 0 {
+            String::from("<no name>")
+        } else {
+            get_constant_utf8(parameter.name_index, &class_file.cp_info)
+        };
+        let access_flags = MethodParameterAccessFlag::parse_flags(parameter.access_flags);
+        let flags: Vec<&str> = access_flags.iter().map(|f| f.to_java_code()).collect();
+        lines.push(format!("      {:<30} {}", name, flags.join(" ")).trim_end().to_string());
     }
-    attributes
+    lines.join("\n")
 }
 
-fn method_info_attribute(attribute_info: &AttributeInfo, method_info: &MethodInfo, class_file: &ClassFile) -> String {
+/// Print an annotation attribute like javap: each annotation is printed once
+/// with its constant pool indexes and once in Java syntax, one element per
+/// line. `indent` is the indentation of the attribute name.
+/// Ex.:
+///     RuntimeVisibleAnnotations:
+///       0: #12(#13=I#14)
+///         org.junit.Test(
+///           timeout=10
+///         )
+fn annotations_attribute(attribute_info: &AttributeInfo, indent: usize, class_file: &ClassFile) -> Result<String, ClassFormatError> {
+    let pad = " ".repeat(indent);
+    let mut lines: Vec<String> = Vec::new();
     match attribute_info {
-        AttributeInfo::ConstantValue { .. } => todo!(),
-        code @ AttributeInfo::Code { .. } => method_attribute_info_code(code, method_info, class_file),
-        AttributeInfo::StackMapTable { .. } => todo!(),
-        AttributeInfo::Exceptions { .. } => todo!(),
-        AttributeInfo::InnerClasses { .. } => todo!(),
-        AttributeInfo::EnclosingMethod { .. } => todo!(),
-        AttributeInfo::Synthetic { .. } => todo!(),
-        AttributeInfo::Signature { .. } => todo!(),
-        AttributeInfo::SourceFile { .. } => todo!(),
-        AttributeInfo::SourceDebugExtension { .. } => todo!(),
-        lnt @ AttributeInfo::LineNumberTable { .. } => line_number_table(lnt),
-        AttributeInfo::LocalVariableTable { .. } => todo!(),
-        AttributeInfo::LocalVariableTypeTable { .. } => todo!(),
-        AttributeInfo::Deprecated { .. } => todo!(),
-        AttributeInfo::RuntimeVisibleAnnotations { .. } => todo!(),
-        AttributeInfo::RuntimeInvisibleAnnotations { .. } => todo!(),
-        AttributeInfo::RuntimeVisibleParameterAnnotations { .. } => todo!(),
-        AttributeInfo::RuntimeInvisibleParameterAnnotations { .. } => todo!(),
-        AttributeInfo::RuntimeVisibleTypeAnnotations { .. } => todo!(),
-        AttributeInfo::RuntimeInvisibleTypeAnnotations { .. } => todo!(),
-        AttributeInfo::AnnotationDefault { .. } => todo!(),
-        AttributeInfo::BootstrapMethods { .. } => todo!(),
-        AttributeInfo::MethodParameters { .. } => todo!(),
-        AttributeInfo::Module { .. } => todo!(),
-        AttributeInfo::ModulePackages { .. } => todo!(),
-        AttributeInfo::ModuleMainClass { .. } => todo!(),
-        AttributeInfo::NestHost { .. } => todo!(),
-        AttributeInfo::NestMembers { .. } => todo!(),
-        AttributeInfo::Record { .. } => todo!(),
-        AttributeInfo::PermittedSubclasses { .. } => todo!()
-    }
-}
-
-fn method_attribute_info_code(code: &AttributeInfo, method_info: &MethodInfo, class_file: &ClassFile) -> String {
+        AttributeInfo::RuntimeVisibleAnnotations { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, num_annotations: _num_annotations, annotations } |
+        AttributeInfo::RuntimeInvisibleAnnotations { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, num_annotations: _num_annotations, annotations } => {
+            lines.push(format!("{}{}:", pad, attribute_info_name(attribute_info, class_file)));
+            for (i, annotation) in annotations.iter().enumerate() {
+                lines.push(format!("{}  {}: {}", pad, i, annotation_indexes(annotation)));
+                lines.push(format!("{}    {}", pad, annotation_java(annotation, indent + 4, class_file)?));
+            }
+        },
+        AttributeInfo::RuntimeVisibleParameterAnnotations { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, num_parameters: _num_parameters, parameter_annotations } |
+        AttributeInfo::RuntimeInvisibleParameterAnnotations { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, num_parameters: _num_parameters, parameter_annotations } => {
+            lines.push(format!("{}{}:", pad, attribute_info_name(attribute_info, class_file)));
+            for (parameter, annotations) in parameter_annotations.iter().enumerate() {
+                lines.push(format!("{}  parameter {}:", pad, parameter));
+                for (i, annotation) in annotations.iter().enumerate() {
+                    lines.push(format!("{}    {}: {}", pad, i, annotation_indexes(annotation)));
+                    lines.push(format!("{}      {}", pad, annotation_java(annotation, indent + 6, class_file)?));
+                }
+            }
+        },
+        AttributeInfo::RuntimeVisibleTypeAnnotations { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, num_annotations: _num_annotations, annotations } |
+        AttributeInfo::RuntimeInvisibleTypeAnnotations { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, num_annotations: _num_annotations, annotations } => {
+            lines.push(format!("{}{}:", pad, attribute_info_name(attribute_info, class_file)));
+            for (i, type_annotation) in annotations.iter().enumerate() {
+                let annotation = &type_annotation.annotation;
+                lines.push(format!("{}  {}: {}: {}", pad, i, annotation_indexes(annotation), type_annotation_position(type_annotation)));
+                lines.push(format!("{}    {}", pad, annotation_java(annotation, indent + 4, class_file)?));
+            }
+        },
+        AttributeInfo::AnnotationDefault { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, default_value } => {
+            lines.push(format!("{}AnnotationDefault:", pad));
+            lines.push(format!("{}  default_value: {}", pad, element_value_indexes(default_value)));
+            lines.push(format!("{}    {}", pad, element_value_java(default_value, indent + 4, class_file)?));
+        },
+        attribute => panic!("Expected an annotation attribute, found {:?}", attribute)
+    }
+    Ok(lines.join("\n"))
+}
+
+fn attribute_info_name(attribute_info: &AttributeInfo, class_file: &ClassFile) -> String {
+    let (attribute_name_index, _attribute_length) = attribute_info.header();
+    get_constant_utf8(attribute_name_index, &class_file.cp_info)
+}
+
+/// Target of a type annotation followed by its type path, if any.
+/// Ex.: LOCAL_VARIABLE, {start_pc=2, length=5, index=1}
+///      FIELD, location=[TYPE_ARGUMENT(1), WILDCARD]
+fn type_annotation_position(type_annotation: &TypeAnnotation) -> String {
+    let mut position = String::from(type_annotation.target_type.to_str());
+    match &type_annotation.target_info {
+        TargetInfo::TypeParameter { type_parameter_index } => {
+            position.push_str(&format!(", param_index={}", type_parameter_index));
+        },
+        TargetInfo::Supertype { supertype_index } => {
+            position.push_str(&format!(", type_index={}", supertype_index));
+        },
+        TargetInfo::TypeParameterBound { type_parameter_index, bound_index } => {
+            position.push_str(&format!(", param_index={}, bound_index={}", type_parameter_index, bound_index));
+        },
+        TargetInfo::Empty => {},
+        TargetInfo::FormalParameter { formal_parameter_index } => {
+            position.push_str(&format!(", param_index={}", formal_parameter_index));
+        },
+        TargetInfo::Throws { throws_type_index } => {
+            position.push_str(&format!(", type_index={}", throws_type_index));
+        },
+        TargetInfo::Localvar { table_length: _table_length, table } => {
+            let ranges: Vec<String> = table.iter()
+                .map(|item| format!("start_pc={}, length={}, index={}", item.start_pc, item.length, item.index))
+                .collect();
+            position.push_str(&format!(", {{{}}}", ranges.join("; ")));
+        },
+        TargetInfo::Catch { exception_table_index } => {
+            position.push_str(&format!(", exception_index={}", exception_table_index));
+        },
+        TargetInfo::Offset { offset } => {
+            position.push_str(&format!(", offset={}", offset));
+        },
+        TargetInfo::TypeArgument { offset, type_argument_index } => {
+            position.push_str(&format!(", offset={}, type_index={}", offset, type_argument_index));
+        }
+    }
+    if !type_annotation.type_path.is_empty() {
+        let location: Vec<String> = type_annotation.type_path.iter()
+            .map(|entry| match entry.type_path_kind {
+                TypePathKind::TypeArgument => format!("{}({})", entry.type_path_kind.to_str(), entry.type_argument_index),
+                _ => String::from(entry.type_path_kind.to_str())
+            })
+            .collect();
+        position.push_str(&format!(", location=[{}]", location.join(", ")));
+    }
+    position
+}
+
+/// Annotation with constant pool indexes. Ex.: #12(#13=I#14,#15=[s#16])
+fn annotation_indexes(annotation: &Annotation) -> String {
+    let element_value_pairs: Vec<String> = annotation.element_value_pairs.iter()
+        .map(|pair| format!("#{}={}", pair.element_name_index, element_value_indexes(&pair.value)))
+        .collect();
+    format!("#{}({})", annotation.type_index, element_value_pairs.join(","))
+}
+
+fn element_value_indexes(element_value: &ElementValue) -> String {
+    match element_value {
+        ElementValue::ConstValue { tag, const_value_index } => format!("{}#{}", *tag as char, const_value_index),
+        ElementValue::EnumConstValue { type_name_index, const_name_index } => format!("e#{}.#{}", type_name_index, const_name_index),
+        ElementValue::ClassInfo { class_info_index } => format!("c#{}", class_info_index),
+        ElementValue::AnnotationValue { annotation_value } => format!("@{}", annotation_indexes(annotation_value)),
+        ElementValue::ArrayValue { num_values: _num_values, values } => {
+            let values: Vec<String> = values.iter().map(element_value_indexes).collect();
+            format!("[{}]", values.join(","))
+        }
+    }
+}
+
+/// Annotation in Java syntax, the elements go on their own lines indented
+/// past `indent`.
+fn annotation_java(annotation: &Annotation, indent: usize, class_file: &ClassFile) -> Result<String, ClassFormatError> {
+    let type_descriptor = get_constant_utf8(annotation.type_index, &class_file.cp_info);
+    let type_name = parse_field_descriptor(&type_descriptor).map_err(|e| e.within("type_index"))?.str_java();
+    if annotation.element_value_pairs.is_empty() {
+        return Ok(type_name);
+    }
+    let pad = " ".repeat(indent);
+    let mut lines: Vec<String> = vec![format!("{}(", type_name)];
+    for pair in annotation.element_value_pairs.iter() {
+        let element_name = get_constant_utf8(pair.element_name_index, &class_file.cp_info);
+        lines.push(format!("{}  {}={}", pad, element_name, element_value_java(&pair.value, indent + 2, class_file)?));
+    }
+    lines.push(format!("{})", pad));
+    Ok(lines.join("\n"))
+}
+
+fn element_value_java(element_value: &ElementValue, indent: usize, class_file: &ClassFile) -> Result<String, ClassFormatError> {
+    let cp_info = &class_file.cp_info;
+    let value = match element_value {
+        ElementValue::ConstValue { tag, const_value_index } => get_element_value_const_description(*tag, *const_value_index, cp_info),
+        ElementValue::EnumConstValue { type_name_index, const_name_index } => {
+            format!("{}.{}", get_constant_utf8(*type_name_index, cp_info), get_constant_utf8(*const_name_index, cp_info))
+        },
+        ElementValue::ClassInfo { class_info_index } => format!("class {}", get_constant_utf8(*class_info_index, cp_info)),
+        ElementValue::AnnotationValue { annotation_value } => format!("@{}", annotation_java(annotation_value, indent, class_file)?),
+        ElementValue::ArrayValue { num_values: _num_values, values } => {
+            let mut acc: Vec<String> = Vec::with_capacity(values.len());
+            for value in values.iter() {
+                acc.push(element_value_java(value, indent, class_file)?);
+            }
+            format!("[{}]", acc.join(","))
+        }
+    };
+    Ok(value)
+}
+
+fn method_attribute_info_code(code: &AttributeInfo, method_info: &MethodInfo, class_file: &ClassFile, options: &PrettyPrintOptions) -> Result<String, ClassFormatError> {
     if let AttributeInfo::Code {
         attribute_name_index: _attribute_name_index,
         attribute_length: _attribute_length,
@@ -204,34 +1170,141 @@ fn method_attribute_info_code(code: &AttributeInfo, method_info: &MethodInfo, cl
         code_length: _code_length,
         code,
         exception_table_length: _exception_table_length,
-        exception_table: _exception_table,
+        exception_table,
         attributes_count,
         attributes
     } = code {
-        let instructions_block = instructions_block(code, class_file);
-        let args_size = method_arguments_count(method_info, &class_file.cp_info);
-        let code_attributes_len = attributes_count.clone() as usize;
-        let mut code_attributes: Vec<String> = Vec::with_capacity(code_attributes_len);
-        for i in 0..code_attributes_len {
-            let attribute_str = method_info_attribute(&attributes[i], method_info, class_file);
-            code_attributes.push(attribute_str);
-        }
-
-        format!(
-            "    Code:\n      \
-            stack={}, locals={}, args_size={}\n\
-            {}\n{}",
-            max_stack,
-            max_locals,
-            args_size,
-            instructions_block,
-            code_attributes.join("\n")
-        )
+        let instructions_block = instructions_block(code, attributes, class_file, options)?;
+        let descriptor = get_constant_utf8(method_info.descriptor_index, &class_file.cp_info);
+        let method_descriptor = parse_method_descriptor(&descriptor).map_err(|e| e.within("descriptor"))?;
+        let args_size = method_arguments_count(method_info, &method_descriptor);
+        let code_attributes_len = *attributes_count as usize;
+        let mut lines: Vec<String> = Vec::with_capacity(code_attributes_len + 4);
+        lines.push(String::from("    Code:"));
+        lines.push(format!("      stack={}, locals={}, args_size={}", max_stack, max_locals, args_size));
+        lines.push(instructions_block);
+        if !exception_table.is_empty() {
+            lines.push(exception_table_to_string(exception_table, class_file));
+        }
+        for attribute in attributes.iter().take(code_attributes_len) {
+            let attribute_str = match attribute {
+                AttributeInfo::RuntimeVisibleTypeAnnotations { .. } |
+                AttributeInfo::RuntimeInvisibleTypeAnnotations { .. } => annotations_attribute(attribute, 6, class_file)?,
+                AttributeInfo::Unknown { .. } |
+                AttributeInfo::Custom { .. } => non_standard_attribute(attribute, 6, class_file),
+                _ => method_info_attribute(attribute, method_info, class_file, options)?
+            };
+            lines.push(attribute_str);
+        }
+        Ok(lines.join("\n"))
     } else {
         panic!("Expected AttributeInfo::Code")
     }
 }
 
+fn exception_table_to_string(exception_table: &[ExceptionTable], class_file: &ClassFile) -> String {
+    let mut items: Vec<String> = Vec::with_capacity(exception_table.len() + 2);
+    items.push(String::from("      Exception table:"));
+    items.push(String::from("         from    to  target type"));
+    for item in exception_table.iter() {
+        let catch_type = if item.catch_type == 0 {
+            get_catch_type_name(item.catch_type, &class_file.cp_info)
+        } else {
+            format!("Class {}", get_catch_type_name(item.catch_type, &class_file.cp_info))
+        };
+        let line = format!("         {:5} {:5} {:5}   {}", item.start_pc, item.end_pc, item.handler_pc, catch_type);
+        items.push(line);
+    }
+    items.join("\n")
+}
+
+/// Print the StackMapTable like javap, the offset_delta is omitted for frames
+/// that encode it in the frame type.
+/// Ex.:
+///       StackMapTable: number_of_entries = 2
+///         frame_type = 252 /* append */
+///           offset_delta = 15
+///           locals = [ class java/lang/String ]
+///         frame_type = 5 /* same */
+fn stack_map_table(stack_map_table: &AttributeInfo, class_file: &ClassFile) -> String {
+    if let AttributeInfo::StackMapTable {
+        attribute_name_index: _attribute_name_index,
+        attribute_length: _attribute_length,
+        number_of_entries,
+        entries
+    } = stack_map_table {
+        let mut lines: Vec<String> = vec![format!("      StackMapTable: number_of_entries = {}", number_of_entries)];
+        for frame in entries.iter() {
+            match frame {
+                StackMapFrame::SameFrame { frame_type } => {
+                    lines.push(format!("        frame_type = {} /* same */", frame_type));
+                },
+                StackMapFrame::SameLocals1StackItemFrame { frame_type, stack } => {
+                    lines.push(format!("        frame_type = {} /* same_locals_1_stack_item */", frame_type));
+                    lines.push(verification_types_line("stack", std::slice::from_ref(stack), class_file));
+                },
+                StackMapFrame::SameLocals1StackItemFrameExtended { frame_type, offset_delta, stack } => {
+                    lines.push(format!("        frame_type = {} /* same_locals_1_stack_item_frame_extended */", frame_type));
+                    lines.push(format!("          offset_delta = {}", offset_delta));
+                    lines.push(verification_types_line("stack", std::slice::from_ref(stack), class_file));
+                },
+                StackMapFrame::ChopFrame { frame_type, offset_delta } => {
+                    lines.push(format!("        frame_type = {} /* chop */", frame_type));
+                    lines.push(format!("          offset_delta = {}", offset_delta));
+                },
+                StackMapFrame::SameFrameExtended { frame_type, offset_delta } => {
+                    lines.push(format!("        frame_type = {} /* same_frame_extended */", frame_type));
+                    lines.push(format!("          offset_delta = {}", offset_delta));
+                },
+                StackMapFrame::AppendFrame { frame_type, offset_delta, locals } => {
+                    lines.push(format!("        frame_type = {} /* append */", frame_type));
+                    lines.push(format!("          offset_delta = {}", offset_delta));
+                    lines.push(verification_types_line("locals", locals, class_file));
+                },
+                StackMapFrame::FullFrame {
+                    frame_type,
+                    offset_delta,
+                    number_of_locals: _number_of_locals,
+                    locals,
+                    number_of_stack_items: _number_of_stack_items,
+                    stack
+                } => {
+                    lines.push(format!("        frame_type = {} /* full_frame */", frame_type));
+                    lines.push(format!("          offset_delta = {}", offset_delta));
+                    lines.push(verification_types_line("locals", locals, class_file));
+                    lines.push(verification_types_line("stack", stack, class_file));
+                }
+            }
+        }
+        lines.join("\n")
+    } else {
+        panic!("Expected AttributeInfo::StackMapTable")
+    }
+}
+
+/// Ex.: locals = [ class "[Ljava/lang/String;", int, top ]
+fn verification_types_line(name: &str, verification_types: &[VerificationTypeInfo], class_file: &ClassFile) -> String {
+    if verification_types.is_empty() {
+        return format!("          {} = []", name);
+    }
+    let types: Vec<String> = verification_types.iter()
+        .map(|verification_type| match verification_type {
+            VerificationTypeInfo::Top => String::from("top"),
+            VerificationTypeInfo::Integer => String::from("int"),
+            VerificationTypeInfo::Float => String::from("float"),
+            VerificationTypeInfo::Double => String::from("double"),
+            VerificationTypeInfo::Long => String::from("long"),
+            VerificationTypeInfo::Null => String::from("null"),
+            VerificationTypeInfo::UninitializedThis => String::from("this"),
+            VerificationTypeInfo::Object { cpool_index } => {
+                format!("class {}", get_name_quoted(get_constant_class_name(*cpool_index, &class_file.cp_info)))
+            },
+            VerificationTypeInfo::Uninitialized { offset } => format!("uninitialized {}", offset)
+        })
+        .collect();
+    format!("          {} = [ {} ]", name, types.join(", "))
+}
+
 fn line_number_table(line_number_table: &AttributeInfo) -> String {
     if let AttributeInfo::LineNumberTable {
         attribute_name_index: _attribute_name_index,
@@ -240,7 +1313,7 @@ fn line_number_table(line_number_table: &AttributeInfo) -> String {
         line_number_tables
     } = line_number_table {
 
-        let mut items: Vec<String> = Vec::with_capacity(line_number_table_length.clone() as usize);
+        let mut items: Vec<String> = Vec::with_capacity(*line_number_table_length as usize);
         for item in line_number_tables.iter() {
             let line = format!("        line {}: {}", item.line_number, item.start_pc);
             items.push(line);
@@ -252,255 +1325,353 @@ fn line_number_table(line_number_table: &AttributeInfo) -> String {
     }
 }
 
-fn instructions_block(code: &Vec<u8>, class_file: &ClassFile) -> String {
-    let mut acc: Vec<String> = Vec::new();
-    let mut i: usize = 0;
-    while i < code.len() {
-        let opcode = get_opcode(code[i]);
-        let (new_i, opcode_args_string_opt) = instruction_args(i, &opcode, code, class_file);
-        let line = match opcode_args_string_opt {
-            None => format!("{:10}: {}", i, opcode.str()),
-            Some(opcode_args_string) => format!("{:10}: {:14}{}", i, opcode.str(), opcode_args_string)
-        };
-        acc.push(line);
-        i = new_i;
-    }
-    acc.join("\n")
-}
-
-fn instruction_args(opcode_idx: usize, opcode: &Opcode, code: &Vec<u8>, class_file: &ClassFile) -> (usize, Option<String>) {
-    match opcode {
-        Opcode::Nop => (opcode_idx + 1, None),
-        Opcode::AconstNull => todo!("opcode_idx[{}]", opcode_idx),
-        Opcode::IconstM1 => todo!(),
-        Opcode::Iconst0 => todo!(),
-        Opcode::Iconst1 => todo!(),
-        Opcode::Iconst2 => todo!(),
-        Opcode::Iconst3 => todo!(),
-        Opcode::Iconst4 => todo!(),
-        Opcode::Iconst5 => todo!(),
-        Opcode::Lconst0 => todo!(),
-        Opcode::Lconst1 => todo!(),
-        Opcode::Fconst0 => todo!(),
-        Opcode::Fconst1 => todo!(),
-        Opcode::Fconst2 => todo!(),
-        Opcode::Dconst0 => todo!(),
-        Opcode::Dconst1 => todo!("opcode_idx[{}] in {:?}", opcode_idx, code),
-        Opcode::Bipush => todo!(),
-        Opcode::Sipush => todo!(),
-        Opcode::Ldc => {
-            // Push item from run-time constant pool
-            let (new_idx, index) = get_u1(opcode_idx + 1, code);
-            let description = get_ldc_description(index as usize, class_file);
-            (new_idx, Some(format!("#{:<19}// {}", index, description)))
-        },
-        Opcode::LdcW => todo!(),
-        Opcode::Ldc2W => todo!(),
-        Opcode::Iload => todo!(),
-        Opcode::Lload => todo!(),
-        Opcode::Fload => todo!(),
-        Opcode::Dload => todo!(),
-        Opcode::Aload => todo!(),
-        Opcode::Iload0 => todo!(),
-        Opcode::Iload1 => todo!(),
-        Opcode::Iload2 => todo!(),
-        Opcode::Iload3 => todo!(),
-        Opcode::Lload0 => todo!(),
-        Opcode::Lload1 => todo!(),
-        Opcode::Lload2 => todo!(),
-        Opcode::Lload3 => todo!(),
-        Opcode::Fload0 => todo!(),
-        Opcode::Fload1 => todo!(),
-        Opcode::Fload2 => todo!(),
-        Opcode::Fload3 => todo!(),
-        Opcode::Dload0 => todo!(),
-        Opcode::Dload1 => todo!(),
-        Opcode::Dload2 => todo!(),
-        Opcode::Dload3 => todo!(),
-        Opcode::Aload0 => {
-            // Load reference from local variable
-            (opcode_idx + 1, None)
-        },
-        Opcode::Aload1 => todo!(),
-        Opcode::Aload2 => todo!(),
-        Opcode::Aload3 => todo!(),
-        Opcode::Iaload => todo!(),
-        Opcode::Laload => todo!(),
-        Opcode::Faload => todo!(),
-        Opcode::Daload => todo!(),
-        Opcode::Aaload => todo!(),
-        Opcode::Baload => todo!(),
-        Opcode::Caload => todo!(),
-        Opcode::Saload => todo!(),
-        Opcode::Istore => todo!(),
-        Opcode::Lstore => todo!(),
-        Opcode::Fstore => todo!(),
-        Opcode::Dstore => todo!(),
-        Opcode::Astore => todo!(),
-        Opcode::Istore0 => todo!(),
-        Opcode::Istore1 => todo!(),
-        Opcode::Istore2 => todo!(),
-        Opcode::Istore3 => todo!(),
-        Opcode::Lstore0 => todo!(),
-        Opcode::Lstore1 => todo!(),
-        Opcode::Lstore2 => todo!(),
-        Opcode::Lstore3 => todo!(),
-        Opcode::Fstore0 => todo!(),
-        Opcode::Fstore1 => todo!(),
-        Opcode::Fstore2 => todo!(),
-        Opcode::Fstore3 => todo!(),
-        Opcode::Dstore0 => todo!(),
-        Opcode::Dstore1 => todo!(),
-        Opcode::Dstore2 => todo!(),
-        Opcode::Dstore3 => todo!(),
-        Opcode::Astore0 => todo!(),
-        Opcode::Astore1 => todo!(),
-        Opcode::Astore2 => todo!(),
-        Opcode::Astore3 => todo!(),
-        Opcode::Iastore => todo!(),
-        Opcode::Lastore => todo!(),
-        Opcode::Fastore => todo!(),
-        Opcode::Dastore => todo!(),
-        Opcode::Aastore => todo!(),
-        Opcode::Bastore => todo!(),
-        Opcode::Castore => todo!(),
-        Opcode::Sastore => todo!(),
-        Opcode::Pop => todo!(),
-        Opcode::Pop2 => todo!(),
-        Opcode::Dup => todo!(),
-        Opcode::DupX1 => todo!(),
-        Opcode::DupX2 => todo!(),
-        Opcode::Dup2 => todo!(),
-        Opcode::Dup2X1 => todo!(),
-        Opcode::Dup2X2 => todo!(),
-        Opcode::Swap => todo!(),
-        Opcode::Iadd => todo!(),
-        Opcode::Ladd => todo!(),
-        Opcode::Fadd => todo!(),
-        Opcode::Dadd => todo!(),
-        Opcode::Isub => todo!(),
-        Opcode::Lsub => todo!(),
-        Opcode::Fsub => todo!(),
-        Opcode::Dsub => todo!(),
-        Opcode::Imul => todo!(),
-        Opcode::Lmul => todo!(),
-        Opcode::Fmul => todo!(),
-        Opcode::Dmul => todo!(),
-        Opcode::Idiv => todo!(),
-        Opcode::Ldiv => todo!(),
-        Opcode::Fdiv => todo!(),
-        Opcode::Ddiv => todo!(),
-        Opcode::Irem => todo!(),
-        Opcode::Lrem => todo!(),
-        Opcode::Frem => todo!(),
-        Opcode::Drem => todo!(),
-        Opcode::Ineg => todo!(),
-        Opcode::Lneg => todo!(),
-        Opcode::Fneg => todo!(),
-        Opcode::Dneg => todo!(),
-        Opcode::Ishl => todo!(),
-        Opcode::Lshl => todo!(),
-        Opcode::Ishr => todo!(),
-        Opcode::Lshr => todo!(),
-        Opcode::Iushr => todo!(),
-        Opcode::Lushr => todo!(),
-        Opcode::Iand => todo!(),
-        Opcode::Land => todo!(),
-        Opcode::Ior => todo!(),
-        Opcode::Lor => todo!(),
-        Opcode::Ixor => todo!(),
-        Opcode::Lxor => todo!(),
-        Opcode::Iinc => todo!(),
-        Opcode::I2l => todo!(),
-        Opcode::I2f => todo!(),
-        Opcode::I2d => todo!(),
-        Opcode::L2i => todo!(),
-        Opcode::L2f => todo!(),
-        Opcode::L2d => todo!(),
-        Opcode::F2i => todo!(),
-        Opcode::F2l => todo!(),
-        Opcode::F2d => todo!(),
-        Opcode::D2i => todo!(),
-        Opcode::D2l => todo!(),
-        Opcode::D2f => todo!(),
-        Opcode::I2b => todo!(),
-        Opcode::I2c => todo!(),
-        Opcode::I2s => todo!(),
-        Opcode::Lcmp => todo!(),
-        Opcode::Fcmpl => todo!(),
-        Opcode::Fcmpg => todo!(),
-        Opcode::Dcmpl => todo!(),
-        Opcode::Dcmpg => todo!(),
-        Opcode::Ifeq => todo!(),
-        Opcode::Ifne => todo!(),
-        Opcode::Iflt => todo!(),
-        Opcode::Ifge => todo!(),
-        Opcode::Ifgt => todo!(),
-        Opcode::Ifle => todo!(),
-        Opcode::IfIcmpeq => todo!(),
-        Opcode::IfIcmpne => todo!(),
-        Opcode::IfIcmplt => todo!(),
-        Opcode::IfIcmpge => todo!(),
-        Opcode::IfIcmpgt => todo!(),
-        Opcode::IfIcmple => todo!(),
-        Opcode::IfAcmpeq => todo!(),
-        Opcode::IfAcmpne => todo!(),
-        Opcode::Goto => todo!(),
-        Opcode::Jsr => todo!(),
-        Opcode::Ret => todo!(),
-        Opcode::Tableswitch => todo!(),
-        Opcode::Lookupswitch => todo!(),
-        Opcode::Ireturn => todo!(),
-        Opcode::Lreturn => todo!(),
-        Opcode::Freturn => todo!(),
-        Opcode::Dreturn => todo!(),
-        Opcode::Areturn => todo!(),
-        Opcode::Return => {
-            // Return void from method
-            (opcode_idx + 1, None)
-        },
-        Opcode::Getstatic => {
-            // get static field from a class
-            let (new_idx, fieldref) = get_u2(opcode_idx + 1, code);
-            let description = get_static_description(fieldref as usize, class_file);
-            (new_idx, Some(format!("#{:<19}// {}", fieldref, description)))
-        },
-        Opcode::Putstatic => todo!(),
-        Opcode::Getfield => todo!(),
-        Opcode::Putfield => todo!(),
-        Opcode::Invokevirtual => {
-            // Invoke instance method; dispatch based on class
-            let (new_idx, index) = get_u2(opcode_idx + 1, code);
-            let method = get_constant_method_ref_description(index as usize, class_file);
-            (new_idx, Some(format!("#{:<19}// Method {}", index, method)))
-        },
-        Opcode::Invokespecial => {
-            // Invoke instance method; direct invocation of instance initialization methods and methods of the current class and its supertypes
-            let (new_idx, index) = get_u2(opcode_idx + 1, code);
-            let method = get_constant_method_ref_description(index as usize, class_file);
-            (new_idx, Some(format!("#{:<19}// Method {}", index, method)))
-        },
-        Opcode::Invokestatic => todo!(),
-        Opcode::Invokeinterface => todo!(),
-        Opcode::Invokedynamic => todo!(),
-        Opcode::New => todo!(),
-        Opcode::Newarray => todo!(),
-        Opcode::Anewarray => todo!(),
-        Opcode::Arraylength => todo!(),
-        Opcode::Athrow => todo!(),
-        Opcode::Checkcast => todo!(),
-        Opcode::Instanceof => todo!(),
-        Opcode::Monitorenter => todo!(),
-        Opcode::Monitorexit => todo!(),
-        Opcode::Wide => todo!(),
-        Opcode::Multianewarray => todo!(),
-        Opcode::Ifnull => todo!(),
-        Opcode::Ifnonnull => todo!(),
-        Opcode::GotoW => todo!(),
-        Opcode::JsrW => todo!(),
-        Opcode::Breakpoint => todo!(),
-        Opcode::Impdep1 => todo!(),
-        Opcode::Impdep2 => todo!(),
+/// Print a LocalVariableTable or LocalVariableTypeTable like javap, both
+/// have the same columns.
+fn local_variable_table(local_variable_table: &AttributeInfo, class_file: &ClassFile) -> String {
+    let cp_info = &class_file.cp_info;
+    let (attribute_name, rows): (&str, Vec<[u16; 5]>) = match local_variable_table {
+        AttributeInfo::LocalVariableTable { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, local_variable_table_length: _local_variable_table_length, local_variable_table } => {
+            ("LocalVariableTable", local_variable_table.iter().map(|item| [item.start_pc, item.length, item.index, item.name_index, item.descriptor_index]).collect())
+        },
+        AttributeInfo::LocalVariableTypeTable { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, local_variable_type_table_length: _local_variable_type_table_length, local_variable_type_table } => {
+            ("LocalVariableTypeTable", local_variable_type_table.iter().map(|item| [item.start_pc, item.length, item.index, item.name_index, item.signature_index]).collect())
+        },
+        attribute => panic!("Expected a local variable table, found {:?}", attribute)
+    };
+    let mut lines: Vec<String> = Vec::with_capacity(rows.len() + 2);
+    lines.push(format!("      {}:", attribute_name));
+    lines.push(String::from("        Start  Length  Slot  Name   Signature"));
+    for [start_pc, length, index, name_index, type_index] in rows {
+        lines.push(format!("        {:5} {:7} {:5} {:>5}   {}", start_pc, length, index,
+                           get_constant_utf8(name_index, cp_info), get_constant_utf8(type_index, cp_info)));
+    }
+    lines.join("\n")
+}
+
+fn instructions_block(code: &[u8], code_attributes: &[AttributeInfo], class_file: &ClassFile, options: &PrettyPrintOptions) -> Result<String, ClassFormatError> {
+    let instructions = decode_instructions(code)?;
+    let mut acc: Vec<String> = Vec::with_capacity(instructions.len());
+    for (n, (pc, instruction)) in instructions.iter().enumerate() {
+        let mut line = instruction_mnemonic(instruction);
+        if let Some(opcode_args_string) = instruction_args(instruction) {
+            line = format!("{:<13} {}", line, opcode_args_string);
+        }
+        let mut comment = instruction_comment(instruction, class_file);
+        if options.annotate_local_variables && comment.is_none() {
+            let next_pc = instructions.get(n + 1).map_or(code.len() as u32, |(next_pc, _)| *next_pc);
+            comment = local_variable_annotation(instruction, *pc, next_pc, code_attributes, class_file)?;
+        }
+        if let Some(comment) = comment {
+            // javap aligns comments on column 34, past the mnemonic
+            line = format!("{:<33} // {}", line, comment);
+        }
+        acc.push(format!("{:10}: {}", pc, line));
+        if options.annotate_invokedynamic {
+            if let Some(annotation) = invoke_dynamic_annotation(instruction, class_file)? {
+                // Continuation of the comment, on the same column
+                acc.push(format!("{:46}//   {}", "", annotation));
+            }
+        }
+    }
+    Ok(acc.join("\n"))
+}
+
+/// Name and type of the local variable accessed by a load, a store or iinc,
+/// the generic type is taken from the LocalVariableTypeTable when the
+/// variable has one. The scope of a variable starts after the store that
+/// initializes it, so stores look up the variable at `next_pc`.
+/// Ex.: int count
+fn local_variable_annotation(instruction: &Instruction, pc: u32, next_pc: u32, code_attributes: &[AttributeInfo], class_file: &ClassFile) -> Result<Option<String>, ClassFormatError> {
+    let index = match instruction.local_variable_index() {
+        Some(index) => index,
+        None => return Ok(None)
+    };
+    let is_store = instruction.opcode.category() == OpcodeCategory::Store;
+    let is_live = |start_pc: u16, length: u16| {
+        let end_pc = start_pc as u32 + length as u32;
+        if is_store {
+            start_pc as u32 <= next_pc && next_pc <= end_pc
+        } else {
+            start_pc as u32 <= pc && pc < end_pc
+        }
+    };
+    let variable = code_attributes.iter()
+        .flat_map(|attribute| match attribute {
+            AttributeInfo::LocalVariableTable { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, local_variable_table_length: _local_variable_table_length, local_variable_table } => {
+                local_variable_table.as_slice()
+            },
+            _ => &[]
+        })
+        .find(|item| item.index == index && is_live(item.start_pc, item.length));
+    let variable = match variable {
+        Some(variable) => variable,
+        None => return Ok(None)
+    };
+    let signature_index = code_attributes.iter()
+        .flat_map(|attribute| match attribute {
+            AttributeInfo::LocalVariableTypeTable { attribute_name_index: _attribute_name_index, attribute_length: _attribute_length, local_variable_type_table_length: _local_variable_type_table_length, local_variable_type_table } => {
+                local_variable_type_table.as_slice()
+            },
+            _ => &[]
+        })
+        .find(|item| item.index == index && item.start_pc == variable.start_pc && item.length == variable.length)
+        .map(|item| item.signature_index);
+    let cp_info = &class_file.cp_info;
+    let variable_type = match signature_index {
+        Some(signature_index) => {
+            let signature = get_constant_utf8(signature_index, cp_info);
+            parse_field_signature(&signature).map_err(|e| e.within("LocalVariableTypeTable"))?.str_java()
+        },
+        None => {
+            let descriptor = get_constant_utf8(variable.descriptor_index, cp_info);
+            parse_field_descriptor(&descriptor).map_err(|e| e.within("LocalVariableTable"))?.str_java()
+        }
+    };
+    Ok(Some(format!("{} {}", variable_type, get_constant_utf8(variable.name_index, cp_info))))
+}
+
+fn invoke_dynamic_annotation(instruction: &Instruction, class_file: &ClassFile) -> Result<Option<String>, ClassFormatError> {
+    match (&instruction.opcode, &instruction.operand) {
+        (Opcode::Invokedynamic, Operand::ConstantPoolIndex(index)) => get_invoke_dynamic_annotation(*index as usize, class_file),
+        _ => Ok(None)
+    }
+}
+
+/// Instructions modified by `wide` are printed with a `_w` suffix.
+/// Ex.: iload_w, iinc_w
+fn instruction_mnemonic(instruction: &Instruction) -> String {
+    if instruction.wide {
+        format!("{}_w", instruction.opcode.str())
+    } else {
+        String::from(instruction.opcode.str())
     }
 }
 
+/// Operands of an instruction. Branch targets are printed as absolute
+/// offsets and switches are printed with one line per case.
+fn instruction_args(instruction: &Instruction) -> Option<String> {
+    match &instruction.operand {
+        Operand::None => None,
+        Operand::Byte(value) => Some(value.to_string()),
+        Operand::Short(value) => Some(value.to_string()),
+        Operand::LocalIndex(index) => Some(index.to_string()),
+        Operand::ConstantPoolIndex(index) => match instruction.opcode {
+            Opcode::Invokedynamic => Some(format!("#{},  0", index)),
+            _ => Some(format!("#{}", index))
+        },
+        Operand::BranchTarget(target) => Some(target.to_string()),
+        Operand::Iinc { index, value } => Some(format!("{}, {}", index, value)),
+        Operand::NewArray(array_type) => Some(format!(" {}", array_type.str())),
+        Operand::MultiANewArray { index, dimensions } => Some(format!("#{},  {}", index, dimensions)),
+        Operand::InvokeInterface { index, count } => Some(format!("#{},  {}", index, count)),
+        Operand::TableSwitch { default, low, high, targets } => {
+            let mut lines = vec![format!("{{ // {} to {}", low, high)];
+            for (key, target) in (*low..=*high).zip(targets.iter()) {
+                lines.push(switch_case_line(key.to_string(), *target));
+            }
+            lines.push(switch_case_line(String::from("default"), *default));
+            lines.push(String::from("            }"));
+            Some(lines.join("\n"))
+        },
+        Operand::LookupSwitch { default, pairs } => {
+            let mut lines = vec![format!("{{ // {}", pairs.len())];
+            for (key, target) in pairs.iter() {
+                lines.push(switch_case_line(key.to_string(), *target));
+            }
+            lines.push(switch_case_line(String::from("default"), *default));
+            lines.push(String::from("            }"));
+            Some(lines.join("\n"))
+        }
+    }
+}
+
+fn switch_case_line(key: String, target: u32) -> String {
+    format!("{:>24}: {}", key, target)
+}
+
+/// Description of the constant pool entry referenced by an instruction.
+/// Ex.: Method java/lang/Object."<init>":()V
+fn instruction_comment(instruction: &Instruction, class_file: &ClassFile) -> Option<String> {
+    let index = match instruction.operand {
+        Operand::ConstantPoolIndex(index) => index,
+        Operand::MultiANewArray { index, dimensions: _dimensions } => index,
+        Operand::InvokeInterface { index, count: _count } => index,
+        _ => return None
+    };
+    Some(get_instruction_constant_description(index as usize, class_file))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use crate::parser::ast::{LocalVariableTableItem, LocalVariableTypeTableItem};
+    use crate::parser::opcodes::ArrayType;
+    use super::*;
+
+    fn utf8(str: &str) -> CpInfo<'_> {
+        CpInfo::ConstantUtf8 { tag: 1, length: str.len() as u16, bytes: Cow::Borrowed(str.as_bytes()), bytes_str: Cow::Borrowed(str) }
+    }
+
+    /// Start of the constant pool of `public class Big { int f0; ... }`,
+    /// followed by the names of the fields.
+    fn cp_info(names: &[String]) -> Vec<CpInfo<'_>> {
+        let mut cp_info = vec![
+            CpInfo::Unusable,
+            CpInfo::ConstantMethodref { tag: 10, class_index: 2, name_and_type_index: 3 },
+            CpInfo::ConstantClass { tag: 7, name_index: 4 },
+            CpInfo::ConstantNameAndType { tag: 12, name_index: 5, descriptor_index: 6 },
+            utf8("java/lang/Object"),
+            utf8("<init>"),
+            utf8("()V"),
+        ];
+        cp_info.extend(names.iter().map(|name| utf8(name)));
+        cp_info
+    }
+
+    fn field_names(fields: usize) -> Vec<String> {
+        (0..fields).map(|n| format!("f{}", n)).collect()
+    }
+
+    #[test]
+    fn cp_info_index_alignment() {
+        let names = field_names(60);
+        let cp_info = cp_info(&names);
+        assert_eq!(cp_info_to_string(1, &cp_info).unwrap(), "   #1 = Methodref          #2.#3          // java/lang/Object.\"<init>\":()V");
+        assert_eq!(cp_info_to_string(66, &cp_info).unwrap(), "  #66 = Utf8               f59");
+    }
+
+    #[test]
+    fn cp_info_large_constant_pool() {
+        // Like javap, the indexes take 6 columns and the comments stay at
+        // the same column
+        let names = field_names(10100);
+        let cp_info = cp_info(&names);
+        assert_eq!(cp_info_to_string(1, &cp_info).unwrap(), "      #1 = Methodref          #2.#3       // java/lang/Object.\"<init>\":()V");
+        assert_eq!(cp_info_to_string(2, &cp_info).unwrap(), "      #2 = Class              #4          // java/lang/Object");
+        assert_eq!(cp_info_to_string(10000, &cp_info).unwrap(), "  #10000 = Utf8               f9993");
+    }
+
+    #[test]
+    fn cp_info_operands_wider_than_their_column() {
+        assert_eq!(cp_info_operands(String::from("#10001.#10002"), "  #10003"), "#10001.#10002 ");
+    }
+
+    /// Instruction as javap prints it after its pc, without the comment.
+    fn instruction_line(opcode: Opcode, wide: bool, operand: Operand) -> String {
+        let instruction = Instruction { opcode, wide, operand };
+        match instruction_args(&instruction) {
+            Some(args) => format!("{:<13} {}", instruction_mnemonic(&instruction), args),
+            None => instruction_mnemonic(&instruction)
+        }
+    }
+
+    #[test]
+    fn instruction_args_like_javap() {
+        assert_eq!(instruction_line(Opcode::Iinc, false, Operand::Iinc { index: 3, value: 1 }), "iinc          3, 1");
+        assert_eq!(instruction_line(Opcode::Newarray, false, Operand::NewArray(ArrayType::Int)), "newarray       int");
+        assert_eq!(instruction_line(Opcode::Multianewarray, false, Operand::MultiANewArray { index: 7, dimensions: 2 }), "multianewarray #7,  2");
+        assert_eq!(instruction_line(Opcode::Invokedynamic, false, Operand::ConstantPoolIndex(9)), "invokedynamic #9,  0");
+        assert_eq!(instruction_line(Opcode::Invokeinterface, false, Operand::InvokeInterface { index: 13, count: 1 }), "invokeinterface #13,  1");
+        assert_eq!(instruction_line(Opcode::Istore, false, Operand::LocalIndex(4)), "istore        4");
+        assert_eq!(instruction_line(Opcode::Arraylength, false, Operand::None), "arraylength");
+    }
+
+    #[test]
+    fn instruction_args_wide() {
+        assert_eq!(instruction_line(Opcode::Iload, true, Operand::LocalIndex(300)), "iload_w       300");
+        assert_eq!(instruction_line(Opcode::Iinc, true, Operand::Iinc { index: 300, value: 1000 }), "iinc_w        300, 1000");
+    }
+
+    #[test]
+    fn instruction_args_switches() {
+        let tableswitch = Operand::TableSwitch { default: 77, low: 1, high: 3, targets: vec![60, 65, 71] };
+        assert_eq!(instruction_line(Opcode::Tableswitch, false, tableswitch), [
+            "tableswitch   { // 1 to 3",
+            "                       1: 60",
+            "                       2: 65",
+            "                       3: 71",
+            "                 default: 77",
+            "            }",
+        ].join("\n"));
+        let lookupswitch = Operand::LookupSwitch { default: 119, pairs: vec![(10, 108), (1000, 113)] };
+        assert_eq!(instruction_line(Opcode::Lookupswitch, false, lookupswitch), [
+            "lookupswitch  { // 2",
+            "                      10: 108",
+            "                    1000: 113",
+            "                 default: 119",
+            "            }",
+        ].join("\n"));
+    }
+
+    fn class_file(cp_info: Vec<CpInfo<'_>>) -> ClassFile<'_> {
+        ClassFile {
+            magic: 0xCAFEBABE,
+            minor_version: 0,
+            major_version: 61,
+            constant_pool_count: cp_info.len() as u16,
+            cp_info,
+            access_flags: 0x21,
+            this_class: 0,
+            super_class: 2,
+            interfaces_count: 0,
+            interfaces: vec![],
+            fields_count: 0,
+            fields: vec![],
+            methods_count: 0,
+            methods: vec![],
+            attributes_count: 0,
+            attributes: vec![]
+        }
+    }
+
+    #[test]
+    fn local_variable_annotations() {
+        let names = ["count", "I", "names", "Ljava/util/List;", "Ljava/util/List<Ljava/lang/String;>;"].map(String::from);
+        let class_file = class_file(cp_info(&names));
+        let code_attributes = [
+            AttributeInfo::LocalVariableTable {
+                attribute_name_index: 0,
+                attribute_length: 22,
+                local_variable_table_length: 2,
+                local_variable_table: vec![
+                    LocalVariableTableItem { start_pc: 1, length: 5, name_index: 7, descriptor_index: 8, index: 1 },
+                    LocalVariableTableItem { start_pc: 0, length: 6, name_index: 9, descriptor_index: 10, index: 2 },
+                ]
+            },
+            AttributeInfo::LocalVariableTypeTable {
+                attribute_name_index: 0,
+                attribute_length: 12,
+                local_variable_type_table_length: 1,
+                local_variable_type_table: vec![LocalVariableTypeTableItem { start_pc: 0, length: 6, name_index: 9, signature_index: 11, index: 2 }]
+            },
+        ];
+        let annotation = |opcode: Opcode, pc: u32| {
+            let instruction = Instruction { opcode, wide: false, operand: Operand::None };
+            local_variable_annotation(&instruction, pc, pc + 1, &code_attributes, &class_file).unwrap()
+        };
+        // The variable is live after the store that initializes it
+        assert_eq!(annotation(Opcode::Istore1, 0).unwrap(), "int count");
+        assert_eq!(annotation(Opcode::Iload1, 5).unwrap(), "int count");
+        assert_eq!(annotation(Opcode::Iload1, 6), None);
+        assert_eq!(annotation(Opcode::Aload2, 2).unwrap(), "java.util.List<java.lang.String> names");
+        assert_eq!(annotation(Opcode::Iconst0, 2), None);
+    }
+
+    #[test]
+    fn unknown_attribute_hex_dump() {
+        let names = [String::from("LineNumberTablx")];
+        let class_file = class_file(cp_info(&names));
+        let info = [
+            0x00, 0x08, 0x00, 0x00, 0x00, 0x04, 0x00, 0x05, 0x00, 0x05, 0x00, 0x0A, 0x00, 0x06, 0x00, 0x12,
+            0x00, 0x07, 0x00, 0x19, 0x00, 0x08, 0x00, 0x20, 0x00, 0x09, 0x00, 0x50, 0x00, 0x0A, 0x00, 0x7A,
+            0x00, 0x0B,
+        ];
+        let unknown = AttributeInfo::Unknown { attribute_name_index: 7, attribute_length: 34, info: Cow::Borrowed(&info) };
+        assert_eq!(non_standard_attribute(&unknown, 6, &class_file), [
+            "        LineNumberTablx: length = 0x22 (unknown attribute)",
+            "         00 08 00 00 00 04 00 05 00 05 00 0A 00 06 00 12",
+            "         00 07 00 19 00 08 00 20 00 09 00 50 00 0A 00 7A",
+            "         00 0B",
+        ].join("\n"));
+    }
+}
@@ -1,58 +1,515 @@
-use crate::parser::ast::{ClassFile, CpInfo};
-use crate::parser::parser_helper::{get_constant_class_name, get_name, get_name_quoted, get_type};
+use crate::parser::ast::{BootstrapCallSite, ClassFile, CpInfo, ReferenceKind};
+use crate::parser::descriptor::parse_method_descriptor;
+use crate::parser::error::ClassFormatError;
+use crate::parser::modified_utf8::decode_modified_utf8_utf16;
+use crate::parser::parser_helper::{get_constant_class_name, get_constant_utf8, get_name, get_name_quoted, get_type, resolve_bootstrap_method};
 
-/// Read a MethodRef from the constant pool and return a string representing
-/// the qualified name of the method along with the arguments. Examples of
-/// output:
-///   java/lang/Object."<init>":()V
-///   java/lang/System.out:Ljava/io/PrintStream;
-pub fn get_constant_method_ref_description(cp_index: usize, class_file: &ClassFile) -> String {
-    match &class_file.cp_info[cp_index] {
-        CpInfo::ConstantMethodref { tag: _tag, class_index, name_and_type_index } => {
-            let class_name = get_constant_class_name(class_index.clone(), &class_file.cp_info);
-            let method_name = get_name_quoted(get_name(name_and_type_index.clone(), &class_file.cp_info));
-            let method_type = get_type(name_and_type_index.clone(), &class_file.cp_info);
-            format!("{}.{}:{}", class_name, method_name, method_type)
+/// Placeholder of a dynamic argument in a StringConcatFactory recipe.
+const CONCAT_ARGUMENT_TAG: char = '\u{1}';
+/// Placeholder of a constant in a StringConcatFactory recipe, the constants
+/// follow the recipe in the bootstrap arguments.
+const CONCAT_CONSTANT_TAG: char = '\u{2}';
+
+/// Get the description of a constant loaded by `ldc`, `ldc_w` or `ldc2_w`.
+/// Ex.: String Hello, World
+///      class "[I"
+///      long 42l
+pub fn get_ldc_description(cp_index: usize, class_file: &ClassFile) -> String {
+    let cp_info = &class_file.cp_info;
+    match &cp_info[cp_index] {
+        CpInfo::ConstantClass { tag: _tag, name_index } => {
+            format!("class {}", get_name_quoted(get_constant_utf8(*name_index, cp_info)))
+        },
+        CpInfo::ConstantString { tag: _tag, string_index} => {
+            let str = get_ldc_description(*string_index as usize, class_file);
+            format!("String {}", str)
+        },
+        CpInfo::ConstantUtf8 { .. } => get_constant_utf8_description(cp_index as u16, cp_info),
+        CpInfo::ConstantInteger { .. } |
+        CpInfo::ConstantFloat { .. } |
+        CpInfo::ConstantLong { .. } |
+        CpInfo::ConstantDouble { .. } => get_constant_value_description(cp_index, cp_info),
+        CpInfo::ConstantMethodHandle { .. } => {
+            format!("MethodHandle {}", get_constant_method_handle_description(cp_index, cp_info))
+        },
+        CpInfo::ConstantMethodType { tag: _tag, descriptor_index } => {
+            format!("MethodType {}", get_constant_utf8(*descriptor_index, cp_info))
+        },
+        CpInfo::ConstantDynamic { tag: _tag, bootstrap_method_attr_index, name_and_type_index } => {
+            let name = get_name_quoted(get_name(*name_and_type_index, cp_info));
+            let typename = get_type(*name_and_type_index, cp_info);
+            format!("Dynamic #{}:{}:{}", bootstrap_method_attr_index, name, typename)
         },
-        _ => panic!("Unexpected type at cp_index {}", cp_index)
+        cp_item => panic!("Unexpected type at cp_index {} {:?}", cp_index, cp_item)
     }
 }
 
-pub fn get_constant_field_ref_description(cp_index: usize, class_file: &ClassFile) -> String {
-    match &class_file.cp_info[cp_index] {
+/// Get the description of the constant referenced by an instruction. Members
+/// of the class being printed are not qualified with the class name.
+/// Ex.: Field java/lang/System.out:Ljava/io/PrintStream;
+///      Method "<init>":()V
+///      InvokeDynamic #0:run:()Ljava/lang/Runnable;
+pub fn get_instruction_constant_description(cp_index: usize, class_file: &ClassFile) -> String {
+    let cp_info = &class_file.cp_info;
+    match &cp_info[cp_index] {
         CpInfo::ConstantFieldref { tag: _tag, class_index, name_and_type_index } => {
-            let class_name = get_constant_class_name(class_index.clone(), &class_file.cp_info);
-            let field_name = get_name(name_and_type_index.clone(), &class_file.cp_info);
-            let field_type = get_type(name_and_type_index.clone(), &class_file.cp_info);
-            format!("{}.{}:{}", class_name, field_name, field_type)
+            format!("Field {}", get_member_description(*class_index, *name_and_type_index, class_file))
+        },
+        CpInfo::ConstantMethodref { tag: _tag, class_index, name_and_type_index } => {
+            format!("Method {}", get_member_description(*class_index, *name_and_type_index, class_file))
+        },
+        CpInfo::ConstantInterfaceMethodref { tag: _tag, class_index, name_and_type_index } => {
+            format!("InterfaceMethod {}", get_member_description(*class_index, *name_and_type_index, class_file))
+        },
+        CpInfo::ConstantInvokeDynamic { tag: _tag, bootstrap_method_attr_index, name_and_type_index } => {
+            let name = get_name_quoted(get_name(*name_and_type_index, cp_info));
+            let typename = get_type(*name_and_type_index, cp_info);
+            format!("InvokeDynamic #{}:{}:{}", bootstrap_method_attr_index, name, typename)
         },
-        _ => panic!("Unexpected type at cp_index {}", cp_index)
+        _ => get_ldc_description(cp_index, class_file)
     }
 }
 
-/// Get the description for the `ldc` opcode.
-/// Ex.: String Hello, World
-pub fn get_ldc_description(cp_index: usize, class_file: &ClassFile) -> String {
-    match &class_file.cp_info[cp_index] {
-        CpInfo::ConstantString { tag: _tag, string_index} => {
-            let str = get_ldc_description(string_index.clone() as usize, class_file);
-            format!("String {}", str)
+/// Describe what an invokedynamic call site does when its bootstrap method
+/// is a well-known one: lambdas and method references created by
+/// LambdaMetafactory and string concatenations made by StringConcatFactory.
+/// Ex.: Lambda java.lang.Runnable.run -> REF_invokeStatic Main.lambda$main$0:(Ljava/lang/String;)V, captures java.lang.String
+///      Method reference java.util.function.Supplier.get -> REF_newInvokeSpecial java/util/ArrayList."<init>":()V
+///      Concat "Hello " + arg0 + "!"
+pub fn get_invoke_dynamic_annotation(cp_index: usize, class_file: &ClassFile) -> Result<Option<String>, ClassFormatError> {
+    let call_site = resolve_bootstrap_method(cp_index as u16, class_file)?;
+    let cp_info = &class_file.cp_info;
+    let (class_name, method_name) = match get_method_handle_member(call_site.bootstrap_method.bootstrap_method_ref as usize, cp_info) {
+        Some(member) => member,
+        None => return Ok(None)
+    };
+    let annotation = match (class_name.as_str(), method_name.as_str()) {
+        ("java/lang/invoke/LambdaMetafactory", "metafactory" | "altMetafactory") => get_lambda_description(&call_site, cp_info)?,
+        ("java/lang/invoke/StringConcatFactory", "makeConcatWithConstants") => get_string_concat_description(&call_site, true, cp_info),
+        ("java/lang/invoke/StringConcatFactory", "makeConcat") => get_string_concat_description(&call_site, false, cp_info),
+        _ => None
+    };
+    Ok(annotation)
+}
+
+/// The functional interface method comes from the call site, the
+/// implementation method is the second static argument and the captured
+/// values are the call site arguments.
+fn get_lambda_description(call_site: &BootstrapCallSite, cp_info: &[CpInfo]) -> Result<Option<String>, ClassFormatError> {
+    let implementation = match call_site.bootstrap_method.bootstrap_arguments.get(1) {
+        Some(implementation) => *implementation as usize,
+        None => return Ok(None)
+    };
+    let (_class_name, implementation_name) = match get_method_handle_member(implementation, cp_info) {
+        Some(member) => member,
+        None => return Ok(None)
+    };
+    let descriptor = parse_method_descriptor(&call_site.descriptor).map_err(|e| e.within("InvokeDynamic"))?;
+    let kind = if implementation_name.starts_with("lambda$") { "Lambda" } else { "Method reference" };
+    let mut description = format!("{} {}.{} -> {}",
+        kind, descriptor.ret.str_java(), call_site.name, get_constant_method_handle_description(implementation, cp_info));
+    if !descriptor.params.is_empty() {
+        let captures: Vec<String> = descriptor.params.iter().map(|p| p.str_java()).collect();
+        description.push_str(&format!(", captures {}", captures.join(", ")));
+    }
+    Ok(Some(description))
+}
+
+/// Rebuild the Java expression of a string concatenation. Dynamic arguments
+/// are named after their position in the call site descriptor. makeConcat has
+/// no recipe, all the arguments are concatenated.
+fn get_string_concat_description(call_site: &BootstrapCallSite, with_constants: bool, cp_info: &[CpInfo]) -> Option<String> {
+    let arguments_count = parse_method_descriptor(&call_site.descriptor).ok()?.params.len();
+    let recipe = if with_constants {
+        match &cp_info[*call_site.bootstrap_method.bootstrap_arguments.first()? as usize] {
+            CpInfo::ConstantString { tag: _tag, string_index } => get_constant_utf8(*string_index, cp_info),
+            _ => return None
+        }
+    } else {
+        CONCAT_ARGUMENT_TAG.to_string().repeat(arguments_count)
+    };
+    let mut constants = call_site.bootstrap_method.bootstrap_arguments.iter().skip(1);
+    let mut terms: Vec<String> = Vec::new();
+    let mut literal = String::new();
+    let mut argument = 0;
+    for c in recipe.chars() {
+        if c != CONCAT_ARGUMENT_TAG && c != CONCAT_CONSTANT_TAG {
+            literal.push(c);
+            continue;
+        }
+        if !literal.is_empty() {
+            terms.push(java_string_literal(&literal));
+            literal.clear();
+        }
+        if c == CONCAT_ARGUMENT_TAG {
+            terms.push(format!("arg{}", argument));
+            argument += 1;
+        } else {
+            terms.push(get_concat_constant_description(*constants.next()? as usize, cp_info));
+        }
+    }
+    if !literal.is_empty() || terms.is_empty() {
+        terms.push(java_string_literal(&literal));
+    }
+    Some(format!("Concat {}", terms.join(" + ")))
+}
+
+fn get_concat_constant_description(cp_index: usize, cp_info: &[CpInfo]) -> String {
+    match &cp_info[cp_index] {
+        CpInfo::ConstantString { tag: _tag, string_index } => java_string_literal(&get_constant_utf8(*string_index, cp_info)),
+        _ => get_bootstrap_argument_description(cp_index, cp_info)
+    }
+}
+
+/// Class and name of the method referenced by a CONSTANT_MethodHandle, None
+/// when it references a field.
+fn get_method_handle_member(cp_index: usize, cp_info: &[CpInfo]) -> Option<(String, String)> {
+    let reference_index = match &cp_info[cp_index] {
+        CpInfo::ConstantMethodHandle { tag: _tag, reference_kind: _reference_kind, reference_index } => *reference_index,
+        _ => return None
+    };
+    match &cp_info[reference_index as usize] {
+        CpInfo::ConstantMethodref { tag: _tag, class_index, name_and_type_index } |
+        CpInfo::ConstantInterfaceMethodref { tag: _tag, class_index, name_and_type_index } => {
+            Some((get_constant_class_name(*class_index, cp_info), get_name(*name_and_type_index, cp_info)))
         },
-        CpInfo::ConstantUtf8 { tag: _tag, length: _length, bytes: _bytes, bytes_str} => {
-            bytes_str.clone()
+        _ => None
+    }
+}
+
+/// Quote a string as a Java string literal.
+/// Ex.: "Hello \"world\"\n"
+pub fn java_string_literal(str: &str) -> String {
+    let mut acc = String::with_capacity(str.len() + 2);
+    acc.push('"');
+    for c in str.chars() {
+        match c {
+            '"' => acc.push_str("\\\""),
+            '\\' => acc.push_str("\\\\"),
+            '\n' => acc.push_str("\\n"),
+            '\t' => acc.push_str("\\t"),
+            '\r' => acc.push_str("\\r"),
+            '\u{8}' => acc.push_str("\\b"),
+            '\u{c}' => acc.push_str("\\f"),
+            c if c.is_control() => acc.push_str(&format!("\\u{:04x}", c as u32)),
+            c => acc.push(c)
+        }
+    }
+    acc.push('"');
+    acc
+}
+
+/// Text of a CONSTANT_Utf8 escaped like javap: quotes, backslashes and
+/// control characters are escaped. Unpaired surrogates are printed as
+/// \uXXXX escapes too.
+/// Ex.: a\tb\"c\'\u0001
+pub fn get_constant_utf8_description(cp_index: u16, cp_info: &[CpInfo]) -> String {
+    let (bytes, bytes_str) = match &cp_info[cp_index as usize] {
+        CpInfo::ConstantUtf8 { tag: _tag, length: _length, bytes, bytes_str } => (bytes, bytes_str),
+        cp_item => panic!("Expected ConstantUtf8 at cp_index {} {:?}", cp_index, cp_item)
+    };
+    let utf16 = decode_modified_utf8_utf16(bytes).unwrap_or_else(|_| bytes_str.encode_utf16().collect());
+    let mut acc = String::with_capacity(bytes.len());
+    for c in char::decode_utf16(utf16) {
+        match c {
+            Ok('\t') => acc.push_str("\\t"),
+            Ok('\n') => acc.push_str("\\n"),
+            Ok('\r') => acc.push_str("\\r"),
+            Ok('\u{8}') => acc.push_str("\\b"),
+            Ok('\u{c}') => acc.push_str("\\f"),
+            Ok('"') => acc.push_str("\\\""),
+            Ok('\'') => acc.push_str("\\'"),
+            Ok('\\') => acc.push_str("\\\\"),
+            Ok(c) if c.is_control() => acc.push_str(&format!("\\u{:04x}", c as u32)),
+            Ok(c) => acc.push(c),
+            Err(e) => acc.push_str(&format!("\\u{:04x}", e.unpaired_surrogate()))
+        }
+    }
+    acc
+}
+
+/// Member name and descriptor, qualified with the class name unless the
+/// member belongs to the class being printed.
+fn get_member_description(class_index: u16, name_and_type_index: u16, class_file: &ClassFile) -> String {
+    let cp_info = &class_file.cp_info;
+    let class_name = get_constant_class_name(class_index, cp_info);
+    let member_name = get_name_quoted(get_name(name_and_type_index, cp_info));
+    let member_type = get_type(name_and_type_index, cp_info);
+    if class_name == get_constant_class_name(class_file.this_class, cp_info) {
+        format!("{}:{}", member_name, member_type)
+    } else {
+        format!("{}.{}:{}", get_name_quoted(class_name), member_name, member_type)
+    }
+}
+
+/// Get the description of a ConstantValue attribute. boolean, byte, char and
+/// short constants are stored as int.
+/// Ex.: int 42, String hello
+pub fn get_constant_value_description(cp_index: usize, cp_info: &[CpInfo]) -> String {
+    match &cp_info[cp_index] {
+        CpInfo::ConstantInteger { tag: _tag, bytes } => format!("int {}", CpInfo::integer_value(*bytes)),
+        CpInfo::ConstantFloat { tag: _tag, bytes } => format!("float {}f", java_float_to_string(CpInfo::float_value(*bytes))),
+        CpInfo::ConstantLong { tag: _tag, high_bytes, low_bytes } => format!("long {}l", CpInfo::long_value(*high_bytes, *low_bytes)),
+        CpInfo::ConstantDouble { tag: _tag, high_bytes, low_bytes } => format!("double {}d", java_double_to_string(CpInfo::double_value(*high_bytes, *low_bytes))),
+        CpInfo::ConstantString { tag: _tag, string_index } => format!("String {}", get_constant_utf8_description(*string_index, cp_info)),
+        cp_item => panic!("Unexpected type at cp_index {} {:?}", cp_index, cp_item)
+    }
+}
+
+/// Get the description of a static argument of a bootstrap method, the
+/// constant value without its type.
+/// Ex.: (Ljava/lang/Object;)V
+///      REF_invokeStatic Main.lambda$main$0:()V
+///      42l
+pub fn get_bootstrap_argument_description(cp_index: usize, cp_info: &[CpInfo]) -> String {
+    match &cp_info[cp_index] {
+        CpInfo::ConstantClass { tag: _tag, name_index } => get_name_quoted(get_constant_utf8(*name_index, cp_info)),
+        CpInfo::ConstantString { tag: _tag, string_index } => get_constant_utf8_description(*string_index, cp_info),
+        CpInfo::ConstantInteger { tag: _tag, bytes } => CpInfo::integer_value(*bytes).to_string(),
+        CpInfo::ConstantFloat { tag: _tag, bytes } => format!("{}f", java_float_to_string(CpInfo::float_value(*bytes))),
+        CpInfo::ConstantLong { tag: _tag, high_bytes, low_bytes } => format!("{}l", CpInfo::long_value(*high_bytes, *low_bytes)),
+        CpInfo::ConstantDouble { tag: _tag, high_bytes, low_bytes } => format!("{}d", java_double_to_string(CpInfo::double_value(*high_bytes, *low_bytes))),
+        CpInfo::ConstantMethodHandle { .. } => get_constant_method_handle_description(cp_index, cp_info),
+        CpInfo::ConstantMethodType { tag: _tag, descriptor_index } => get_constant_utf8(*descriptor_index, cp_info),
+        CpInfo::ConstantDynamic { tag: _tag, bootstrap_method_attr_index, name_and_type_index } => {
+            let name = get_name_quoted(get_name(*name_and_type_index, cp_info));
+            let typename = get_type(*name_and_type_index, cp_info);
+            format!("#{}:{}:{}", bootstrap_method_attr_index, name, typename)
         },
-        _ => panic!("TODO: get_ldc_description cp_index {}", cp_index)
+        cp_item => panic!("Unexpected type at cp_index {} {:?}", cp_index, cp_item)
     }
 }
 
-/// Get the description for the `getstatic` opcode.
-/// Ex.: Field java/lang/System.out:Ljava/io/PrintStream;
-pub fn get_static_description(cp_index: usize, class_file: &ClassFile) -> String {
-    match &class_file.cp_info[cp_index] {
-        CpInfo::ConstantFieldref { tag: _tag, class_index: _class_index, name_and_type_index: _name_and_type_index } => {
-            let str = get_constant_field_ref_description(cp_index, class_file);
-            format!("Field {}", str)
+/// Get the description of a constant element of an annotation, the tag
+/// gives the type of the element. boolean, byte, char and short elements are
+/// stored as int.
+/// Ex.: (byte) 1, 'q', 3l, "text"
+pub fn get_element_value_const_description(tag: u8, cp_index: u16, cp_info: &[CpInfo]) -> String {
+    match (tag, &cp_info[cp_index as usize]) {
+        (b'B', CpInfo::ConstantInteger { tag: _tag, bytes }) => format!("(byte) {}", CpInfo::integer_value(*bytes)),
+        (b'S', CpInfo::ConstantInteger { tag: _tag, bytes }) => format!("(short) {}", CpInfo::integer_value(*bytes)),
+        (b'C', CpInfo::ConstantInteger { tag: _tag, bytes }) => {
+            format!("'{}'", char::from_u32(CpInfo::integer_value(*bytes) as u32).unwrap_or(char::REPLACEMENT_CHARACTER))
+        },
+        (b'Z', CpInfo::ConstantInteger { tag: _tag, bytes }) => String::from(if *bytes == 0 { "false" } else { "true" }),
+        (b'I', CpInfo::ConstantInteger { tag: _tag, bytes }) => CpInfo::integer_value(*bytes).to_string(),
+        (b'J', CpInfo::ConstantLong { tag: _tag, high_bytes, low_bytes }) => format!("{}l", CpInfo::long_value(*high_bytes, *low_bytes)),
+        (b'F', CpInfo::ConstantFloat { tag: _tag, bytes }) => format!("{}f", java_float_to_string(CpInfo::float_value(*bytes))),
+        (b'D', CpInfo::ConstantDouble { tag: _tag, high_bytes, low_bytes }) => format!("{}d", java_double_to_string(CpInfo::double_value(*high_bytes, *low_bytes))),
+        (b's', CpInfo::ConstantUtf8 { .. }) => format!("\"{}\"", get_constant_utf8_description(cp_index, cp_info)),
+        (tag, cp_item) => panic!("Unexpected type for element value tag {} at cp_index {} {:?}", tag as char, cp_index, cp_item)
+    }
+}
+
+/// Describe a Fieldref, Methodref or InterfaceMethodref as the qualified
+/// member name followed by its descriptor. Examples of output:
+///   java/lang/Object."<init>":()V
+///   java/util/List.size:()I
+pub fn get_constant_member_ref_description(cp_index: usize, cp_info: &[CpInfo]) -> String {
+    match &cp_info[cp_index] {
+        CpInfo::ConstantFieldref { tag: _tag, class_index, name_and_type_index } => {
+            let class_name = get_name_quoted(get_constant_class_name(*class_index, cp_info));
+            let field_name = get_name_quoted(get_name(*name_and_type_index, cp_info));
+            let field_type = get_type(*name_and_type_index, cp_info);
+            format!("{}.{}:{}", class_name, field_name, field_type)
         },
-        cp_info => panic!("Unexpected type at cp_index {} {:?}", cp_index, cp_info)
+        CpInfo::ConstantMethodref { tag: _tag, class_index, name_and_type_index } |
+        CpInfo::ConstantInterfaceMethodref { tag: _tag, class_index, name_and_type_index } => {
+            let class_name = get_name_quoted(get_constant_class_name(*class_index, cp_info));
+            let method_name = get_name_quoted(get_name(*name_and_type_index, cp_info));
+            let method_type = get_type(*name_and_type_index, cp_info);
+            format!("{}.{}:{}", class_name, method_name, method_type)
+        },
+        cp_item => panic!("Unexpected type at cp_index {} {:?}", cp_index, cp_item)
+    }
+}
+
+/// Describe a MethodHandle as its reference kind followed by the referenced
+/// member.
+/// Ex.: REF_invokeStatic java/lang/invoke/LambdaMetafactory.metafactory:(...)Ljava/lang/invoke/CallSite;
+pub fn get_constant_method_handle_description(cp_index: usize, cp_info: &[CpInfo]) -> String {
+    match &cp_info[cp_index] {
+        CpInfo::ConstantMethodHandle { tag: _tag, reference_kind, reference_index } => {
+            let kind = match ReferenceKind::from_value(*reference_kind) {
+                Some(kind) => String::from(kind.to_str()),
+                None => format!("REF_???({})", reference_kind)
+            };
+            let member = get_constant_member_ref_description(*reference_index as usize, cp_info);
+            format!("{} {}", kind, member)
+        },
+        cp_item => panic!("Unexpected type at cp_index {} {:?}", cp_index, cp_item)
+    }
+}
+
+/// Format a float the way Java's `Float.toString` does.
+/// Ex.: 1.5, 1.0E10, NaN, -Infinity
+pub fn java_float_to_string(value: f32) -> String {
+    if value.is_nan() || value.is_infinite() || value == 0.0 {
+        java_double_to_string(value as f64)
+    } else {
+        let scientific = format!("{:e}", value);
+        // Java never picks a single digit, it uses the closest two digits
+        // decimal instead. Ex.: 4.9E-324 rather than 5.0E-324
+        let scientific = if scientific.contains('.') { scientific } else { format!("{:.1e}", value) };
+        java_scientific_to_string(&scientific)
+    }
+}
+
+/// Format a double the way Java's `Double.toString` does: plain decimal
+/// notation for magnitudes in [10^-3, 10^7) and computerized scientific
+/// notation otherwise.
+/// Ex.: 2.5, 0.001, 1.0E7, 4.9E-324
+pub fn java_double_to_string(value: f64) -> String {
+    if value.is_nan() {
+        String::from("NaN")
+    } else if value.is_infinite() {
+        String::from(if value > 0.0 { "Infinity" } else { "-Infinity" })
+    } else if value == 0.0 {
+        String::from(if value.is_sign_negative() { "-0.0" } else { "0.0" })
+    } else {
+        let scientific = format!("{:e}", value);
+        let scientific = if scientific.contains('.') { scientific } else { format!("{:.1e}", value) };
+        java_scientific_to_string(&scientific)
     }
-}
\ No newline at end of file
+}
+
+/// Convert the shortest round-trip representation produced by Rust's `{:e}`
+/// formatter (ex.: `-1.25e-5`) into Java's notation.
+fn java_scientific_to_string(scientific: &str) -> String {
+    let (mantissa, exponent) = scientific.split_once('e').expect("Expected scientific notation");
+    let exponent: i32 = exponent.parse().expect("Expected exponent");
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa)
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let digits: &str = match digits.trim_end_matches('0') {
+        "" => "0",
+        trimmed => trimmed
+    };
+    if (-3..7).contains(&exponent) {
+        if exponent >= 0 {
+            let int_len = exponent as usize + 1;
+            if digits.len() > int_len {
+                format!("{}{}.{}", sign, &digits[..int_len], &digits[int_len..])
+            } else {
+                format!("{}{:0<width$}.0", sign, digits, width = int_len)
+            }
+        } else {
+            let zeros = "0".repeat((-exponent - 1) as usize);
+            format!("{}0.{}{}", sign, zeros, digits)
+        }
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let rest = if rest.is_empty() { "0" } else { rest };
+        format!("{}{}.{}E{}", sign, first, rest, exponent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use crate::parser::ast::{AttributeInfo, BootstrapMethod};
+    use crate::parser::error::ClassFormatErrorKind;
+    use super::*;
+
+    fn utf8(str: &str) -> CpInfo<'_> {
+        CpInfo::ConstantUtf8 { tag: 1, length: str.len() as u16, bytes: Cow::Borrowed(str.as_bytes()), bytes_str: Cow::Borrowed(str) }
+    }
+
+    fn method_handle(reference_index: u16) -> CpInfo<'static> {
+        CpInfo::ConstantMethodHandle { tag: 15, reference_kind: 6, reference_index }
+    }
+
+    fn methodref(class_index: u16, name_and_type_index: u16) -> CpInfo<'static> {
+        CpInfo::ConstantMethodref { tag: 10, class_index, name_and_type_index }
+    }
+
+    fn name_and_type(name_index: u16, descriptor_index: u16) -> CpInfo<'static> {
+        CpInfo::ConstantNameAndType { tag: 12, name_index, descriptor_index }
+    }
+
+    /// Class with a lambda `() -> {}` (#1) and the concatenation
+    /// `"n=" + n + ", s=" + s` (#18), the descriptors of the bootstrap methods
+    /// are left out.
+    fn class_file(bootstrap_methods: Vec<BootstrapMethod>) -> ClassFile<'static> {
+        let cp_info = vec![
+            CpInfo::Unusable,
+            CpInfo::ConstantInvokeDynamic { tag: 18, bootstrap_method_attr_index: 0, name_and_type_index: 2 },
+            name_and_type(3, 4),
+            utf8("run"),
+            utf8("()Ljava/lang/Runnable;"),
+            method_handle(6),
+            methodref(7, 8),
+            CpInfo::ConstantClass { tag: 7, name_index: 9 },
+            name_and_type(10, 11),
+            utf8("java/lang/invoke/LambdaMetafactory"),
+            utf8("metafactory"),
+            utf8("()V"),
+            method_handle(13),
+            methodref(14, 15),
+            CpInfo::ConstantClass { tag: 7, name_index: 16 },
+            name_and_type(17, 11),
+            utf8("Ann"),
+            utf8("lambda$m$0"),
+            CpInfo::ConstantInvokeDynamic { tag: 18, bootstrap_method_attr_index: 1, name_and_type_index: 19 },
+            name_and_type(20, 21),
+            utf8("makeConcatWithConstants"),
+            utf8("(ILjava/lang/String;)Ljava/lang/String;"),
+            method_handle(23),
+            methodref(24, 25),
+            CpInfo::ConstantClass { tag: 7, name_index: 26 },
+            name_and_type(20, 11),
+            utf8("java/lang/invoke/StringConcatFactory"),
+            CpInfo::ConstantString { tag: 8, string_index: 28 },
+            utf8("n=\u{1}, s=\u{1}"),
+            CpInfo::ConstantMethodType { tag: 16, descriptor_index: 11 },
+            utf8("BootstrapMethods"),
+        ];
+        let bootstrap_methods = AttributeInfo::BootstrapMethods {
+            attribute_name_index: 30,
+            attribute_length: 0,
+            num_bootstrap_methods: bootstrap_methods.len() as u16,
+            bootstrap_methods
+        };
+        ClassFile {
+            magic: 0xCAFEBABE,
+            minor_version: 0,
+            major_version: 61,
+            constant_pool_count: cp_info.len() as u16,
+            cp_info,
+            access_flags: 0x21,
+            this_class: 14,
+            super_class: 0,
+            interfaces_count: 0,
+            interfaces: vec![],
+            fields_count: 0,
+            fields: vec![],
+            methods_count: 0,
+            methods: vec![],
+            attributes_count: 1,
+            attributes: vec![bootstrap_methods]
+        }
+    }
+
+    fn bootstrap_methods() -> Vec<BootstrapMethod> {
+        vec![
+            BootstrapMethod { bootstrap_method_ref: 5, num_bootstrap_arguments: 3, bootstrap_arguments: vec![29, 12, 29] },
+            BootstrapMethod { bootstrap_method_ref: 22, num_bootstrap_arguments: 1, bootstrap_arguments: vec![27] },
+        ]
+    }
+
+    #[test]
+    fn invoke_dynamic_annotation() {
+        let class_file = class_file(bootstrap_methods());
+        assert_eq!(get_invoke_dynamic_annotation(1, &class_file).unwrap().unwrap(), "Lambda java.lang.Runnable.run -> REF_invokeStatic Ann.lambda$m$0:()V");
+        assert_eq!(get_invoke_dynamic_annotation(18, &class_file).unwrap().unwrap(), "Concat \"n=\" + arg0 + \", s=\" + arg1");
+    }
+
+    #[test]
+    fn invoke_dynamic_annotation_without_bootstrap_method() {
+        let class_file = class_file(bootstrap_methods()[..1].to_vec());
+        let err = get_invoke_dynamic_annotation(18, &class_file).unwrap_err();
+        assert_eq!(err.kind, ClassFormatErrorKind::InvalidBootstrapMethodIndex(1));
+    }
+}